@@ -2,11 +2,18 @@ import * as t from 'io-ts';
 import {currencyType, localDateType} from "./codecs";
 import {ExtraRequestProps, request} from "./common";
 import config from "../config";
+import {frequencyType} from "../models/frequency";
 
-const DataPoint = t.type({
-    balance: currencyType,
-    date: localDateType,
-});
+const DataPoint = t.intersection([
+    t.type({
+        balance: currencyType,
+        date: localDateType,
+        timePoint: t.string,
+    }),
+    t.partial({
+        series: t.string,
+    }),
+]);
 
 const responseType = t.array(DataPoint);
 
@@ -14,6 +21,9 @@ const filterType = t.partial({
     from: t.string,
     to: t.string,
     accounts: t.array(t.string),
+    accountGroups: t.array(t.string),
+    freq: frequencyType,
+    seriesBy: t.union([t.literal('none'), t.literal('selection')]),
 });
 
 
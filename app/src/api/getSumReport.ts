@@ -5,10 +5,21 @@ import {ExtraRequestProps, request} from "./common";
 import config from "../config";
 import {frequencyType} from "../models/frequency";
 
-const dataPointType = t.type({
-    total: currencyType,
-    timePoint: codec.NonEmptyString,
-});
+const dataPointType = t.intersection([
+    t.type({
+        total: currencyType,
+        timePoint: codec.NonEmptyString,
+        start: t.string,
+        end: t.string,
+    }),
+    t.partial({
+        series: t.string,
+        previous: currencyType,
+        lastYear: currencyType,
+        rollingAverage: currencyType,
+        yearToDate: currencyType,
+    }),
+]);
 
 const responseType = t.array(dataPointType);
 
@@ -16,6 +27,14 @@ const optionalFilterType = t.partial({
     from: t.string,
     to: t.string,
     accounts: t.array(t.string),
+    accountGroups: t.array(t.string),
+    seriesBy: t.union([t.literal('none'), t.literal('selection')]),
+    extras: t.partial({
+        previous: t.boolean,
+        lastYear: t.boolean,
+        rollingAverage: t.number,
+        yearToDate: t.boolean,
+    }),
 });
 
 const mandatoryFilterType = t.type({
@@ -27,7 +46,7 @@ const filterType = t.intersection([optionalFilterType, mandatoryFilterType]);
 export type Filter = t.TypeOf<typeof filterType>;
 export type Response = t.TypeOf<typeof responseType>;
 
-export function getSumReport({accounts, freq, from, to, ...extraProps}: Filter & ExtraRequestProps) {
+export function getSumReport({accounts, accountGroups, seriesBy, freq, from, to, ...extraProps}: Filter & ExtraRequestProps) {
     return request({
         url: `${config.baseUrl}/reports/sum`,
         method: 'post',
@@ -37,6 +56,8 @@ export function getSumReport({accounts, freq, from, to, ...extraProps}: Filter &
             to,
             freq,
             accounts,
+            accountGroups,
+            seriesBy,
         },
         outputType: responseType,
         ...extraProps
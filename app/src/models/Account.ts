@@ -5,7 +5,7 @@ import {currencyType} from "../api/codecs";
 export const AccountType = t.type({
     name: codec.NonEmptyString,
     balance: currencyType,
-    lastTransDate: codec.DateFromISOString,
+    lastTransDate: t.union([codec.DateFromISOString, t.null]),
 });
 
 export const AccountArrayType = t.array(AccountType);
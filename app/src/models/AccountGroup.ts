@@ -1,10 +1,35 @@
 import * as t from 'io-ts';
 
-export const accountGroupType = t.type({
-    groupName: t.string,
-    accounts: t.array(t.string),
+export const accountGroupMemberType = t.type({
+    type: t.union([t.literal('account'), t.literal('group')]),
+    name: t.string,
+    weight: t.number,
 });
 
+export const accountGroupRuleType = t.type({
+    type: t.union([
+        t.literal('prefix'),
+        t.literal('regex'),
+        t.literal('type'),
+        t.literal('tag'),
+        t.literal('currency'),
+    ]),
+    value: t.string,
+});
+
+export const accountGroupType = t.intersection([
+    t.type({
+        groupName: t.string,
+        accounts: t.array(t.string),
+    }),
+    t.partial({
+        members: t.union([t.array(accountGroupMemberType), t.null]),
+        rules: t.union([t.array(accountGroupRuleType), t.null]),
+    }),
+]);
+
 export const accountGroupArrayType = t.array(accountGroupType);
 
-export type AccountGroup = t.TypeOf<typeof accountGroupType>;
\ No newline at end of file
+export type AccountGroupMember = t.TypeOf<typeof accountGroupMemberType>;
+export type AccountGroupRule = t.TypeOf<typeof accountGroupRuleType>;
+export type AccountGroup = t.TypeOf<typeof accountGroupType>;
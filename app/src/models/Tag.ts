@@ -6,7 +6,10 @@ export const tagType = t.type({
     tag: codec.NonEmptyString,
     numTx: t.number,
     total: currencyType,
-    lastUpdated: zonedDateTimeType
+    lastUpdated: t.union([zonedDateTimeType, t.null]),
+    description: t.union([t.string, t.null]),
+    colour: t.union([t.string, t.null]),
+    archived: t.boolean,
 });
 
 export const tagArrayType = t.array(tagType);
@@ -1,6 +1,6 @@
 import {createEnumType} from "../utils/codecs";
 
-export const allFrequencies = ['Monthly', 'Weekly', 'Daily', 'Yearly'] as const;
+export const allFrequencies = ['Monthly', 'Weekly', 'Daily', 'Yearly', 'Quarterly', 'IsoWeekly', 'Fortnightly', 'FiscalYearly'] as const;
 
 export type Frequency = typeof allFrequencies[number];
 
@@ -33,7 +33,7 @@ export default function TagListPage() {
             <td><Link to={`/transactions?tag=${encodeURIComponent(tag)}`}>{tag}</Link></td>
             <td>{numTx}</td>
             <td>{formatAsCurrency(total)}</td>
-            <td>{formatAsLocaleLocalDate(lastUpdated)}</td>
+            <td>{lastUpdated ? formatAsLocaleLocalDate(lastUpdated) : ""}</td>
         </tr>);
     }, [tags]);
 
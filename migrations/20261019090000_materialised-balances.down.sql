@@ -0,0 +1,20 @@
+-- Add down migration script here
+drop view daily_sum;
+create view daily_sum as
+    select account, sum(amount) as total, transDate
+    from account_transactions
+    group by account collate nocase, transDate;
+
+drop view accounts;
+create view accounts(name, balance, lastTransDate) as
+    select account, sum(amount), max(transDate)
+    from account_transactions
+    group by account collate nocase;
+
+drop trigger transactions_balance_replace;
+drop trigger transactions_balance_update;
+drop trigger transactions_balance_delete;
+drop trigger transactions_balance_insert;
+
+drop table account_daily_sums;
+drop table account_balances;
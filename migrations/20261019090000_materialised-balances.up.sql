@@ -0,0 +1,148 @@
+-- Add up migration script here
+
+-- Balance of each account, maintained by the triggers on transactions below
+create table account_balances
+(
+    account         text    not null primary key collate nocase,
+    balance         integer not null default 0,
+    numTransactions integer not null default 0
+);
+
+-- Daily sum of each account, maintained by the triggers on transactions below
+create table account_daily_sums
+(
+    account         text    not null collate nocase,
+    transDate       date    not null,
+    total           integer not null default 0,
+    numTransactions integer not null default 0,
+    primary key (account, transDate)
+);
+
+create index account_daily_sums_trans_date on account_daily_sums (transDate);
+
+insert into account_daily_sums (account, transDate, total, numTransactions)
+select account, transDate, sum(amount), count(*)
+from account_transactions
+group by account collate nocase, transDate;
+
+insert into account_balances (account, balance, numTransactions)
+select account, sum(total), sum(numTransactions)
+from account_daily_sums
+group by account;
+
+-- The triggers below add rows with upserts rather than "insert or ignore", because the conflict
+-- resolution of the outer "insert or replace into transactions" would override the latter.
+
+create trigger transactions_balance_insert
+    after insert
+    on transactions
+begin
+    insert into account_balances (account, balance, numTransactions)
+    values (trim(NEW.fromAccount), -NEW.amount, 1),
+           (trim(NEW.toAccount), NEW.amount, 1)
+    on conflict (account) do update
+        set balance         = balance + excluded.balance,
+            numTransactions = numTransactions + excluded.numTransactions;
+
+    insert into account_daily_sums (account, transDate, total, numTransactions)
+    values (trim(NEW.fromAccount), NEW.transDate, -NEW.amount, 1),
+           (trim(NEW.toAccount), NEW.transDate, NEW.amount, 1)
+    on conflict (account, transDate) do update
+        set total           = total + excluded.total,
+            numTransactions = numTransactions + excluded.numTransactions;
+end;
+
+create trigger transactions_balance_delete
+    after delete
+    on transactions
+begin
+    update account_balances
+    set balance = balance + OLD.amount, numTransactions = numTransactions - 1
+    where account = trim(OLD.fromAccount);
+
+    update account_balances
+    set balance = balance - OLD.amount, numTransactions = numTransactions - 1
+    where account = trim(OLD.toAccount);
+
+    delete from account_balances
+    where numTransactions <= 0 and account in (trim(OLD.fromAccount), trim(OLD.toAccount));
+
+    update account_daily_sums
+    set total = total + OLD.amount, numTransactions = numTransactions - 1
+    where account = trim(OLD.fromAccount) and transDate = OLD.transDate;
+
+    update account_daily_sums
+    set total = total - OLD.amount, numTransactions = numTransactions - 1
+    where account = trim(OLD.toAccount) and transDate = OLD.transDate;
+
+    delete from account_daily_sums
+    where numTransactions <= 0
+      and transDate = OLD.transDate
+      and account in (trim(OLD.fromAccount), trim(OLD.toAccount));
+end;
+
+-- An update takes the old row off and adds the new one
+create trigger transactions_balance_update
+    after update of fromAccount, toAccount, amount, transDate
+    on transactions
+begin
+    update account_balances
+    set balance = balance + OLD.amount, numTransactions = numTransactions - 1
+    where account = trim(OLD.fromAccount);
+
+    update account_balances
+    set balance = balance - OLD.amount, numTransactions = numTransactions - 1
+    where account = trim(OLD.toAccount);
+
+    update account_daily_sums
+    set total = total + OLD.amount, numTransactions = numTransactions - 1
+    where account = trim(OLD.fromAccount) and transDate = OLD.transDate;
+
+    update account_daily_sums
+    set total = total - OLD.amount, numTransactions = numTransactions - 1
+    where account = trim(OLD.toAccount) and transDate = OLD.transDate;
+
+    insert into account_balances (account, balance, numTransactions)
+    values (trim(NEW.fromAccount), -NEW.amount, 1),
+           (trim(NEW.toAccount), NEW.amount, 1)
+    on conflict (account) do update
+        set balance         = balance + excluded.balance,
+            numTransactions = numTransactions + excluded.numTransactions;
+
+    insert into account_daily_sums (account, transDate, total, numTransactions)
+    values (trim(NEW.fromAccount), NEW.transDate, -NEW.amount, 1),
+           (trim(NEW.toAccount), NEW.transDate, NEW.amount, 1)
+    on conflict (account, transDate) do update
+        set total           = total + excluded.total,
+            numTransactions = numTransactions + excluded.numTransactions;
+
+    delete from account_balances
+    where numTransactions <= 0 and account in (trim(OLD.fromAccount), trim(OLD.toAccount));
+
+    delete from account_daily_sums
+    where numTransactions <= 0
+      and transDate = OLD.transDate
+      and account in (trim(OLD.fromAccount), trim(OLD.toAccount));
+end;
+
+-- "insert or replace" doesn't fire delete triggers, so delete the row being replaced ourselves
+-- to have its amount taken off first.
+create trigger transactions_balance_replace
+    before insert
+    on transactions
+    when exists (select 1 from transactions where id = NEW.id)
+begin
+    delete from transactions where id = NEW.id;
+end;
+
+drop view accounts;
+create view accounts(name, balance, lastTransDate) as
+select b.account,
+       b.balance,
+       (select max(ds.transDate) from account_daily_sums ds where ds.account = b.account)
+from account_balances b;
+
+drop view daily_sum;
+create view daily_sum as
+select account, total, transDate
+from account_daily_sums;
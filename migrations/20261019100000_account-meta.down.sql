@@ -0,0 +1,9 @@
+-- Add down migration script here
+drop view accounts;
+create view accounts(name, balance, lastTransDate) as
+select b.account,
+       b.balance,
+       (select max(ds.transDate) from account_daily_sums ds where ds.account = b.account)
+from account_balances b;
+
+drop table accounts_meta;
@@ -0,0 +1,32 @@
+-- Add up migration script here
+create table accounts_meta
+(
+    name           text not null primary key collate nocase check ( length(trim(name)) > 0 ),
+    type           text check ( type in ('asset', 'liability', 'equity', 'income', 'expense') ),
+    currency       text,
+    notes          text,
+    openedDate     date,
+    closedDate     date,
+    openingBalance integer,
+    check ( openedDate is null or closedDate is null or openedDate <= closedDate )
+);
+
+create index accounts_meta_type on accounts_meta (type);
+
+-- Accounts are those with transactions plus those with metadata only
+drop view accounts;
+create view accounts(name, balance, lastTransDate, type, currency, notes, openedDate, closedDate, openingBalance,
+                     closed) as
+select coalesce(b.account, m.name),
+       coalesce(b.balance, 0) + coalesce(m.openingBalance, 0),
+       (select max(ds.transDate) from account_daily_sums ds where ds.account = n.name),
+       m.type,
+       m.currency,
+       m.notes,
+       m.openedDate,
+       m.closedDate,
+       m.openingBalance,
+       coalesce(m.closedDate <= date('now'), false)
+from (select account as name from account_balances union select name from accounts_meta) n
+         left join account_balances b on b.account = n.name
+         left join accounts_meta m on m.name = n.name;
@@ -0,0 +1,34 @@
+-- Add up migration script here
+
+-- Every account paired with each of its ancestors and itself, where account names are split into
+-- levels by the "accountSeparator" client config (":" by default, "" for flat names). For example
+-- "Expenses:Food" gives ("Expenses:Food", "Expenses") and ("Expenses:Food", "Expenses:Food").
+create view account_ancestors(account, ancestor) as
+with recursive
+    separator(value) as (
+        select coalesce((select value from configs where name = 'client' and id = 'accountSeparator'), ':')
+    ),
+    names(name) as (
+        select account from account_balances
+        union
+        select name from accounts_meta
+    ),
+    prefixes(account, prefix, rest) as (
+        select name, '', name from names
+
+        union all
+
+        select p.account,
+               p.prefix || iif(p.prefix = '', '', s.value) ||
+               iif(length(s.value) > 0 and instr(p.rest, s.value) > 0,
+                   substr(p.rest, 1, instr(p.rest, s.value) - 1),
+                   p.rest),
+               iif(length(s.value) > 0 and instr(p.rest, s.value) > 0,
+                   substr(p.rest, instr(p.rest, s.value) + length(s.value)),
+                   null)
+        from prefixes p, separator s
+        where p.rest is not null
+    )
+select account, prefix
+from prefixes
+where prefix != '';
@@ -0,0 +1,24 @@
+-- Add down migration script here
+drop view account_groups_view;
+drop view account_group_accounts;
+drop table account_group_members;
+delete from account_groups where weight != 1;
+alter table account_groups drop column weight;
+
+create view account_groups_view as
+select groupName, json_group_array(accountName) as accounts
+from account_groups
+group by groupName collate nocase;
+
+create trigger account_groups_view_insert
+    instead of insert
+    on account_groups_view
+begin
+    delete from account_groups where groupName = trim(NEW.groupName) collate nocase;
+
+    insert or
+    replace
+    into account_groups(groupName, accountName)
+    select trim(NEW.groupName), trim(value)
+    from json_each(NEW.accounts);
+end;
@@ -0,0 +1,73 @@
+-- Add down migration script here
+drop view account_groups_view;
+drop view account_group_accounts;
+drop view account_group_rule_accounts;
+drop table account_group_rules;
+
+create view account_group_accounts(groupName, accountName, weight) as
+with recursive
+    expanded(groupName, memberGroup, weight, path) as (
+        select groupName, groupName, 1.0, json_array(groupName)
+        from (select groupName from account_groups union all select groupName from account_group_members)
+        group by groupName collate nocase
+
+        union all
+
+        select e.groupName, m.memberGroup, e.weight * m.weight, json_insert(e.path, '$[#]', m.memberGroup)
+        from expanded e
+                 inner join account_group_members m on m.groupName = e.memberGroup
+        where not exists (select 1 from json_each(e.path) p where m.memberGroup = p.value)
+    )
+select e.groupName, trim(ag.accountName), sum(e.weight * ag.weight)
+from expanded e
+         inner join account_groups ag on ag.groupName = e.memberGroup collate nocase
+group by e.groupName collate nocase, trim(ag.accountName) collate nocase;
+
+create view account_groups_view(groupName, accounts, members) as
+with names(groupName) as (
+    select groupName from account_groups
+    union all
+    select groupName from account_group_members
+)
+select n.groupName,
+       (select json_group_array(ag.accountName)
+        from account_groups ag
+        where ag.groupName = n.groupName collate nocase and ag.weight = 1),
+       (select json_group_array(json(m.member))
+        from (select json_object('type', 'account', 'name', ag.accountName, 'weight', ag.weight) as member
+              from account_groups ag
+              where ag.groupName = n.groupName collate nocase and ag.weight != 1
+              union all
+              select json_object('type', 'group', 'name', gm.memberGroup, 'weight', gm.weight)
+              from account_group_members gm
+              where gm.groupName = n.groupName) m)
+from names n
+group by n.groupName collate nocase;
+
+create trigger account_groups_view_insert
+    instead of insert
+    on account_groups_view
+begin
+    -- Without members, only the accounts are replaced
+    delete from account_groups where groupName = trim(NEW.groupName) collate nocase
+                                 and (NEW.members is not null or weight = 1);
+    delete from account_group_members where groupName = trim(NEW.groupName) and NEW.members is not null;
+
+    insert or
+    replace
+    into account_groups(groupName, accountName)
+    select trim(NEW.groupName), trim(value)
+    from json_each(NEW.accounts);
+
+    insert into account_groups(groupName, accountName, weight)
+    select trim(NEW.groupName), trim(m.value ->> 'name'), coalesce(m.value ->> 'weight', 1)
+    from json_each(coalesce(NEW.members, '[]')) m
+    where m.value ->> 'type' = 'account'
+    on conflict (groupName, accountName) do update set weight = excluded.weight;
+
+    insert into account_group_members(groupName, memberGroup, weight)
+    select trim(NEW.groupName), trim(m.value ->> 'name'), coalesce(m.value ->> 'weight', 1)
+    from json_each(coalesce(NEW.members, '[]')) m
+    where m.value ->> 'type' = 'group'
+    on conflict (groupName, memberGroup) do update set weight = excluded.weight;
+end;
@@ -0,0 +1,125 @@
+-- Add up migration script here
+
+-- Rules adding every account they match to a group, resolved whenever the group is used
+create table account_group_rules
+(
+    groupName text not null collate nocase check ( length(trim(groupName)) > 0 ),
+    ruleType  text not null check ( ruleType in ('prefix', 'regex', 'type', 'tag', 'currency') ),
+    value     text not null check ( length(value) > 0 ),
+    primary key (groupName, ruleType, value)
+);
+
+create view account_group_rule_accounts(groupName, accountName) as
+select distinct r.groupName, a.name
+from account_group_rules r
+         inner join accounts a on case r.ruleType
+    when 'prefix' then substr(a.name, 1, length(r.value)) = r.value collate nocase
+    when 'regex' then a.name regexp r.value
+    when 'type' then a.type = r.value
+    when 'currency' then a.currency = upper(trim(r.value))
+    when 'tag' then exists (select 1
+                            from account_transactions at
+                                     inner join transaction_tags tt on tt.transactionId = at.id
+                            where at.account = a.name collate nocase
+                              and tt.tag = r.value collate nocase)
+    end;
+
+-- Include the accounts matched by rules
+drop view account_group_accounts;
+
+create view account_group_accounts(groupName, accountName, weight) as
+with recursive
+    group_names(groupName) as (
+        select groupName from account_groups
+        union all
+        select groupName from account_group_members
+        union all
+        select groupName from account_group_rules
+    ),
+    group_accounts(groupName, accountName, weight) as (
+        select groupName, accountName, weight from account_groups
+        union all
+        select ra.groupName, ra.accountName, 1.0
+        from account_group_rule_accounts ra
+        where not exists (select 1
+                          from account_groups ag
+                          where ag.groupName = ra.groupName collate nocase
+                            and trim(ag.accountName) = ra.accountName collate nocase)
+    ),
+    expanded(groupName, memberGroup, weight, path) as (
+        select groupName, groupName, 1.0, json_array(groupName)
+        from group_names
+        group by groupName collate nocase
+
+        union all
+
+        select e.groupName, m.memberGroup, e.weight * m.weight, json_insert(e.path, '$[#]', m.memberGroup)
+        from expanded e
+                 inner join account_group_members m on m.groupName = e.memberGroup
+        where not exists (select 1 from json_each(e.path) p where m.memberGroup = p.value)
+    )
+select e.groupName, trim(ga.accountName), sum(e.weight * ga.weight)
+from expanded e
+         inner join group_accounts ga on ga.groupName = e.memberGroup collate nocase
+group by e.groupName collate nocase, trim(ga.accountName) collate nocase;
+
+drop view account_groups_view;
+
+create view account_groups_view(groupName, accounts, members, rules) as
+with names(groupName) as (
+    select groupName from account_groups
+    union all
+    select groupName from account_group_members
+    union all
+    select groupName from account_group_rules
+)
+select n.groupName,
+       (select json_group_array(ag.accountName)
+        from account_groups ag
+        where ag.groupName = n.groupName collate nocase and ag.weight = 1),
+       (select json_group_array(json(m.member))
+        from (select json_object('type', 'account', 'name', ag.accountName, 'weight', ag.weight) as member
+              from account_groups ag
+              where ag.groupName = n.groupName collate nocase and ag.weight != 1
+              union all
+              select json_object('type', 'group', 'name', gm.memberGroup, 'weight', gm.weight)
+              from account_group_members gm
+              where gm.groupName = n.groupName) m),
+       (select json_group_array(json_object('type', r.ruleType, 'value', r.value))
+        from account_group_rules r
+        where r.groupName = n.groupName)
+from names n
+group by n.groupName collate nocase;
+
+create trigger account_groups_view_insert
+    instead of insert
+    on account_groups_view
+begin
+    -- Without members or rules, those are kept and only the accounts are replaced
+    delete from account_groups where groupName = trim(NEW.groupName) collate nocase
+                                 and (NEW.members is not null or weight = 1);
+    delete from account_group_members where groupName = trim(NEW.groupName) and NEW.members is not null;
+    delete from account_group_rules where groupName = trim(NEW.groupName) and NEW.rules is not null;
+
+    insert or
+    replace
+    into account_groups(groupName, accountName)
+    select trim(NEW.groupName), trim(value)
+    from json_each(NEW.accounts);
+
+    insert into account_groups(groupName, accountName, weight)
+    select trim(NEW.groupName), trim(m.value ->> 'name'), coalesce(m.value ->> 'weight', 1)
+    from json_each(coalesce(NEW.members, '[]')) m
+    where m.value ->> 'type' = 'account'
+    on conflict (groupName, accountName) do update set weight = excluded.weight;
+
+    insert into account_group_members(groupName, memberGroup, weight)
+    select trim(NEW.groupName), trim(m.value ->> 'name'), coalesce(m.value ->> 'weight', 1)
+    from json_each(coalesce(NEW.members, '[]')) m
+    where m.value ->> 'type' = 'group'
+    on conflict (groupName, memberGroup) do update set weight = excluded.weight;
+
+    insert or ignore into account_group_rules(groupName, ruleType, value)
+    select trim(NEW.groupName), r.value ->> 'type', r.value ->> 'value'
+    from json_each(coalesce(NEW.rules, '[]')) r;
+end;
@@ -0,0 +1,8 @@
+-- Add up migration script here
+create table tags
+(
+    name        text    not null primary key collate nocase check ( length(trim(name)) > 0 ),
+    description text,
+    colour      text,
+    archived    boolean not null default false
+);
@@ -0,0 +1,18 @@
+-- Add down migration script here
+drop view account_group_rule_accounts;
+drop view tag_ancestors;
+
+create view account_group_rule_accounts(groupName, accountName) as
+select distinct r.groupName, a.name
+from account_group_rules r
+         inner join accounts a on case r.ruleType
+    when 'prefix' then substr(a.name, 1, length(r.value)) = r.value collate nocase
+    when 'regex' then a.name regexp r.value
+    when 'type' then a.type = r.value
+    when 'currency' then a.currency = upper(trim(r.value))
+    when 'tag' then exists (select 1
+                            from account_transactions at
+                                     inner join transaction_tags tt on tt.transactionId = at.id
+                            where at.account = a.name collate nocase
+                              and tt.tag = r.value collate nocase)
+    end;
@@ -0,0 +1,53 @@
+-- Add up migration script here
+
+-- Every tag paired with each of its ancestors and itself, where tags are split into levels by the
+-- "tagSeparator" client config ("/" by default, "" for flat tags). For example "trip/japan" gives
+-- ("trip/japan", "trip") and ("trip/japan", "trip/japan").
+create view tag_ancestors(tag, ancestor) as
+with recursive
+    separator(value) as (
+        select coalesce((select value from configs where name = 'client' and id = 'tagSeparator'), '/')
+    ),
+    names(name) as (
+        select tag from transaction_tags
+        union
+        select name from tags
+    ),
+    prefixes(tag, prefix, rest) as (
+        select name, '', name from names
+
+        union all
+
+        select p.tag,
+               p.prefix || iif(p.prefix = '', '', s.value) ||
+               iif(length(s.value) > 0 and instr(p.rest, s.value) > 0,
+                   substr(p.rest, 1, instr(p.rest, s.value) - 1),
+                   p.rest),
+               iif(length(s.value) > 0 and instr(p.rest, s.value) > 0,
+                   substr(p.rest, instr(p.rest, s.value) + length(s.value)),
+                   null)
+        from prefixes p, separator s
+        where p.rest is not null
+    )
+select tag, prefix
+from prefixes
+where prefix != '';
+
+-- Tag rules match the tags under the rule's tag too
+drop view account_group_rule_accounts;
+
+create view account_group_rule_accounts(groupName, accountName) as
+select distinct r.groupName, a.name
+from account_group_rules r
+         inner join accounts a on case r.ruleType
+    when 'prefix' then substr(a.name, 1, length(r.value)) = r.value collate nocase
+    when 'regex' then a.name regexp r.value
+    when 'type' then a.type = r.value
+    when 'currency' then a.currency = upper(trim(r.value))
+    when 'tag' then exists (select 1
+                            from account_transactions at
+                                     inner join transaction_tags tt on tt.transactionId = at.id
+                                     inner join tag_ancestors ta on ta.tag = tt.tag
+                            where at.account = a.name collate nocase
+                              and ta.ancestor = r.value collate nocase)
+    end;
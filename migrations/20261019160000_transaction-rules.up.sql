@@ -0,0 +1,27 @@
+-- Add up migration script here
+
+-- Rules labelling new transactions. Every condition given must hold for a rule to apply, and
+-- rules apply from the lowest priority up, each seeing the changes of the ones before.
+create table transaction_rules
+(
+    id               integer primary key autoincrement,
+    name             text    not null check ( length(trim(name)) > 0 ),
+    priority         integer not null default 0,
+    enabled          boolean not null default true,
+
+    -- Conditions
+    descriptionRegex text,
+    minAmount        integer,
+    maxAmount        integer,
+    fromAccount      text collate nocase,
+    toAccount        text collate nocase,
+    -- A JSON array of short day names, e.g. ["Sat", "Sun"]
+    weekdays         text,
+
+    -- Actions
+    setFromAccount   text,
+    setToAccount     text,
+    setDescription   text,
+    -- A JSON array of tags to add
+    addTags          text
+);
@@ -0,0 +1,20 @@
+-- Add up migration script here
+
+-- A budget for the spending on an account, or on an account group, in each period from
+-- startDate. Custom budgets have a single period ending at endDate.
+create table budgets
+(
+    id           integer primary key autoincrement,
+    name         text    not null check ( length(trim(name)) > 0 ),
+    account      text collate nocase,
+    accountGroup text collate nocase,
+    period       text    not null check ( period in ('Monthly', 'Yearly', 'Custom') ),
+    amount       integer not null,
+    startDate    date    not null,
+    endDate      date,
+    -- Whether the unspent amount of a period adds to the next one
+    rollover     boolean not null default false,
+    check ( (account is null) != (accountGroup is null) ),
+    check ( period != 'Custom' or endDate is not null ),
+    check ( endDate is null or endDate >= startDate )
+);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+drop table envelope_income_accounts;
+drop table envelope_allocations;
+drop table envelope_accounts;
+drop table envelopes;
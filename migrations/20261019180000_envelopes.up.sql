@@ -0,0 +1,36 @@
+-- Add up migration script here
+
+-- Envelopes hold money allocated to them, and are drawn down by the spending on their accounts.
+create table envelopes
+(
+    name text not null primary key collate nocase check ( length(trim(name)) > 0 )
+);
+
+-- An account, with the accounts under it, is drawn from one envelope. Where both an account and
+-- one under it are mapped, the more specific mapping wins.
+create table envelope_accounts
+(
+    account  text not null primary key collate nocase,
+    envelope text not null collate nocase references envelopes (name) on delete cascade
+);
+
+-- Money moved between envelopes. A null envelope is the pool of unassigned income, so a null
+-- fromEnvelope allocates income and a null toEnvelope returns money to the pool.
+create table envelope_allocations
+(
+    id           integer primary key autoincrement,
+    allocDate    date    not null,
+    fromEnvelope text collate nocase references envelopes (name) on delete cascade,
+    toEnvelope   text collate nocase references envelopes (name) on delete cascade,
+    amount       integer not null,
+    note         text,
+    check ( fromEnvelope is not toEnvelope )
+);
+
+create index envelope_allocations_date on envelope_allocations (allocDate);
+
+-- The accounts, with the accounts under them, whose income fills the unassigned pool
+create table envelope_income_accounts
+(
+    account text not null primary key collate nocase
+);
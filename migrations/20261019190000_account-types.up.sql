@@ -0,0 +1,12 @@
+-- Add up migration script here
+
+-- The type of every account, which is its own or else that of its nearest typed ancestor, so
+-- typing "Expenses" types "Expenses:Food" too.
+create view account_types(account, type) as
+select account, type
+from (select aa.account,
+             m.type,
+             row_number() over (partition by lower(aa.account) order by length(aa.ancestor) desc) as n
+      from account_ancestors aa
+               inner join accounts_meta m on m.name = aa.ancestor and m.type is not null)
+where n = 1;
@@ -0,0 +1,16 @@
+-- Add up migration script here
+
+-- Transactions expected to repeat, on startDate and every cadence after it until endDate
+create table recurring_transactions
+(
+    id          integer primary key autoincrement,
+    description text    not null,
+    fromAccount text    not null collate nocase check ( length(trim(fromAccount)) > 0 ),
+    toAccount   text    not null collate nocase check ( length(trim(toAccount)) > 0 ),
+    amount      integer not null,
+    cadence     text    not null check ( cadence in ('weekly', 'fortnightly', 'monthly', 'quarterly', 'yearly') ),
+    startDate   date    not null,
+    endDate     date check ( endDate is null or endDate >= startDate ),
+    -- A JSON array of tags
+    tags        text    not null default '[]'
+);
@@ -0,0 +1,18 @@
+-- Add up migration script here
+
+-- Anomalies found by the periodic scan, kept until dismissed
+create table alerts
+(
+    id          integer  primary key autoincrement,
+    kind        text     not null check ( kind in ('unusualAmount', 'newMerchant', 'categorySpike') ),
+    -- The transaction id, or the month and account of a category spike
+    subject     text     not null collate nocase,
+    date        date     not null,
+    description text     not null,
+    account     text collate nocase,
+    amount      integer  not null,
+    score       real,
+    dismissed   boolean  not null default false,
+    createdDate datetime not null default current_timestamp,
+    unique (kind, subject)
+);
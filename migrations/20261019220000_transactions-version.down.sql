@@ -0,0 +1,9 @@
+-- Add down migration script here
+drop trigger transaction_tags_version_delete;
+drop trigger transaction_tags_version_update;
+drop trigger transaction_tags_version_insert;
+drop trigger transactions_version_delete;
+drop trigger transactions_version_update;
+drop trigger transactions_version_insert;
+
+drop table transactions_version;
@@ -0,0 +1,52 @@
+-- Add up migration script here
+
+-- A counter bumped on every change to transactions or their tags, so what is learnt from them can
+-- be kept until it changes
+create table transactions_version
+(
+    version integer not null
+);
+
+insert into transactions_version (version) values (0);
+
+create trigger transactions_version_insert
+    after insert
+    on transactions
+begin
+    update transactions_version set version = version + 1;
+end;
+
+create trigger transactions_version_update
+    after update
+    on transactions
+begin
+    update transactions_version set version = version + 1;
+end;
+
+create trigger transactions_version_delete
+    after delete
+    on transactions
+begin
+    update transactions_version set version = version + 1;
+end;
+
+create trigger transaction_tags_version_insert
+    after insert
+    on transaction_tags
+begin
+    update transactions_version set version = version + 1;
+end;
+
+create trigger transaction_tags_version_update
+    after update
+    on transaction_tags
+begin
+    update transactions_version set version = version + 1;
+end;
+
+create trigger transaction_tags_version_delete
+    after delete
+    on transaction_tags
+begin
+    update transactions_version set version = version + 1;
+end;
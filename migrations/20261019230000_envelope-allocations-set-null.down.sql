@@ -0,0 +1,21 @@
+-- Add down migration script here
+create table envelope_allocations_old
+(
+    id           integer primary key autoincrement,
+    allocDate    date    not null,
+    fromEnvelope text collate nocase references envelopes (name) on delete cascade,
+    toEnvelope   text collate nocase references envelopes (name) on delete cascade,
+    amount       integer not null,
+    note         text,
+    check ( fromEnvelope is not toEnvelope )
+);
+
+insert into envelope_allocations_old (id, allocDate, fromEnvelope, toEnvelope, amount, note)
+select id, allocDate, fromEnvelope, toEnvelope, amount, note
+from envelope_allocations
+where fromEnvelope is not null or toEnvelope is not null;
+
+drop table envelope_allocations;
+alter table envelope_allocations_old rename to envelope_allocations;
+
+create index envelope_allocations_date on envelope_allocations (allocDate);
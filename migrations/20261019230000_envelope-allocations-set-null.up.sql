@@ -0,0 +1,24 @@
+-- Add up migration script here
+
+-- Deleting an envelope turns its allocations with other envelopes into ones with the pool, instead
+-- of taking them away from those envelopes. An allocation with neither envelope is money spent from
+-- a deleted envelope, which has left the pool for good.
+create table envelope_allocations_new
+(
+    id           integer primary key autoincrement,
+    allocDate    date    not null,
+    fromEnvelope text collate nocase references envelopes (name) on delete set null,
+    toEnvelope   text collate nocase references envelopes (name) on delete set null,
+    amount       integer not null,
+    note         text,
+    check ( fromEnvelope is not toEnvelope or fromEnvelope is null )
+);
+
+insert into envelope_allocations_new (id, allocDate, fromEnvelope, toEnvelope, amount, note)
+select id, allocDate, fromEnvelope, toEnvelope, amount, note
+from envelope_allocations;
+
+drop table envelope_allocations;
+alter table envelope_allocations_new rename to envelope_allocations;
+
+create index envelope_allocations_date on envelope_allocations (allocDate);
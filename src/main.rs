@@ -38,8 +38,7 @@ async fn serve_static_asset(req: Request) -> Result<Response, (StatusCode, &'sta
         p => p,
     };
 
-    let asset =
-        Asset::get(path).ok_or_else(|| (StatusCode::NOT_FOUND, "Unable to find given path"))?;
+    let asset = Asset::get(path).ok_or((StatusCode::NOT_FOUND, "Unable to find given path"))?;
 
     let mime = mime_guess::from_path(path).first_or_octet_stream();
 
@@ -70,19 +69,23 @@ async fn main() {
         SqliteConnectOptions::from_str(&database_url)
             .expect("to parse database url")
             .create_if_missing(true)
-            .journal_mode(SqliteJournalMode::Delete),
+            .journal_mode(SqliteJournalMode::Delete)
+            .with_regexp(),
     )
     .await
-    .expect(&format!(
-        "Unable to open database connection to {}",
-        &database_url
-    ));
+    .unwrap_or_else(|_| panic!("Unable to open database connection to {}", &database_url));
 
     if std::env::var("DATABASE_RUN_MIGRATION") != Ok("false".to_string()) {
         sqlx::migrate!().run(&conn).await.expect("Migration to run");
     }
 
-    let state = AppState { conn, port };
+    service::alert::scan::spawn(conn.clone());
+
+    let state = AppState {
+        conn,
+        port,
+        suggest_cache: Default::default(),
+    };
 
     let cors = CorsLayer::new()
         .allow_methods([
@@ -100,9 +103,14 @@ async fn main() {
         .nest("/", service::login::router())
         .nest("/", service::config::router())
         .nest("/", service::report::router())
+        .nest("/", service::rule::router())
         .nest("/", service::tag::router())
         .nest("/", service::transaction::router())
         .nest("/", service::attachment::router())
+        .nest("/", service::budget::router())
+        .nest("/", service::envelope::router())
+        .nest("/", service::recurring::router())
+        .nest("/", service::alert::router())
         .route("/", get(serve_static_asset))
         .route("/*path", get(serve_static_asset))
         .layer(TraceLayer::new_for_http())
@@ -20,7 +20,7 @@ pub async fn execute(state: State<AppState>, request: Request, next: Next) -> Re
         .headers()
         .get("Authorization")
         .and_then(|v| v.to_str().ok())
-        .and_then(|v| v.split("Bearer ").skip(1).next())
+        .and_then(|v| v.split("Bearer ").nth(1))
         .map(|v| Signed(Cow::from(v)))
         .unwrap_or_default();
 
@@ -0,0 +1,67 @@
+use axum::extract::{Json, State};
+
+use crate::{service::Result, state::AppState};
+
+#[derive(Debug, sqlx::FromRow, serde::Serialize, PartialEq, Eq)]
+#[sqlx(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct Mismatch {
+    pub account: String,
+    /// The day of a mismatching daily sum, or `None` for an account balance.
+    pub trans_date: Option<String>,
+    pub expected: i64,
+    pub actual: i64,
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Output {
+    pub consistent: bool,
+    pub mismatches: Vec<Mismatch>,
+}
+
+//language=sql
+const SQL: &str = r#"
+with expected_balances(account, balance) as (
+    select account, sum(amount)
+    from account_transactions
+    group by account collate nocase
+),
+expected_daily_sums(account, transDate, total) as (
+    select account, transDate, sum(amount)
+    from account_transactions
+    group by account collate nocase, transDate
+)
+select coalesce(e.account, b.account) as account,
+       null as transDate,
+       coalesce(e.balance, 0) as expected,
+       coalesce(b.balance, 0) as actual
+from expected_balances e
+full outer join account_balances b on b.account = e.account
+where e.account is null or b.account is null or e.balance != b.balance
+
+union all
+
+select coalesce(e.account, ds.account),
+       coalesce(e.transDate, ds.transDate),
+       coalesce(e.total, 0),
+       coalesce(ds.total, 0)
+from expected_daily_sums e
+full outer join account_daily_sums ds on ds.account = e.account and ds.transDate = e.transDate
+where e.account is null or ds.account is null or e.total != ds.total
+
+order by account, transDate
+"#;
+
+/// Compares the materialised balances and daily sums against the transactions.
+pub async fn check(state: &AppState) -> Result<Output> {
+    let mismatches: Vec<Mismatch> = sqlx::query_as(SQL).fetch_all(&state.conn).await?;
+    Ok(Output {
+        consistent: mismatches.is_empty(),
+        mismatches,
+    })
+}
+
+pub async fn execute(state: State<AppState>) -> Result<Json<Output>> {
+    Ok(check(&state).await?.into())
+}
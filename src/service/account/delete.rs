@@ -0,0 +1,23 @@
+use axum::extract::{Json, State};
+
+use crate::{
+    service::{GenericUpdateResponse, Result},
+    state::AppState,
+};
+
+pub type Input = Vec<String>;
+
+/// Deletes the metadata of the given accounts. Their transactions are left alone.
+pub async fn execute(
+    state: State<AppState>,
+    Json(names): Json<Input>,
+) -> Result<Json<GenericUpdateResponse>> {
+    let output: GenericUpdateResponse = sqlx::query(
+        "delete from accounts_meta where name in (select trim(value) from json_each(?))",
+    )
+    .bind(crate::sqlx_ext::Json(names))
+    .execute(&state.conn)
+    .await?
+    .into();
+    Ok(output.into())
+}
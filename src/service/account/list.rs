@@ -10,40 +10,72 @@ use chrono::NaiveDate;
 
 use crate::sqlx_ext::Json;
 
-type Sort = service::Sort<SortField>;
+use super::model::{AccountMeta, AccountType};
+
+pub type Sort = service::Sort<SortField>;
 
 #[derive(Debug, sqlx::FromRow, serde::Serialize)]
 #[sqlx(rename_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub struct Account {
-    pub name: String,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub meta: AccountMeta,
+    /// The opening balance plus the sum of all transactions.
     pub balance: i64,
-    pub last_trans_date: NaiveDate,
+    pub last_trans_date: Option<NaiveDate>,
+    pub closed: bool,
 }
 
 #[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Input {
-    q: Option<String>,
-    includes: Option<Json<Vec<String>>>,
+    pub q: Option<String>,
+    pub includes: Option<Json<Vec<String>>>,
+    pub types: Option<Json<Vec<AccountType>>>,
+    pub currencies: Option<Json<Vec<String>>>,
+    #[serde(default = "default_include_closed")]
+    pub include_closed: bool,
+    /// Report balances and closed states as at the end of this date, instead of now.
+    pub as_of: Option<NaiveDate>,
     #[serde(default = "default_sorts")]
-    sorts: Cow<'static, [Sort]>,
+    pub sorts: Cow<'static, [Sort]>,
 }
 
 #[derive(serde::Deserialize, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
-enum SortField {
+pub enum SortField {
     Name,
     Balance,
     LastTransDate,
+    Type,
 }
 
-const DEFAULT_SORTS: &'static [Sort] = &[Sort {
+const DEFAULT_SORTS: &[Sort] = &[Sort {
     field: SortField::LastTransDate,
     order: SortOrder::DESC,
 }];
 
 const fn default_sorts() -> Cow<'static, [Sort]> {
-    return Cow::Borrowed(DEFAULT_SORTS);
+    Cow::Borrowed(DEFAULT_SORTS)
+}
+
+const fn default_include_closed() -> bool {
+    true
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Self {
+            q: Default::default(),
+            includes: Default::default(),
+            types: Default::default(),
+            currencies: Default::default(),
+            include_closed: default_include_closed(),
+            as_of: Default::default(),
+            sorts: default_sorts(),
+        }
+    }
 }
 
 impl ToSQL for SortField {
@@ -52,33 +84,72 @@ impl ToSQL for SortField {
             SortField::Name => "name",
             SortField::Balance => "balance",
             SortField::LastTransDate => "lastTransDate",
+            SortField::Type => "type",
         })
     }
 }
 
 //language=sql
 const SQL: &str = r#"
+with accounts_as_of as (
+    select name,
+           type,
+           currency,
+           notes,
+           openedDate,
+           closedDate,
+           openingBalance,
+           iif(?6 is null, balance, iif(openedDate is null or openedDate <= ?6, coalesce(openingBalance, 0), 0) + coalesce(
+               (select sum(ds.total) from account_daily_sums ds where ds.account = name and ds.transDate <= ?6),
+               0
+           )) as balance,
+           iif(?6 is null, lastTransDate,
+               (select max(ds.transDate) from account_daily_sums ds where ds.account = name and ds.transDate <= ?6)
+           ) as lastTransDate,
+           iif(?6 is null, closed, coalesce(closedDate <= ?6, false)) as closed
+    from accounts
+)
 select *
-from accounts
+from accounts_as_of
 where (?1 is null or trim(?1) = '' or name like '%' || trim(?1) || '%' collate nocase)
-  and (?2 is null or name collate nocase in (select value from json_each(?2)))
+  and (?2 is null or name collate nocase in (
+      select account from account_ancestors
+      where ancestor collate nocase in (select trim(value) from json_each(?2))
+  ))
+  and (?3 is null or type in (select value from json_each(?3)))
+  and (?4 is null or currency in (select upper(trim(value)) from json_each(?4)))
+  and (?5 or not closed)
 "#;
 
 pub async fn execute(
     state: State<AppState>,
-    req: extract::Query<Input>,
+    extract::Json(input): extract::Json<Input>,
 ) -> Result<extract::Json<Vec<Account>>> {
-    let Input { q, includes, sorts } = req.0;
+    Ok(query(&state, input).await?.into())
+}
+
+pub async fn query(state: &AppState, input: Input) -> Result<Vec<Account>> {
+    let Input {
+        q,
+        includes,
+        types,
+        currencies,
+        include_closed,
+        as_of,
+        sorts,
+    } = input;
     let sql = format!(
         "with cte as({SQL}) select * from cte {order}",
         order = display_sorts_sql(sorts)
     );
 
-    Ok(extract::Json::from(
-        sqlx::query_as(&sql)
-            .bind(q)
-            .bind(includes)
-            .fetch_all(&state.conn)
-            .await?,
-    ))
+    Ok(sqlx::query_as(&sql)
+        .bind(q)
+        .bind(includes)
+        .bind(types)
+        .bind(currencies)
+        .bind(include_closed)
+        .bind(as_of)
+        .fetch_all(&state.conn)
+        .await?)
 }
@@ -0,0 +1,232 @@
+use axum::extract::{Json, State};
+use sqlx::{Sqlite, Transaction};
+
+use crate::{
+    service::{merge, Error, Result},
+    state::AppState,
+};
+
+use super::model::AccountMeta;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    pub sources: Vec<String>,
+    pub target: String,
+}
+
+/// The number of rows rewritten in each table.
+#[derive(serde::Serialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Output {
+    pub transactions: u64,
+    pub account_groups: u64,
+    pub mappings: u64,
+    pub accounts_meta: u64,
+    pub rules: u64,
+    pub budgets: u64,
+    pub envelope_accounts: u64,
+    pub recurring_transactions: u64,
+}
+
+//language=sql
+const SAME_ACCOUNT_SQL: &str = r#"
+with names(name) as (select trim(value) from json_each(?1) union select trim(?2))
+select count(*)
+from transactions
+where trim(fromAccount) collate nocase in (select name from names)
+  and trim(toAccount) collate nocase in (select name from names)
+"#;
+
+//language=sql
+const TRANSACTIONS_SQL: &str = r#"
+update transactions
+set fromAccount = iif(trim(fromAccount) collate nocase in (select trim(value) from json_each(?1)), trim(?2), fromAccount),
+    toAccount   = iif(trim(toAccount) collate nocase in (select trim(value) from json_each(?1)), trim(?2), toAccount)
+where trim(fromAccount) collate nocase in (select trim(value) from json_each(?1))
+   or trim(toAccount) collate nocase in (select trim(value) from json_each(?1))
+"#;
+
+//language=sql
+const ACCOUNT_GROUPS_SQL: &str = r#"
+update or ignore account_groups
+set accountName = trim(?2)
+where accountName collate nocase in (select trim(value) from json_each(?1));
+
+delete from account_groups
+where accountName collate nocase in (select trim(value) from json_each(?1))
+  and accountName != trim(?2);
+"#;
+
+//language=sql
+const MAPPINGS_SQL: &str = r#"
+update mappings
+set dest = trim(?2)
+where mapping_type = 'account'
+  and dest collate nocase in (select trim(value) from json_each(?1))
+"#;
+
+//language=sql
+const RULES_SQL: &str = r#"
+with sources(name) as (select trim(value) from json_each(?1))
+update transaction_rules
+set fromAccount    = iif(fromAccount collate nocase in sources, trim(?2), fromAccount),
+    toAccount      = iif(toAccount collate nocase in sources, trim(?2), toAccount),
+    setFromAccount = iif(setFromAccount collate nocase in sources, trim(?2), setFromAccount),
+    setToAccount   = iif(setToAccount collate nocase in sources, trim(?2), setToAccount)
+where fromAccount collate nocase in sources
+   or toAccount collate nocase in sources
+   or setFromAccount collate nocase in sources
+   or setToAccount collate nocase in sources
+"#;
+
+//language=sql
+const BUDGETS_SQL: &str = r#"
+update budgets
+set account = trim(?2)
+where account collate nocase in (select trim(value) from json_each(?1))
+"#;
+
+//language=sql
+const ENVELOPE_ACCOUNTS_SQL: &str = r#"
+update or ignore envelope_accounts
+set account = trim(?2)
+where account collate nocase in (select trim(value) from json_each(?1));
+
+delete from envelope_accounts
+where account collate nocase in (select trim(value) from json_each(?1))
+  and account != trim(?2);
+
+update or ignore envelope_income_accounts
+set account = trim(?2)
+where account collate nocase in (select trim(value) from json_each(?1));
+
+delete from envelope_income_accounts
+where account collate nocase in (select trim(value) from json_each(?1))
+  and account != trim(?2);
+"#;
+
+//language=sql
+const RECURRING_TRANSACTIONS_SQL: &str = r#"
+update recurring_transactions
+set fromAccount = iif(trim(fromAccount) collate nocase in (select trim(value) from json_each(?1)), trim(?2), fromAccount),
+    toAccount   = iif(trim(toAccount) collate nocase in (select trim(value) from json_each(?1)), trim(?2), toAccount)
+where trim(fromAccount) collate nocase in (select trim(value) from json_each(?1))
+   or trim(toAccount) collate nocase in (select trim(value) from json_each(?1))
+"#;
+
+//language=sql
+const RENAME_BALANCES_SQL: &str = r#"
+update account_balances set account = trim(?1) where account = trim(?1);
+update account_daily_sums set account = trim(?1) where account = trim(?1);
+"#;
+
+/// Rewrites every reference to `sources` into `target`, within `tx`.
+pub async fn merge_into(
+    tx: &mut Transaction<'_, Sqlite>,
+    sources: &[String],
+    target: &str,
+) -> Result<Output> {
+    let (sources, target) = merge::names("account", sources, target)?;
+
+    let (same_account,): (i64,) = sqlx::query_as(SAME_ACCOUNT_SQL)
+        .bind(&sources)
+        .bind(target)
+        .fetch_one(&mut **tx)
+        .await?;
+    if same_account > 0 {
+        return Err(Error::InvalidArgument(
+            format!("{same_account} transaction(s) would move money from {target} to itself")
+                .into(),
+        ));
+    }
+
+    let mut output = Output {
+        transactions: sqlx::query(TRANSACTIONS_SQL)
+            .bind(&sources)
+            .bind(target)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected(),
+        account_groups: sqlx::query(ACCOUNT_GROUPS_SQL)
+            .bind(&sources)
+            .bind(target)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected(),
+        mappings: sqlx::query(MAPPINGS_SQL)
+            .bind(&sources)
+            .bind(target)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected(),
+        rules: sqlx::query(RULES_SQL)
+            .bind(&sources)
+            .bind(target)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected(),
+        budgets: sqlx::query(BUDGETS_SQL)
+            .bind(&sources)
+            .bind(target)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected(),
+        envelope_accounts: sqlx::query(ENVELOPE_ACCOUNTS_SQL)
+            .bind(&sources)
+            .bind(target)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected(),
+        recurring_transactions: sqlx::query(RECURRING_TRANSACTIONS_SQL)
+            .bind(&sources)
+            .bind(target)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected(),
+        ..Default::default()
+    };
+
+    let (taken, metas): (_, Vec<AccountMeta>) =
+        merge::take_metas(tx, "accounts_meta", &sources, target).await?;
+    output.accounts_meta = taken;
+    if let Some(first) = metas.first() {
+        // Opening balances add up
+        let opening_balance = metas
+            .iter()
+            .filter_map(|m| m.opening_balance)
+            .reduce(|a, b| a + b);
+
+        sqlx::query(
+            "insert into accounts_meta (name, type, currency, notes, openedDate, closedDate, openingBalance) \
+             values (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(target)
+        .bind(first.account_type)
+        .bind(&first.currency)
+        .bind(&first.notes)
+        .bind(first.opened_date)
+        .bind(first.closed_date)
+        .bind(opening_balance)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    // The materialised balances keep the first spelling they saw, so take on the target's
+    sqlx::query(RENAME_BALANCES_SQL)
+        .bind(target)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(output)
+}
+
+pub async fn execute(
+    state: State<AppState>,
+    Json(Input { sources, target }): Json<Input>,
+) -> Result<Json<Output>> {
+    let mut tx = state.conn.begin().await?;
+    let output = merge_into(&mut tx, &sources, &target).await?;
+    tx.commit().await?;
+    Ok(output.into())
+}
@@ -0,0 +1,13 @@
+use axum::extract::{Json, State};
+
+use crate::{service::Result, state::AppState};
+
+use super::model::AccountMeta;
+
+pub async fn execute(state: State<AppState>) -> Result<Json<Vec<AccountMeta>>> {
+    Ok(Json::from(
+        sqlx::query_as("select * from accounts_meta order by name")
+            .fetch_all(&state.conn)
+            .await?,
+    ))
+}
@@ -1,9 +1,38 @@
-use axum::{routing::post, Router};
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
 
 use crate::state::AppState;
 
+mod check;
+mod delete;
 mod list;
+mod merge;
+mod meta;
+pub mod model;
+mod rebuild;
+mod rename;
+pub mod save;
+mod statement;
+pub mod tree;
+
+#[cfg(test)]
+pub mod test;
 
 pub fn router() -> Router<AppState> {
-    Router::new().route("/api/accounts/list", post(list::execute))
+    Router::new().nest(
+        "/api/accounts",
+        Router::new()
+            .route("/", get(meta::execute))
+            .route("/", post(save::execute))
+            .route("/", delete(delete::execute))
+            .route("/list", post(list::execute))
+            .route("/tree", post(tree::execute))
+            .route("/rename", post(rename::execute))
+            .route("/merge", post(merge::execute))
+            .route("/:name/statement", get(statement::execute))
+            .route("/balances/check", get(check::execute))
+            .route("/balances/rebuild", post(rebuild::execute)),
+    )
 }
@@ -0,0 +1,28 @@
+use chrono::NaiveDate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, sqlx::Type)]
+#[serde(rename_all = "camelCase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum AccountType {
+    Asset,
+    Liability,
+    Equity,
+    Income,
+    Expense,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+#[sqlx(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct AccountMeta {
+    pub name: String,
+    #[sqlx(rename = "type")]
+    #[serde(rename = "type")]
+    pub account_type: Option<AccountType>,
+    pub currency: Option<String>,
+    pub notes: Option<String>,
+    pub opened_date: Option<NaiveDate>,
+    pub closed_date: Option<NaiveDate>,
+    /// The balance on `opened_date`, before any of the account's transactions.
+    pub opening_balance: Option<i64>,
+}
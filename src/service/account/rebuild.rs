@@ -0,0 +1,34 @@
+use axum::extract::{Json, State};
+
+use crate::{
+    service::{GenericUpdateResponse, Result},
+    state::AppState,
+};
+
+//language=sql
+const SQL: &str = r#"
+delete from account_balances;
+delete from account_daily_sums;
+
+insert into account_daily_sums (account, transDate, total, numTransactions)
+select account, transDate, sum(amount), count(*)
+from account_transactions
+group by account collate nocase, transDate;
+
+insert into account_balances (account, balance, numTransactions)
+select account, sum(total), sum(numTransactions)
+from account_daily_sums
+group by account;
+"#;
+
+/// Recomputes `account_balances` and `account_daily_sums` from scratch.
+pub async fn rebuild(state: &AppState) -> Result<GenericUpdateResponse> {
+    let mut tx = state.conn.begin().await?;
+    let res = sqlx::query(SQL).execute(&mut *tx).await?;
+    tx.commit().await?;
+    Ok(res.into())
+}
+
+pub async fn execute(state: State<AppState>) -> Result<Json<GenericUpdateResponse>> {
+    Ok(rebuild(&state).await?.into())
+}
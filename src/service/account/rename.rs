@@ -0,0 +1,35 @@
+use axum::extract::{Json, State};
+
+use crate::{
+    service::{merge, Result},
+    state::AppState,
+};
+
+use super::merge::{merge_into, Output};
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    pub from: String,
+    pub to: String,
+}
+
+pub async fn execute(
+    state: State<AppState>,
+    Json(Input { from, to }): Json<Input>,
+) -> Result<Json<Output>> {
+    let mut tx = state.conn.begin().await?;
+
+    merge::check_rename(
+        &mut tx,
+        "Account",
+        "select exists (select 1 from accounts where name = ? collate nocase)",
+        &from,
+        &to,
+    )
+    .await?;
+
+    let output = merge_into(&mut tx, &[from], &to).await?;
+    tx.commit().await?;
+    Ok(output.into())
+}
@@ -0,0 +1,50 @@
+use axum::extract::{Json, State};
+
+use crate::{
+    service::{GenericUpdateResponse, Result},
+    state::AppState,
+};
+
+use super::model::AccountMeta;
+
+//language=sql
+const SQL: &str = r#"
+insert or replace into accounts_meta (name, type, currency, notes, openedDate, closedDate, openingBalance)
+values (trim(?), ?, upper(trim(?)), ?, ?, ?, ?)
+"#;
+
+pub async fn execute(
+    state: State<AppState>,
+    Json(accounts): Json<Vec<AccountMeta>>,
+) -> Result<Json<GenericUpdateResponse>> {
+    let mut tx = state.conn.begin().await?;
+    let mut num_affected: u64 = 0;
+
+    for AccountMeta {
+        name,
+        account_type,
+        currency,
+        notes,
+        opened_date,
+        closed_date,
+        opening_balance,
+    } in accounts
+    {
+        num_affected += sqlx::query(SQL)
+            .bind(name)
+            .bind(account_type)
+            .bind(currency)
+            .bind(notes)
+            .bind(opened_date)
+            .bind(closed_date)
+            .bind(opening_balance)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    }
+
+    tx.commit().await?;
+    Ok(Json::from(GenericUpdateResponse {
+        num_affected: num_affected as usize,
+    }))
+}
@@ -0,0 +1,227 @@
+use std::fmt::Write;
+
+use anyhow::Context;
+use axum::{
+    extract::{Path, Query, State},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::NaiveDate;
+
+use crate::{
+    service::{Error, Result},
+    state::AppState,
+};
+
+#[derive(serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Format {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    #[serde(default)]
+    pub format: Format,
+}
+
+#[derive(sqlx::FromRow, serde::Serialize, Debug, PartialEq, Eq)]
+#[sqlx(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct Row {
+    pub id: String,
+    pub trans_date: NaiveDate,
+    pub description: String,
+    pub opposite_account: String,
+    pub amount: i64,
+    /// The balance after this transaction.
+    pub balance: i64,
+}
+
+#[derive(serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Statement {
+    pub account: String,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// The balance before `from`, including the account's opening balance when it was opened
+    /// before `from`. An account opened within the statement has its opening balance as a row.
+    pub opening_balance: i64,
+    pub rows: Vec<Row>,
+    pub closing_balance: i64,
+}
+
+// The opening balance counts from the day the account was opened, as a row of its own when that
+// is within the statement.
+//language=sql
+const ACCOUNT_SQL: &str = r#"
+select name, iif(openedDate is null or openedDate < ?2, coalesce(openingBalance, 0), 0) + coalesce(
+    (select sum(ds.total) from account_daily_sums ds where ds.account = name and ?2 is not null and ds.transDate < ?2),
+    0
+)
+from accounts
+where name = trim(?1) collate nocase
+"#;
+
+//language=sql
+const ROWS_SQL: &str = r#"
+with entries as (
+    select id, transDate, updatedDate, description, oppositeAccount, amount, 1 as priority
+    from account_transactions
+    where account = trim(?1) collate nocase
+
+    union all
+
+    select '', openedDate, null, 'Opening balance', '', openingBalance, 0
+    from accounts_meta
+    where name = trim(?1) collate nocase
+      and openingBalance is not null
+      and openedDate is not null
+)
+select id,
+       transDate,
+       description,
+       oppositeAccount,
+       amount,
+       ?4 + sum(amount) over (order by transDate, priority, updatedDate, id) as balance
+from entries
+where (?2 is null or transDate >= ?2)
+  and (?3 is null or transDate <= ?3)
+order by transDate, priority, updatedDate, id
+"#;
+
+pub async fn statement(state: &AppState, name: &str, input: &Input) -> Result<Statement> {
+    let (account, opening_balance): (String, i64) = sqlx::query_as(ACCOUNT_SQL)
+        .bind(name)
+        .bind(input.from)
+        .fetch_optional(&state.conn)
+        .await?
+        .ok_or(Error::ResourceNotFound)?;
+
+    let rows: Vec<Row> = sqlx::query_as(ROWS_SQL)
+        .bind(&account)
+        .bind(input.from)
+        .bind(input.to)
+        .bind(opening_balance)
+        .fetch_all(&state.conn)
+        .await?;
+
+    Ok(Statement {
+        account,
+        from: input.from,
+        to: input.to,
+        opening_balance,
+        closing_balance: rows.last().map_or(opening_balance, |r| r.balance),
+        rows,
+    })
+}
+
+/// Quotes `v` when needed, and puts a `'` before text a spreadsheet would run as a formula.
+fn csv_field(v: &str) -> String {
+    let v = if v.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{v}")
+    } else {
+        v.to_string()
+    };
+    if v.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", v.replace('"', "\"\""))
+    } else {
+        v
+    }
+}
+
+fn format_cents(v: i64) -> String {
+    let sign = if v < 0 { "-" } else { "" };
+    format!(
+        "{sign}{}.{:02}",
+        v.unsigned_abs() / 100,
+        v.unsigned_abs() % 100
+    )
+}
+
+impl Statement {
+    /// Writes the statement as CSV, with the opening and closing balances as the first and last
+    /// lines so the file stands on its own.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("Date,Id,Description,Account,Amount,Balance\n");
+        let mut line =
+            |date: &str, id: &str, desc: &str, account: &str, amount: &str, balance: i64| {
+                let _ = writeln!(
+                    csv,
+                    "{date},{},{},{},{amount},{}",
+                    csv_field(id),
+                    csv_field(desc),
+                    csv_field(account),
+                    format_cents(balance)
+                );
+            };
+
+        let date = |d: Option<NaiveDate>| d.map(|d| d.to_string()).unwrap_or_default();
+        line(
+            &date(self.from),
+            "",
+            "Opening balance",
+            "",
+            "",
+            self.opening_balance,
+        );
+        for row in &self.rows {
+            line(
+                &row.trans_date.to_string(),
+                &row.id,
+                &row.description,
+                &row.opposite_account,
+                &format_cents(row.amount),
+                row.balance,
+            );
+        }
+        line(
+            &date(self.to),
+            "",
+            "Closing balance",
+            "",
+            "",
+            self.closing_balance,
+        );
+        csv
+    }
+}
+
+pub async fn execute(
+    state: State<AppState>,
+    Path(name): Path<String>,
+    Query(input): Query<Input>,
+) -> Result<Response> {
+    let statement = statement(&state, &name, &input).await?;
+
+    Ok(match input.format {
+        Format::Json => Json(statement).into_response(),
+        Format::Csv => Response::builder()
+            .header("Content-Type", "text/csv; charset=utf-8")
+            .header(
+                "Content-Disposition",
+                format!(
+                    "attachment; filename=\"statement.csv\"; filename*=UTF-8''{}.csv",
+                    percent_encode(&statement.account)
+                ),
+            )
+            .body(statement.to_csv().into())
+            .context("Creating response")?,
+    })
+}
+
+fn percent_encode(v: &str) -> String {
+    v.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
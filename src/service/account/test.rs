@@ -0,0 +1,793 @@
+use std::time::SystemTime;
+
+use axum::extract::{Json, State};
+use chrono::{DateTime, NaiveDate};
+use itertools::Itertools;
+
+use crate::service::transaction::{model::Transaction, save};
+use crate::sqlx_ext;
+use crate::state::AppState;
+
+use super::*;
+
+pub fn new_transaction(id: &str, from: &str, to: &str, amount: i64, date: &str) -> Transaction {
+    Transaction {
+        id: id.to_string(),
+        description: "desc".to_string(),
+        from_account: from.to_string(),
+        to_account: to.to_string(),
+        amount,
+        trans_date: date.to_string(),
+        updated_date: DateTime::from(SystemTime::now()),
+        attachments: sqlx_ext::Json(vec![]),
+        tags: sqlx_ext::Json(vec![]),
+    }
+}
+
+async fn balances(state: &State<AppState>) -> Vec<(String, i64, NaiveDate)> {
+    list::execute(state.clone(), Json(Default::default()))
+        .await
+        .expect("To list accounts")
+        .0
+        .into_iter()
+        .map(|a| (a.meta.name, a.balance, a.last_trans_date.unwrap()))
+        .sorted()
+        .collect_vec()
+}
+
+async fn assert_consistent(state: &State<AppState>) {
+    let output = check::check(state).await.expect("To check");
+    assert_eq!(output.mismatches, vec![]);
+    assert!(output.consistent);
+}
+
+#[tokio::test]
+async fn balances_follow_transactions() {
+    let state = State(AppState::new_test().await);
+    let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+
+    let _ = save::execute(
+        state.clone(),
+        Json(vec![
+            new_transaction("1", "Bank", "Groceries", 100, "2020-01-01"),
+            new_transaction("2", "bank ", "Rent", 250, "2020-01-02"),
+        ]),
+    )
+    .await
+    .expect("To save");
+    assert_consistent(&state).await;
+    assert_eq!(
+        balances(&state).await,
+        vec![
+            ("Bank".to_string(), -350, date("2020-01-02")),
+            ("Groceries".to_string(), 100, date("2020-01-01")),
+            ("Rent".to_string(), 250, date("2020-01-02")),
+        ]
+    );
+
+    // Replacing a transaction moves its amount to the new accounts and date, and keeps rows
+    // referring to it valid
+    sqlx::query(
+        "insert into imports (id, uploaded_at, file_name) values ('i', current_timestamp, 'f'); \
+         insert into import_transactions (import_id, transaction_id) values ('i', '1')",
+    )
+    .execute(&state.conn)
+    .await
+    .expect("To record an import");
+    let _ = save::execute(
+        state.clone(),
+        Json(vec![new_transaction(
+            "1",
+            "Bank",
+            "Transport",
+            40,
+            "2020-01-03",
+        )]),
+    )
+    .await
+    .expect("To replace");
+    assert_consistent(&state).await;
+    assert_eq!(
+        balances(&state).await,
+        vec![
+            ("Bank".to_string(), -290, date("2020-01-03")),
+            ("Rent".to_string(), 250, date("2020-01-02")),
+            ("Transport".to_string(), 40, date("2020-01-03")),
+        ]
+    );
+
+    // Direct updates and deletes are followed too
+    sqlx::query("update transactions set amount = 300 where id = '2'")
+        .execute(&state.conn)
+        .await
+        .expect("To update");
+    sqlx::query("delete from import_transactions; delete from transactions where id = '1'")
+        .execute(&state.conn)
+        .await
+        .expect("To delete");
+    assert_consistent(&state).await;
+    assert_eq!(
+        balances(&state).await,
+        vec![
+            ("Bank".to_string(), -300, date("2020-01-02")),
+            ("Rent".to_string(), 300, date("2020-01-02")),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn rebuild_restores_balances() {
+    let state = State(AppState::new_test().await);
+    let _ = save::execute(
+        state.clone(),
+        Json(vec![new_transaction(
+            "1",
+            "Bank",
+            "Groceries",
+            100,
+            "2020-01-01",
+        )]),
+    )
+    .await
+    .expect("To save");
+
+    sqlx::query("update account_balances set balance = 0; delete from account_daily_sums")
+        .execute(&state.conn)
+        .await
+        .expect("To corrupt balances");
+    let output = check::check(&state).await.expect("To check");
+    assert!(!output.consistent);
+    assert_eq!(output.mismatches.len(), 4);
+
+    let _ = rebuild::rebuild(&state).await.expect("To rebuild");
+    assert_consistent(&state).await;
+}
+
+#[tokio::test]
+async fn account_meta_works() {
+    use super::model::{AccountMeta, AccountType};
+
+    let state = State(AppState::new_test().await);
+    let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok();
+    let meta = |name: &str, account_type: AccountType| AccountMeta {
+        name: name.to_string(),
+        account_type: Some(account_type),
+        currency: Some("nzd ".to_string()),
+        notes: None,
+        opened_date: None,
+        closed_date: None,
+        opening_balance: None,
+    };
+
+    let _ = save::execute(
+        state.clone(),
+        Json(vec![new_transaction(
+            "1",
+            "Bank",
+            "Groceries",
+            100,
+            "2020-01-01",
+        )]),
+    )
+    .await
+    .expect("To save");
+
+    let _ = super::save::execute(
+        state.clone(),
+        Json(vec![
+            AccountMeta {
+                opening_balance: Some(1000),
+                opened_date: date("2019-12-01"),
+                ..meta("bank", AccountType::Asset)
+            },
+            meta("Groceries", AccountType::Expense),
+            AccountMeta {
+                closed_date: date("2020-06-01"),
+                ..meta("Old savings", AccountType::Asset)
+            },
+        ]),
+    )
+    .await
+    .expect("To save meta");
+
+    let list = |input: list::Input| {
+        let state = state.clone();
+        async move {
+            list::execute(state, Json(input))
+                .await
+                .expect("To list")
+                .0
+                .into_iter()
+                .map(|a| (a.meta.name, a.balance, a.closed))
+                .sorted()
+                .collect_vec()
+        }
+    };
+
+    assert_eq!(
+        list(Default::default()).await,
+        vec![
+            ("Bank".to_string(), 900, false),
+            ("Groceries".to_string(), 100, false),
+            ("Old savings".to_string(), 0, true),
+        ]
+    );
+    assert_eq!(
+        list(list::Input {
+            types: Some(sqlx_ext::Json(vec![AccountType::Asset])),
+            include_closed: false,
+            ..Default::default()
+        })
+        .await,
+        vec![("Bank".to_string(), 900, false)]
+    );
+
+    let metas = meta::execute(state.clone()).await.expect("To get meta").0;
+    assert_eq!(metas.len(), 3);
+    assert_eq!(metas[0].currency.as_deref(), Some("NZD"));
+
+    let _ = delete::execute(state.clone(), Json(vec!["old savings".to_string()]))
+        .await
+        .expect("To delete meta");
+    assert_eq!(
+        list(Default::default()).await.len(),
+        2,
+        "Accounts with metadata only are gone once it's deleted"
+    );
+}
+
+#[tokio::test]
+async fn rename_and_merge_works() {
+    let state = State(AppState::new_test().await);
+    let _ = save::execute(
+        state.clone(),
+        Json(vec![
+            new_transaction("1", "Bank", "Groceies", 100, "2020-01-01"),
+            new_transaction("2", "Bank", "Countdown", 50, "2020-01-02"),
+            new_transaction("3", "Countdown", "groceies", 10, "2020-01-03"),
+        ]),
+    )
+    .await
+    .expect("To save");
+    sqlx::query(
+        "insert into account_groups (groupName, accountName) values ('Food', 'groceies'), ('Food', 'Countdown'); \
+         insert into mappings (mapping_type, source, dest) values ('account', 'COUNTDOWN 123', 'countdown')",
+    )
+    .execute(&state.conn)
+    .await
+    .expect("To add groups and mappings");
+
+    // Renaming into an existing account is refused
+    assert!(rename::execute(
+        state.clone(),
+        Json(rename::Input {
+            from: "Groceies".to_string(),
+            to: "countdown".to_string(),
+        }),
+    )
+    .await
+    .is_err());
+
+    let output = rename::execute(
+        state.clone(),
+        Json(rename::Input {
+            from: "groceies".to_string(),
+            to: "Groceries".to_string(),
+        }),
+    )
+    .await
+    .expect("To rename")
+    .0;
+    assert_eq!(output.transactions, 2);
+    assert_eq!(output.account_groups, 1);
+
+    // Merging would turn transaction 3 into a transfer from Groceries to itself
+    assert!(merge::execute(
+        state.clone(),
+        Json(merge::Input {
+            sources: vec!["Countdown".to_string()],
+            target: "Groceries".to_string(),
+        }),
+    )
+    .await
+    .is_err());
+    assert_consistent(&state).await;
+
+    sqlx::query("delete from transactions where id = '3'")
+        .execute(&state.conn)
+        .await
+        .expect("To delete");
+    let output = merge::execute(
+        state.clone(),
+        Json(merge::Input {
+            sources: vec!["Countdown".to_string()],
+            target: "Groceries".to_string(),
+        }),
+    )
+    .await
+    .expect("To merge")
+    .0;
+    assert_eq!(
+        output,
+        merge::Output {
+            transactions: 1,
+            account_groups: 1,
+            mappings: 1,
+            accounts_meta: 0,
+            rules: 0,
+            budgets: 0,
+            envelope_accounts: 0,
+            recurring_transactions: 0,
+        }
+    );
+
+    assert_consistent(&state).await;
+    assert_eq!(
+        balances(&state)
+            .await
+            .into_iter()
+            .map(|(name, balance, _)| (name, balance))
+            .collect_vec(),
+        vec![("Bank".to_string(), -150), ("Groceries".to_string(), 150)]
+    );
+
+    let groups: Vec<(String, String)> =
+        sqlx::query_as("select groupName, accountName from account_groups")
+            .fetch_all(&state.conn)
+            .await
+            .expect("To list groups");
+    assert_eq!(groups, vec![("Food".to_string(), "Groceries".to_string())]);
+
+    let (dest,): (String,) = sqlx::query_as("select dest from mappings")
+        .fetch_one(&state.conn)
+        .await
+        .expect("To get mapping");
+    assert_eq!(dest, "Groceries");
+}
+
+#[tokio::test]
+async fn rename_and_merge_rewrite_references() {
+    let state = State(AppState::new_test().await);
+    let _ = save::execute(
+        state.clone(),
+        Json(vec![
+            new_transaction("1", "Bank", "Groceies", 100, "2020-01-01"),
+            new_transaction("2", "Bank", "Countdown", 50, "2020-01-02"),
+        ]),
+    )
+    .await
+    .expect("To save");
+    sqlx::query(
+        "insert into transaction_rules (name, fromAccount, toAccount, setToAccount) \
+         values ('Groceries', 'bank', 'Expenses', 'countdown'); \
+         insert into budgets (name, account, period, amount, startDate) \
+         values ('Groceries', 'COUNTDOWN', 'Monthly', 100, '2020-01-01'); \
+         insert into envelopes (name) values ('Food'), ('Banking'); \
+         insert into envelope_accounts (account, envelope) \
+         values ('Countdown', 'Food'), ('Groceries', 'Food'), ('Bank', 'Banking'); \
+         insert into envelope_income_accounts (account) values ('Bank'); \
+         insert into recurring_transactions (description, fromAccount, toAccount, amount, cadence, startDate) \
+         values ('Groceries', 'bank', 'countdown', 100, 'weekly', '2020-01-01')",
+    )
+    .execute(&state.conn)
+    .await
+    .expect("To add references");
+
+    let output = rename::execute(
+        state.clone(),
+        Json(rename::Input {
+            from: "Bank".to_string(),
+            to: "Cheque".to_string(),
+        }),
+    )
+    .await
+    .expect("To rename")
+    .0;
+    assert_eq!(output.rules, 1);
+    assert_eq!(output.envelope_accounts, 2);
+    assert_eq!(output.recurring_transactions, 1);
+
+    let output = merge::execute(
+        state.clone(),
+        Json(merge::Input {
+            sources: vec!["Countdown".to_string()],
+            target: "Groceries".to_string(),
+        }),
+    )
+    .await
+    .expect("To merge")
+    .0;
+    assert_eq!(output.rules, 1);
+    assert_eq!(output.budgets, 1);
+
+    let rules: Vec<(String, String, String)> =
+        sqlx::query_as("select fromAccount, toAccount, setToAccount from transaction_rules")
+            .fetch_all(&state.conn)
+            .await
+            .expect("To list rules");
+    assert_eq!(
+        rules,
+        vec![(
+            "Cheque".to_string(),
+            "Expenses".to_string(),
+            "Groceries".to_string()
+        )]
+    );
+
+    let (budget,): (String,) = sqlx::query_as("select account from budgets")
+        .fetch_one(&state.conn)
+        .await
+        .expect("To get budget");
+    assert_eq!(budget, "Groceries");
+
+    // Groceries was already mapped, so Countdown's mapping goes rather than clash with it
+    let envelope_accounts: Vec<(String, String)> =
+        sqlx::query_as("select account, envelope from envelope_accounts order by account")
+            .fetch_all(&state.conn)
+            .await
+            .expect("To list envelope accounts");
+    assert_eq!(
+        envelope_accounts,
+        vec![
+            ("Cheque".to_string(), "Banking".to_string()),
+            ("Groceries".to_string(), "Food".to_string()),
+        ]
+    );
+    let (income,): (String,) = sqlx::query_as("select account from envelope_income_accounts")
+        .fetch_one(&state.conn)
+        .await
+        .expect("To get income account");
+    assert_eq!(income, "Cheque");
+
+    let recurring: (String, String) =
+        sqlx::query_as("select fromAccount, toAccount from recurring_transactions")
+            .fetch_one(&state.conn)
+            .await
+            .expect("To get recurring transaction");
+    assert_eq!(recurring, ("Cheque".to_string(), "Groceries".to_string()));
+}
+
+#[tokio::test]
+async fn hierarchy_works() {
+    use crate::service::{report, transaction};
+
+    let state = State(AppState::new_test().await);
+    let _ = save::execute(
+        state.clone(),
+        Json(vec![
+            new_transaction("1", "Bank", "Expenses:Food:Groceries", 100, "2020-01-01"),
+            new_transaction("2", "Bank", "expenses:food:Dining", 50, "2020-01-02"),
+            new_transaction("3", "Bank", "Expenses:Rent", 200, "2020-01-03"),
+            new_transaction("4", "Bank", "Expenses Fund", 5, "2020-01-04"),
+        ]),
+    )
+    .await
+    .expect("To save");
+
+    let tree = tree::execute(
+        state.clone(),
+        Json(list::Input {
+            sorts: vec![list::Sort::new(
+                list::SortField::Name,
+                crate::service::SortOrder::ASC,
+            )]
+            .into(),
+            ..Default::default()
+        }),
+    )
+    .await
+    .expect("To get the tree")
+    .0;
+
+    fn flatten(
+        nodes: &[tree::AccountNode],
+        depth: usize,
+        out: &mut Vec<(usize, String, bool, i64)>,
+    ) {
+        for node in nodes {
+            out.push((
+                depth,
+                node.name.clone(),
+                node.account.is_some(),
+                node.subtree_balance,
+            ));
+            flatten(&node.children, depth + 1, out);
+        }
+    }
+    let mut nodes = vec![];
+    flatten(&tree, 0, &mut nodes);
+    assert_eq!(
+        nodes,
+        vec![
+            (0, "Bank".to_string(), true, -355),
+            (0, "Expenses Fund".to_string(), true, 5),
+            (0, "Expenses".to_string(), false, 350),
+            (1, "Expenses:Food".to_string(), false, 150),
+            (2, "Expenses:Food:Groceries".to_string(), true, 100),
+            (2, "expenses:food:Dining".to_string(), true, 50),
+            (1, "Expenses:Rent".to_string(), true, 200),
+        ]
+    );
+
+    // A parent name matches all the accounts under it, but not accounts merely sharing a prefix
+    let output = transaction::list::execute(
+        state.clone(),
+        Json(transaction::list::Input {
+            accounts: Some(sqlx_ext::Json(vec!["EXPENSES:food".to_string()])),
+            ..Default::default()
+        }),
+    )
+    .await
+    .expect("To list transactions")
+    .0;
+    assert_eq!(
+        output
+            .data
+            .iter()
+            .map(|t| t.id.as_str())
+            .sorted()
+            .collect_vec(),
+        vec!["1", "2"]
+    );
+
+    let output = transaction::list::execute(
+        state.clone(),
+        Json(transaction::list::Input {
+            query: Some("to:Expenses".to_string()),
+            ..Default::default()
+        }),
+    )
+    .await
+    .expect("To list transactions")
+    .0;
+    assert_eq!(output.total, Some(3));
+
+    let output = report::balance::execute(
+        state.clone(),
+        Json(report::balance::Input {
+            accounts: sqlx_ext::Json(vec!["Expenses".to_string(), "Expenses:Food".to_string()]),
+            ..Default::default()
+        }),
+    )
+    .await
+    .expect("To report balances")
+    .0;
+    assert_eq!(output.last().map(|r| r.balance), Some(350));
+
+    // Without a separator, names are flat
+    sqlx::query("insert into configs (name, id, value) values ('client', 'accountSeparator', '')")
+        .execute(&state.conn)
+        .await
+        .expect("To save config");
+    let output = transaction::list::execute(
+        state.clone(),
+        Json(transaction::list::Input {
+            accounts: Some(sqlx_ext::Json(vec!["Expenses:Food".to_string()])),
+            ..Default::default()
+        }),
+    )
+    .await
+    .expect("To list transactions")
+    .0;
+    assert_eq!(output.total, Some(0));
+}
+
+#[tokio::test]
+async fn balance_as_of_and_statement_works() {
+    let state = State(AppState::new_test().await);
+    let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+    let _ = save::execute(
+        state.clone(),
+        Json(vec![
+            new_transaction("1", "Bank", "Groceries", 100, "2020-01-01"),
+            new_transaction("2", "Groceries", "bank", 25, "2020-01-05"),
+            new_transaction("3", "Bank", "Rent", 250, "2020-01-10"),
+        ]),
+    )
+    .await
+    .expect("To save");
+    let _ = super::save::execute(
+        state.clone(),
+        Json(vec![model::AccountMeta {
+            name: "Bank".to_string(),
+            account_type: Some(model::AccountType::Asset),
+            currency: None,
+            notes: None,
+            opened_date: None,
+            closed_date: Some(date("2020-01-20")),
+            opening_balance: Some(1000),
+        }]),
+    )
+    .await
+    .expect("To save meta");
+
+    let accounts = list::execute(
+        state.clone(),
+        Json(list::Input {
+            as_of: Some(date("2020-01-05")),
+            ..Default::default()
+        }),
+    )
+    .await
+    .expect("To list accounts")
+    .0
+    .into_iter()
+    .map(|a| (a.meta.name, a.balance, a.last_trans_date, a.closed))
+    .sorted()
+    .collect_vec();
+    assert_eq!(
+        accounts,
+        vec![
+            ("Bank".to_string(), 925, Some(date("2020-01-05")), false),
+            ("Groceries".to_string(), 75, Some(date("2020-01-05")), false),
+            ("Rent".to_string(), 0, None, false),
+        ]
+    );
+
+    let input = statement::Input {
+        from: Some(date("2020-01-02")),
+        to: Some(date("2020-01-31")),
+        ..Default::default()
+    };
+    let output = statement::statement(&state, "BANK", &input)
+        .await
+        .expect("To get statement");
+    assert_eq!(output.account, "Bank");
+    assert_eq!(output.opening_balance, 900);
+    assert_eq!(
+        output
+            .rows
+            .iter()
+            .map(|r| (
+                r.id.as_str(),
+                r.opposite_account.as_str(),
+                r.amount,
+                r.balance
+            ))
+            .collect_vec(),
+        vec![("2", "Groceries", 25, 925), ("3", "Rent", -250, 675)]
+    );
+    assert_eq!(output.closing_balance, 675);
+    assert_eq!(
+        output.to_csv(),
+        "Date,Id,Description,Account,Amount,Balance\n\
+         2020-01-02,,Opening balance,,,9.00\n\
+         2020-01-05,2,desc,Groceries,0.25,9.25\n\
+         2020-01-10,3,desc,Rent,-2.50,6.75\n\
+         2020-01-31,,Closing balance,,,6.75\n"
+    );
+
+    // Nothing in the CSV runs as a formula in a spreadsheet
+    let mut refund = new_transaction("4", "-Misc", "Bank", 5, "2020-01-15");
+    refund.description = "=1+1, \"refund\"".to_string();
+    let mut tab = new_transaction("5", "Bank", "Misc", 0, "2020-01-16");
+    tab.description = "\t@SUM(A1)".to_string();
+    let mut cr = new_transaction("6", "Bank", "Misc", 0, "2020-01-17");
+    cr.description = "\r=A1".to_string();
+    let _ = save::execute(state.clone(), Json(vec![refund, tab, cr]))
+        .await
+        .expect("To save");
+    let input = statement::Input {
+        from: Some(date("2020-01-11")),
+        to: Some(date("2020-01-31")),
+        ..Default::default()
+    };
+    assert_eq!(
+        statement::statement(&state, "Bank", &input)
+            .await
+            .expect("To get statement")
+            .to_csv(),
+        "Date,Id,Description,Account,Amount,Balance\n\
+         2020-01-11,,Opening balance,,,6.75\n\
+         2020-01-15,4,\"'=1+1, \"\"refund\"\"\",'-Misc,0.05,6.80\n\
+         2020-01-16,5,'\t@SUM(A1),Misc,0.00,6.80\n\
+         2020-01-17,6,\"'\r=A1\",Misc,0.00,6.80\n\
+         2020-01-31,,Closing balance,,,6.80\n"
+    );
+
+    assert!(matches!(
+        statement::statement(&state, "Nope", &input).await,
+        Err(crate::service::Error::ResourceNotFound)
+    ));
+
+    // The routes don't conflict
+    let _ = router();
+}
+
+#[tokio::test]
+async fn opening_balance_counts_from_opened_date() {
+    use crate::service::report::balance_sheet;
+
+    let state = State(AppState::new_test().await);
+    let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+    let meta = |name: &str, opened_date, opening_balance| model::AccountMeta {
+        name: name.to_string(),
+        account_type: Some(model::AccountType::Asset),
+        currency: None,
+        notes: None,
+        opened_date,
+        closed_date: None,
+        opening_balance,
+    };
+    let _ = super::save::execute(
+        state.clone(),
+        Json(vec![
+            meta("Bank", None, Some(1000)),
+            meta("Cash", Some(date("2020-02-10")), Some(70)),
+        ]),
+    )
+    .await
+    .expect("To save meta");
+    let _ = save::execute(
+        state.clone(),
+        Json(vec![
+            new_transaction("1", "Bank", "Cash", 5, "2020-02-12"),
+            new_transaction("2", "Bank", "Cash", 10, "2020-01-05"),
+        ]),
+    )
+    .await
+    .expect("To save");
+
+    // The account list agrees with the balance sheet on every day
+    for as_of in ["2020-01-31", "2020-02-09", "2020-02-10", "2020-02-12"] {
+        let accounts = list::execute(
+            state.clone(),
+            Json(list::Input {
+                as_of: Some(date(as_of)),
+                ..Default::default()
+            }),
+        )
+        .await
+        .expect("To list accounts")
+        .0
+        .into_iter()
+        .map(|a| (a.meta.name, a.balance))
+        .sorted()
+        .collect_vec();
+        let sheet = balance_sheet::execute(
+            state.clone(),
+            axum::extract::Query(balance_sheet::Input {
+                as_of: Some(date(as_of)),
+            }),
+        )
+        .await
+        .expect("To report")
+        .0;
+        let sheet = sheet
+            .assets
+            .iter()
+            .map(|l| (l.name.clone(), l.amounts[0]))
+            .collect_vec();
+        assert_eq!(accounts, sheet, "as of {as_of}");
+    }
+
+    let statement = |from: &str| {
+        let state = state.clone();
+        let input = statement::Input {
+            from: Some(date(from)),
+            to: Some(date("2020-02-29")),
+            ..Default::default()
+        };
+        async move {
+            statement::statement(&state, "Cash", &input)
+                .await
+                .expect("To get statement")
+        }
+    };
+    let output = statement("2020-02-01").await;
+    assert_eq!(output.opening_balance, 10);
+    assert_eq!(
+        output
+            .rows
+            .iter()
+            .map(|r| (r.description.as_str(), r.amount, r.balance))
+            .collect_vec(),
+        vec![("Opening balance", 70, 80), ("desc", 5, 85)]
+    );
+    assert_eq!(output.closing_balance, 85);
+
+    let output = statement("2020-02-11").await;
+    assert_eq!(output.opening_balance, 80);
+    assert_eq!(output.rows.len(), 1);
+    assert_eq!(output.closing_balance, 85);
+}
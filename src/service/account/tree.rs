@@ -0,0 +1,117 @@
+use axum::extract::{self, State};
+
+use crate::{
+    service::{config::client::account_separator, Result},
+    state::AppState,
+};
+
+use super::list::{query, Account, Input};
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountNode {
+    /// The full name, e.g. "Expenses:Food".
+    pub name: String,
+    /// The last level of the name, e.g. "Food".
+    pub label: String,
+    /// The account itself, or `None` for a parent that only exists through its children.
+    pub account: Option<Account>,
+    /// The balance of this account plus all accounts under it.
+    pub subtree_balance: i64,
+    pub children: Vec<AccountNode>,
+}
+
+impl AccountNode {
+    fn new(name: String, label: String) -> Self {
+        Self {
+            name,
+            label,
+            account: None,
+            subtree_balance: 0,
+            children: Default::default(),
+        }
+    }
+
+    fn roll_up(&mut self) -> i64 {
+        let children: i64 = self.children.iter_mut().map(AccountNode::roll_up).sum();
+        self.subtree_balance = children + self.account.as_ref().map_or(0, |a| a.balance);
+        self.subtree_balance
+    }
+}
+
+/// A node of a tree of names, where a name's parents are the names before each separator in it,
+/// e.g. "Expenses" for "Expenses:Food".
+pub trait Node: Sized {
+    /// The last level of the name, e.g. "Food".
+    fn label(&self) -> &str;
+    fn children_mut(&mut self) -> &mut Vec<Self>;
+}
+
+/// The node of `name` under `roots`, adding it and any parents missing on the way with `new`,
+/// which is given the full name and the label. Levels are matched ignoring case.
+pub fn node_mut<'a, N: Node>(
+    roots: &'a mut Vec<N>,
+    name: &str,
+    separator: &str,
+    mut new: impl FnMut(String, String) -> N,
+) -> &'a mut N {
+    let labels: Vec<&str> = if separator.is_empty() {
+        vec![name]
+    } else {
+        name.split(separator).collect()
+    };
+
+    let mut siblings = roots;
+    for (i, label) in labels.iter().enumerate() {
+        let index = match siblings
+            .iter()
+            .position(|n| n.label().eq_ignore_ascii_case(label))
+        {
+            Some(index) => index,
+            None => {
+                siblings.push(new(labels[..=i].join(separator), label.to_string()));
+                siblings.len() - 1
+            }
+        };
+        if i + 1 == labels.len() {
+            return &mut siblings[index];
+        }
+        siblings = siblings[index].children_mut();
+    }
+    unreachable!("Splitting a name gives at least one label")
+}
+
+impl Node for AccountNode {
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn children_mut(&mut self) -> &mut Vec<Self> {
+        &mut self.children
+    }
+}
+
+/// Arranges accounts by their names, keeping the order of `accounts` among siblings.
+pub fn build_tree(accounts: Vec<Account>, separator: &str) -> Vec<AccountNode> {
+    let mut roots: Vec<AccountNode> = Default::default();
+
+    for account in accounts {
+        let node = node_mut(&mut roots, &account.meta.name, separator, AccountNode::new);
+        node.name = account.meta.name.clone();
+        node.account = Some(account);
+    }
+
+    for root in &mut roots {
+        root.roll_up();
+    }
+    roots
+}
+
+pub async fn execute(
+    state: State<AppState>,
+    extract::Json(input): extract::Json<Input>,
+) -> Result<extract::Json<Vec<AccountNode>>> {
+    let separator = account_separator(&state.conn).await?;
+    let accounts = query(&state, input).await?;
+    Ok(build_tree(accounts, &separator).into())
+}
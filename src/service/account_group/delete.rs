@@ -18,10 +18,28 @@ pub async fn execute(
     state: extract::State<AppState>,
     extract::Query(Input { group_names }): extract::Query<Input>,
 ) -> Result<extract::Json<Output>> {
+    let mut tx = state.conn.begin().await?;
     let output: Output = sqlx::query(
-        "delete from account_groups where groupName in (select trim(value) from json_each(?)) collate nocase"
+        "delete from account_groups where groupName collate nocase in (select trim(value) from json_each(?))"
     )
-    .bind(group_names)
-    .execute(&state.conn).await?.into();
+    .bind(&group_names)
+    .execute(&mut *tx).await?.into();
+
+    // Deleting a group also takes it out of the groups including it
+    sqlx::query(
+        "delete from account_group_members where groupName in (select trim(value) from json_each(?1)) \
+         or memberGroup in (select trim(value) from json_each(?1))",
+    )
+    .bind(&group_names)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        "delete from account_group_rules where groupName in (select trim(value) from json_each(?))",
+    )
+    .bind(&group_names)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
     Ok(extract::Json::from(output))
 }
@@ -7,18 +7,56 @@ use crate::state::AppState;
 
 mod delete;
 mod list;
-mod models;
-mod save;
+pub mod models;
+mod preview;
+pub mod save;
 
 #[cfg(test)]
 mod test;
 
+/// Selects `(series, account, weight)` for the accounts picked by the JSON arrays of account
+/// names bound at `?{accounts}` and of group names bound at `?{groups}`.
+///
+/// Account names include the accounts under them, and groups include their members with their
+/// weights. When the SQL expression `split` is true, each name given is a series of its own;
+/// otherwise `series` is null. Each account is counted once per series, taking the weight from
+/// the first name that picks it.
+pub fn input_accounts_sql(accounts: usize, groups: usize, split: &str) -> String {
+    format!(
+        r#"
+        with selected(selector, account, weight, position) as (
+            select trim(j.value), aa.account, 1.0, j.key
+            from json_each(?{accounts}) j
+            inner join account_ancestors aa on aa.ancestor = trim(j.value) collate nocase
+
+            union all
+
+            select trim(g.value), aa.account, ga.weight, ifnull(json_array_length(?{accounts}), 0) + g.key
+            from json_each(?{groups}) g
+            inner join account_group_accounts ga on ga.groupName = trim(g.value) collate nocase
+            inner join account_ancestors aa on aa.ancestor = ga.accountName collate nocase
+        )
+        select series, account, weight
+        from (
+            select iif({split}, selector, null) as series, account, weight,
+                   row_number() over (
+                       partition by iif({split}, lower(selector), null), lower(account)
+                       order by position
+                   ) as n
+            from selected
+        )
+        where n = 1
+        "#
+    )
+}
+
 pub fn router() -> Router<AppState> {
     Router::new().nest(
         "/api/accountGroups",
         Router::new()
             .route("/", get(list::execute))
             .route("/", post(save::execute))
-            .route("/", delete(delete::execute)),
+            .route("/", delete(delete::execute))
+            .route("/preview", post(preview::execute)),
     )
 }
@@ -5,5 +5,57 @@ use crate::sqlx_ext::Json;
 #[sqlx(rename_all = "camelCase")]
 pub struct AccountGroup {
     pub group_name: String,
+    /// Accounts counted as they are.
     pub accounts: Json<Vec<String>>,
+    /// Other groups, and accounts counted with a weight other than 1. When saving, leaving this
+    /// out keeps the existing members.
+    pub members: Option<Json<Vec<Member>>>,
+    /// Rules adding every account they match. When saving, leaving this out keeps the existing
+    /// rules.
+    pub rules: Option<Json<Vec<Rule>>>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum MemberType {
+    Account,
+    Group,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Member {
+    #[serde(rename = "type")]
+    pub member_type: MemberType,
+    pub name: String,
+    /// Multiplies the member's amounts, e.g. -1 to subtract debts.
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+}
+
+const fn default_weight() -> f64 {
+    1.0
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum RuleType {
+    /// The account name starts with the value, ignoring case.
+    Prefix,
+    /// The account name matches the regular expression.
+    Regex,
+    /// The account has the type, see [`crate::service::account::model::AccountType`].
+    Type,
+    /// Any of the account's transactions has the tag.
+    Tag,
+    /// The account has the currency.
+    Currency,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    #[serde(rename = "type")]
+    pub rule_type: RuleType,
+    pub value: String,
 }
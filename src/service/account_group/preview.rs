@@ -0,0 +1,35 @@
+use axum::{extract::State, Json};
+
+use crate::{service::Result, state::AppState};
+
+use super::models::AccountGroup;
+
+#[derive(sqlx::FromRow, serde::Serialize, Debug, PartialEq)]
+#[sqlx(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct GroupAccount {
+    pub account_name: String,
+    pub weight: f64,
+}
+
+/// Lists the accounts a group would include if it were saved as given, without saving it.
+pub async fn execute(
+    state: State<AppState>,
+    Json(group): Json<AccountGroup>,
+) -> Result<Json<Vec<GroupAccount>>> {
+    let mut tx = state.conn.begin().await?;
+    let group_name = group.group_name.trim().to_string();
+    let _ = super::save::save(&mut tx, vec![group]).await?;
+
+    let accounts = sqlx::query_as(
+        "select accountName, weight from account_group_accounts \
+         where groupName = ? collate nocase \
+         order by accountName collate nocase",
+    )
+    .bind(group_name)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.rollback().await?;
+    Ok(Json::from(accounts))
+}
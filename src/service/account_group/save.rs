@@ -1,34 +1,83 @@
 use axum::{extract::State, Json};
+use sqlx::{Sqlite, Transaction};
+
+use std::borrow::Cow;
 
 use crate::{
-    service::{GenericUpdateResponse, Result},
+    service::{Error, GenericUpdateResponse, Result},
     state::AppState,
 };
 
-use super::models::AccountGroup;
+use super::models::{AccountGroup, RuleType};
 
-pub async fn execute(
-    state: State<AppState>,
-    Json(groups): Json<Vec<AccountGroup>>,
-) -> Result<Json<GenericUpdateResponse>> {
-    let mut tx = state.conn.begin().await?;
+//language=sql
+const CYCLE_SQL: &str = r#"
+with recursive reachable(groupName, memberGroup) as (
+    select groupName, memberGroup from account_group_members
+    union
+    select r.groupName, m.memberGroup
+    from reachable r
+    inner join account_group_members m on m.groupName = r.memberGroup
+)
+select groupName from reachable
+where groupName = memberGroup
+limit 1
+"#;
+
+/// Saves the groups in `tx`, refusing invalid rules and groups that include themselves.
+pub async fn save(tx: &mut Transaction<'_, Sqlite>, groups: Vec<AccountGroup>) -> Result<u64> {
     let mut num_affected: u64 = 0;
 
     for AccountGroup {
         group_name,
         accounts,
+        members,
+        rules,
     } in groups
     {
+        for rule in rules.iter().flat_map(|r| r.iter()) {
+            if rule.rule_type == RuleType::Regex
+                && sqlx::query("select '' regexp ?")
+                    .bind(&rule.value)
+                    .execute(&mut **tx)
+                    .await
+                    .is_err()
+            {
+                return Err(Error::InvalidArgument(Cow::Owned(format!(
+                    "Invalid regular expression {}",
+                    rule.value
+                ))));
+            }
+        }
+
         num_affected += sqlx::query(
-            "insert into account_groups_view (groupName, accounts) values (trim(?), ?)",
+            "insert into account_groups_view (groupName, accounts, members, rules) values (trim(?), ?, ?, ?)",
         )
         .bind(group_name)
         .bind(accounts)
-        .execute(&mut *tx)
+        .bind(members)
+        .bind(rules)
+        .execute(&mut **tx)
         .await?
         .rows_affected();
     }
 
+    let cycle: Option<(String,)> = sqlx::query_as(CYCLE_SQL).fetch_optional(&mut **tx).await?;
+    if let Some((group_name,)) = cycle {
+        return Err(Error::InvalidArgument(Cow::Owned(format!(
+            "Group {group_name} includes itself"
+        ))));
+    }
+
+    Ok(num_affected)
+}
+
+pub async fn execute(
+    state: State<AppState>,
+    Json(groups): Json<Vec<AccountGroup>>,
+) -> Result<Json<GenericUpdateResponse>> {
+    let mut tx = state.conn.begin().await?;
+    let num_affected = save(&mut tx, groups).await?;
     tx.commit().await?;
     Ok(Json::from(GenericUpdateResponse {
         num_affected: num_affected as usize,
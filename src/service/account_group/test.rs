@@ -13,6 +13,8 @@ async fn account_group_rw_works() {
         vec![models::AccountGroup {
             group_name: "g1".into(),
             accounts: Json(vec!["a1".into(), "a2".into()]),
+            members: None,
+            rules: None,
         }]
         .into(),
     )
@@ -32,6 +34,8 @@ async fn account_group_rw_works() {
         vec![models::AccountGroup {
             group_name: "g1".into(),
             accounts: Json(vec!["a2".into(), "a3".into()]),
+            members: None,
+            rules: None,
         }]
         .into(),
     )
@@ -61,3 +65,297 @@ async fn account_group_rw_works() {
         0
     );
 }
+
+#[tokio::test]
+async fn nested_account_groups_work() {
+    use crate::service::{account::test::new_transaction, transaction};
+    use models::{AccountGroup, Member, MemberType};
+
+    let state = State(AppState::new_test().await);
+    let group = |name: &str, accounts: &[&str], members: Vec<Member>| AccountGroup {
+        group_name: name.to_string(),
+        accounts: Json(accounts.iter().map(|a| a.to_string()).collect()),
+        members: Some(Json(members)),
+        rules: None,
+    };
+    let member = |member_type, name: &str, weight| Member {
+        member_type,
+        name: name.to_string(),
+        weight,
+    };
+
+    let _ = save::execute(
+        state.clone(),
+        vec![
+            group("Cash", &["Wallet", "Bank"], vec![]),
+            group("Investments", &["Shares"], vec![]),
+            group(
+                "Debts",
+                &["Mortgage"],
+                vec![member(MemberType::Account, "Credit card", 2.0)],
+            ),
+            group(
+                "Net worth",
+                &[],
+                vec![
+                    member(MemberType::Group, "cash", 1.0),
+                    member(MemberType::Group, "Investments", 1.0),
+                    member(MemberType::Group, "Debts", -1.0),
+                ],
+            ),
+        ]
+        .into(),
+    )
+    .await
+    .expect("To save");
+
+    let groups = list::execute(state.clone()).await.expect("To list");
+    let net_worth = groups
+        .iter()
+        .find(|g| g.group_name == "Net worth")
+        .expect("To have net worth");
+    assert!(net_worth.accounts.is_empty());
+    assert_eq!(
+        net_worth.members.as_ref().unwrap().0,
+        vec![
+            member(MemberType::Group, "cash", 1.0),
+            member(MemberType::Group, "Debts", -1.0),
+            member(MemberType::Group, "Investments", 1.0),
+        ]
+    );
+
+    let mut accounts: Vec<(String, f64)> = sqlx::query_as(
+        "select accountName, weight from account_group_accounts where groupName = 'Net worth'",
+    )
+    .fetch_all(&state.conn)
+    .await
+    .expect("To expand groups");
+    accounts.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        accounts,
+        vec![
+            ("Bank".to_string(), 1.0),
+            ("Credit card".to_string(), -2.0),
+            ("Mortgage".to_string(), -1.0),
+            ("Shares".to_string(), 1.0),
+            ("Wallet".to_string(), 1.0),
+        ]
+    );
+
+    // Saving without members keeps them
+    let _ = save::execute(
+        state.clone(),
+        vec![AccountGroup {
+            group_name: "Debts".to_string(),
+            accounts: Json(vec!["Mortgage".to_string(), "Loan".to_string()]),
+            members: None,
+            rules: None,
+        }]
+        .into(),
+    )
+    .await
+    .expect("To save");
+    let (weight,): (f64,) = sqlx::query_as(
+        "select weight from account_group_accounts where groupName = 'Net worth' and accountName = 'Credit card'",
+    )
+    .fetch_one(&state.conn)
+    .await
+    .expect("To keep weighted accounts");
+    assert_eq!(weight, -2.0);
+
+    // A group can't include itself, directly or through others
+    assert!(save::execute(
+        state.clone(),
+        vec![group(
+            "Cash",
+            &["Wallet"],
+            vec![member(MemberType::Group, "Net worth", 1.0)],
+        )]
+        .into(),
+    )
+    .await
+    .is_err());
+    assert_eq!(
+        list::execute(state.clone()).await.expect("To list").len(),
+        4
+    );
+
+    // Nested groups filter transactions
+    let _ = transaction::save::execute(
+        state.clone(),
+        axum::Json(vec![
+            new_transaction("1", "Bank", "Groceries", 100, "2020-01-01"),
+            new_transaction("2", "Income", "Shares", 50, "2020-01-01"),
+            new_transaction("3", "Income", "Salary", 50, "2020-01-01"),
+        ]),
+    )
+    .await
+    .expect("To save transactions");
+    let output = transaction::list::execute(
+        state.clone(),
+        axum::Json(transaction::list::Input {
+            account_groups: Some(Json(vec!["Net worth".to_string()])),
+            ..Default::default()
+        }),
+    )
+    .await
+    .expect("To list transactions")
+    .0;
+    assert_eq!(output.total, Some(2));
+
+    // Deleting a group takes it out of the groups including it
+    let _ = delete::execute(
+        state.clone(),
+        Query(delete::Input {
+            group_names: Json(vec!["debts".into()]),
+        }),
+    )
+    .await
+    .expect("To delete");
+    let groups = list::execute(state.clone()).await.expect("To list");
+    let net_worth = groups
+        .iter()
+        .find(|g| g.group_name == "Net worth")
+        .expect("To have net worth");
+    assert_eq!(net_worth.members.as_ref().unwrap().0.len(), 2);
+}
+
+#[tokio::test]
+async fn rule_based_account_groups_work() {
+    use crate::service::{
+        account::{
+            model::{AccountMeta, AccountType},
+            test::new_transaction,
+        },
+        transaction,
+    };
+    use models::{AccountGroup, Rule, RuleType};
+
+    let state = State(AppState::new_test().await);
+    let mut holiday = new_transaction("3", "Bank", "Flights", 300, "2020-01-03");
+    holiday.tags = Json(vec!["Holiday".to_string()]);
+    let _ = transaction::save::execute(
+        state.clone(),
+        axum::Json(vec![
+            new_transaction("1", "Bank", "Expenses:Food", 100, "2020-01-01"),
+            new_transaction("2", "Bank", "expenses:Rent", 200, "2020-01-02"),
+            holiday,
+        ]),
+    )
+    .await
+    .expect("To save transactions");
+    let _ = crate::service::account::save::execute(
+        state.clone(),
+        axum::Json(vec![AccountMeta {
+            name: "Savings".to_string(),
+            account_type: Some(AccountType::Asset),
+            currency: Some("nzd".to_string()),
+            notes: None,
+            opened_date: None,
+            closed_date: None,
+            opening_balance: None,
+        }]),
+    )
+    .await
+    .expect("To save account");
+
+    let rule = |rule_type, value: &str| Rule {
+        rule_type,
+        value: value.to_string(),
+    };
+    let group = |rules: Vec<Rule>| AccountGroup {
+        group_name: "Spending".to_string(),
+        accounts: Json(vec!["Expenses:Food".to_string()]),
+        members: None,
+        rules: Some(Json(rules)),
+    };
+    let preview = |group: AccountGroup| {
+        let state = state.clone();
+        async move {
+            preview::execute(state, axum::Json(group))
+                .await
+                .map(|accounts| {
+                    accounts
+                        .0
+                        .into_iter()
+                        .map(|a| (a.account_name, a.weight))
+                        .collect::<Vec<_>>()
+                })
+        }
+    };
+
+    assert_eq!(
+        preview(group(vec![
+            rule(RuleType::Prefix, "EXPENSES:"),
+            rule(RuleType::Tag, "holiday"),
+        ]))
+        .await
+        .expect("To preview"),
+        vec![
+            ("Bank".to_string(), 1.0),
+            ("Expenses:Food".to_string(), 1.0),
+            ("expenses:Rent".to_string(), 1.0),
+            ("Flights".to_string(), 1.0),
+        ]
+    );
+    assert_eq!(
+        preview(group(vec![
+            rule(RuleType::Regex, "^B.nk$"),
+            rule(RuleType::Type, "asset"),
+            rule(RuleType::Currency, "NZD"),
+        ]))
+        .await
+        .expect("To preview"),
+        vec![
+            ("Bank".to_string(), 1.0),
+            ("Expenses:Food".to_string(), 1.0),
+            ("Savings".to_string(), 1.0),
+        ]
+    );
+    assert!(preview(group(vec![rule(RuleType::Regex, "(")]))
+        .await
+        .is_err());
+
+    // Previewing saves nothing
+    assert!(list::execute(state.clone())
+        .await
+        .expect("To list")
+        .is_empty());
+
+    let _ = save::execute(
+        state.clone(),
+        vec![group(vec![rule(RuleType::Prefix, "expenses:")])].into(),
+    )
+    .await
+    .expect("To save");
+    let groups = list::execute(state.clone()).await.expect("To list");
+    assert_eq!(
+        groups[0].rules.as_ref().map(|r| r.0.clone()),
+        Some(vec![rule(RuleType::Prefix, "expenses:")])
+    );
+
+    // Accounts appearing later join the group
+    let _ = transaction::save::execute(
+        state.clone(),
+        axum::Json(vec![new_transaction(
+            "4",
+            "Bank",
+            "Expenses:Power",
+            50,
+            "2020-01-04",
+        )]),
+    )
+    .await
+    .expect("To save transactions");
+    let output = transaction::list::execute(
+        state.clone(),
+        axum::Json(transaction::list::Input {
+            account_groups: Some(Json(vec!["spending".to_string()])),
+            ..Default::default()
+        }),
+    )
+    .await
+    .expect("To list transactions")
+    .0;
+    assert_eq!(output.total, Some(3));
+}
@@ -0,0 +1,33 @@
+use axum::extract::{Json, Query, State};
+
+use crate::{service::Result, state::AppState};
+
+use super::model::Alert;
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    #[serde(default)]
+    pub include_dismissed: bool,
+}
+
+// Alerts about a transaction go away with the transaction.
+//language=sql
+const SQL: &str = r#"
+select * from alerts a
+where (?1 or not a.dismissed)
+  and (a.kind not in ('unusualAmount', 'newMerchant')
+       or exists (select 1 from transactions t where t.id = a.subject))
+order by a.date desc, a.id desc
+"#;
+
+pub async fn execute(
+    state: State<AppState>,
+    Query(Input { include_dismissed }): Query<Input>,
+) -> Result<Json<Vec<Alert>>> {
+    let alerts = sqlx::query_as(SQL)
+        .bind(include_dismissed)
+        .fetch_all(&state.conn)
+        .await?;
+    Ok(Json::from(alerts))
+}
@@ -0,0 +1,24 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::state::AppState;
+
+mod dismiss;
+mod list;
+pub mod model;
+pub mod scan;
+
+#[cfg(test)]
+mod test;
+
+pub fn router() -> Router<AppState> {
+    Router::new().nest(
+        "/api/alerts",
+        Router::new()
+            .route("/", get(list::execute))
+            .route("/dismiss", post(dismiss::execute))
+            .route("/scan", post(scan::execute)),
+    )
+}
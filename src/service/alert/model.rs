@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+
+use crate::service::report::anomaly::Anomaly;
+
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow, serde::Serialize)]
+#[sqlx(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct Alert {
+    pub id: i64,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub anomaly: Anomaly,
+    pub dismissed: bool,
+    pub created_date: DateTime<Utc>,
+}
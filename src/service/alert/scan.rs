@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use axum::extract::{Json, State};
+use chrono::{Months, NaiveDate};
+use sqlx::SqlitePool;
+
+use crate::{
+    service::{
+        report::anomaly::{self, Thresholds},
+        GenericUpdateResponse, Result,
+    },
+    sqlx_ext,
+    state::AppState,
+};
+
+/// How many months back each scan looks for anomalies.
+const SCAN_MONTHS: u32 = 3;
+
+/// How often the scan runs in the background, unless overridden by `ALERT_SCAN_INTERVAL_HOURS`.
+const DEFAULT_INTERVAL_HOURS: u64 = 24;
+
+//language=sql
+const SQL: &str = r#"
+insert into alerts (kind, subject, date, description, account, amount, score)
+values (?, ?, ?, ?, ?, ?, ?)
+on conflict (kind, subject) do update set
+    date = excluded.date,
+    description = excluded.description,
+    account = excluded.account,
+    amount = excluded.amount,
+    score = excluded.score
+"#;
+
+/// Stores the anomalies of the last few months up to `as_of` as alerts. Alerts seen before are
+/// updated, keeping whether they were dismissed.
+pub async fn scan(conn: &SqlitePool, as_of: NaiveDate) -> Result<usize> {
+    let from = as_of
+        .checked_sub_months(Months::new(SCAN_MONTHS))
+        .unwrap_or(as_of);
+    let anomalies = anomaly::find(
+        conn,
+        from,
+        as_of,
+        Thresholds::default(),
+        &sqlx_ext::Json(Vec::new()),
+    )
+    .await?;
+
+    let mut tx = conn.begin().await?;
+    for a in &anomalies {
+        sqlx::query(SQL)
+            .bind(a.kind)
+            .bind(&a.subject)
+            .bind(a.date)
+            .bind(&a.description)
+            .bind(&a.account)
+            .bind(a.amount)
+            .bind(a.score)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    Ok(anomalies.len())
+}
+
+/// Runs [`scan`] every `ALERT_SCAN_INTERVAL_HOURS` hours, or never when it is 0.
+pub fn spawn(conn: SqlitePool) {
+    let hours = std::env::var("ALERT_SCAN_INTERVAL_HOURS")
+        .ok()
+        .and_then(|h| h.parse().ok())
+        .unwrap_or(DEFAULT_INTERVAL_HOURS);
+    if hours == 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(hours.saturating_mul(3600)));
+        loop {
+            interval.tick().await;
+            match scan(&conn, chrono::Local::now().date_naive()).await {
+                Ok(n) => log::info!("Alert scan found {n} anomalies"),
+                Err(e) => log::error!("Alert scan failed: {e}"),
+            }
+        }
+    });
+}
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    /// The day to scan up to, today when left out.
+    pub as_of: Option<NaiveDate>,
+}
+
+/// Runs the scan now rather than waiting for the next one.
+pub async fn execute(
+    state: State<AppState>,
+    Json(Input { as_of }): Json<Input>,
+) -> Result<Json<GenericUpdateResponse>> {
+    let as_of = as_of.unwrap_or_else(|| chrono::Local::now().date_naive());
+    let num_affected = scan(&state.conn, as_of).await?;
+    Ok(GenericUpdateResponse { num_affected }.into())
+}
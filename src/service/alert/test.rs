@@ -0,0 +1,168 @@
+use axum::extract::{Json, Query, State};
+use chrono::NaiveDate;
+
+use crate::service::{
+    account::{
+        self,
+        model::{AccountMeta, AccountType},
+        test::new_transaction,
+    },
+    report::anomaly::{self, Kind, Thresholds},
+    transaction,
+};
+use crate::sqlx_ext;
+use crate::state::AppState;
+
+use super::*;
+
+fn date(s: &str) -> NaiveDate {
+    s.parse().expect("A date")
+}
+
+#[tokio::test]
+async fn alerts_work() {
+    let state = State(AppState::new_test().await);
+    let _ = router();
+
+    let _ = account::save::execute(
+        state.clone(),
+        Json(vec![AccountMeta {
+            name: "Expenses".to_string(),
+            account_type: Some(AccountType::Expense),
+            currency: None,
+            notes: None,
+            opened_date: None,
+            closed_date: None,
+            opening_balance: None,
+        }]),
+    )
+    .await
+    .expect("To save meta");
+
+    let transaction = |id: &str, description: &str, to: &str, amount: i64, date: &str| {
+        let mut tx = new_transaction(id, "Bank", to, amount, date);
+        tx.description = description.to_string();
+        tx
+    };
+    let mut transactions: Vec<_> = (1..=6)
+        .map(|m| {
+            transaction(
+                &m.to_string(),
+                "Supermarket",
+                "Expenses:Food",
+                100,
+                &format!("2020-0{m}-01"),
+            )
+        })
+        .collect();
+    // Salary coming into the bank doesn't make the large payment out of it look usual.
+    transactions.extend((1..=6).map(|m| {
+        let mut salary = new_transaction(
+            &format!("salary-{m}"),
+            "Income",
+            "Bank",
+            300000,
+            &format!("2020-0{m}-15"),
+        );
+        salary.description = "Salary".to_string();
+        salary
+    }));
+    transactions.push(transaction(
+        "7",
+        "Supermarket",
+        "Expenses:Food",
+        1000,
+        "2020-07-01",
+    ));
+    transactions.push(transaction(
+        "8",
+        "Fancy furniture",
+        "Expenses:Home",
+        20000,
+        "2020-07-10",
+    ));
+    let _ = transaction::save::execute(state.clone(), Json(transactions))
+        .await
+        .expect("To save transactions");
+
+    let anomalies = anomaly::find(
+        &state.conn,
+        date("2020-07-01"),
+        date("2020-07-31"),
+        Thresholds::default(),
+        &sqlx_ext::Json(vec![]),
+    )
+    .await
+    .expect("To find anomalies");
+    assert_eq!(
+        anomalies
+            .iter()
+            .map(|a| (a.kind, a.subject.as_str(), a.account.as_deref()))
+            .collect::<Vec<_>>(),
+        vec![
+            (Kind::UnusualAmount, "8", Some("Bank")),
+            (Kind::NewMerchant, "8", Some("Expenses:Home")),
+            (
+                Kind::CategorySpike,
+                "2020-07 Expenses:Food",
+                Some("Expenses:Food")
+            ),
+            (Kind::UnusualAmount, "7", Some("Expenses:Food")),
+        ]
+    );
+    assert_eq!(anomalies[2].amount, 1000);
+    assert_eq!(anomalies[2].score, Some(90.0));
+
+    // Nothing stands out before there is enough history.
+    assert!(anomaly::find(
+        &state.conn,
+        date("2020-02-01"),
+        date("2020-05-31"),
+        Thresholds::default(),
+        &sqlx_ext::Json(vec![]),
+    )
+    .await
+    .expect("To find anomalies")
+    .is_empty());
+
+    let Json(scanned) = scan::execute(
+        state.clone(),
+        Json(scan::Input {
+            as_of: Some(date("2020-07-31")),
+        }),
+    )
+    .await
+    .expect("To scan");
+    assert_eq!(scanned.num_affected, 4);
+
+    let list =
+        |include_dismissed| list::execute(state.clone(), Query(list::Input { include_dismissed }));
+    let Json(alerts) = list(false).await.expect("To list");
+    assert_eq!(alerts.len(), 4);
+    assert!(anomalies
+        .iter()
+        .all(|a| alerts.iter().any(|alert| &alert.anomaly == a)));
+
+    let Json(dismissed) = dismiss::execute(state.clone(), Json(vec![alerts[0].id]))
+        .await
+        .expect("To dismiss");
+    assert_eq!(dismissed.num_affected, 1);
+    assert_eq!(list(false).await.expect("To list").0.len(), 3);
+
+    // Scanning again keeps the alerts dismissed rather than adding them back.
+    let _ = scan::scan(&state.conn, date("2020-07-31"))
+        .await
+        .expect("To scan");
+    assert_eq!(list(false).await.expect("To list").0.len(), 3);
+    let Json(all) = list(true).await.expect("To list");
+    assert_eq!(all.len(), 4);
+    assert!(all.iter().find(|a| a.id == alerts[0].id).unwrap().dismissed);
+
+    // Alerts about a deleted transaction are gone with it.
+    let _ = transaction::delete::execute(state.clone(), Json(vec!["8".to_string()]))
+        .await
+        .expect("To delete");
+    let Json(all) = list(true).await.expect("To list");
+    assert_eq!(all.len(), 2);
+    assert!(all.iter().all(|a| a.anomaly.subject != "8"));
+}
@@ -35,14 +35,14 @@ pub async fn get(
     signed_token: &str,
 ) -> crate::service::Result<(String, Vec<u8>)> {
     let signed = Signed(Cow::Borrowed(signed_token));
-    let c = CredentialsConfig::from_app(&state).await;
+    let c = CredentialsConfig::from_app(state).await;
 
     let id = match super::sign::verify(&signed, c.as_ref()) {
         Some(v) => v,
         _ => return Err(Error::InvalidCredentials),
     };
 
-    match sql::execute(&state, &id).await {
+    match sql::execute(state, &id).await {
         Ok(Some((data, mime_type))) => Ok((mime_type, data)),
         Ok(_) => Err(Error::ResourceNotFound),
         Err(e) => Err(e),
@@ -23,15 +23,20 @@ pub struct Input {
     pub sorts: Cow<'static, [Sort]>,
 
     pub q: Option<String>,
+    pub query: Option<String>,
     pub from: Option<NaiveDate>,
     pub to: Option<NaiveDate>,
     #[serde(default = "default_limit")]
     pub limit: i64,
     #[serde(default)]
     pub offset: i64,
+    pub cursor: Option<String>,
+    #[serde(default = "default_with_total")]
+    pub with_total: bool,
 
     pub includes: Option<Json<Vec<String>>>,
     pub accounts: Option<Json<Vec<String>>>,
+    pub account_groups: Option<Json<Vec<String>>>,
     pub tags: Option<Json<Vec<String>>>,
 
     #[serde(default = "default_with_data")]
@@ -42,6 +47,10 @@ const fn default_with_data() -> bool {
     false
 }
 
+const fn default_with_total() -> bool {
+    true
+}
+
 const fn default_sorts() -> Cow<'static, [Sort]> {
     Cow::Borrowed(DEFAULT_SORTS)
 }
@@ -56,18 +65,22 @@ impl Default for Input {
             limit: default_limit(),
             sorts: default_sorts(),
             q: Default::default(),
+            query: Default::default(),
             from: Default::default(),
             to: Default::default(),
             offset: Default::default(),
+            cursor: Default::default(),
+            with_total: default_with_total(),
             includes: Default::default(),
             accounts: Default::default(),
+            account_groups: Default::default(),
             tags: Default::default(),
             with_data: Default::default(),
         }
     }
 }
 
-const DEFAULT_SORTS: &'static [Sort] = &[
+const DEFAULT_SORTS: &[Sort] = &[
     Sort::new(SortField::Created, SortOrder::DESC),
     Sort::new(SortField::Updated, SortOrder::DESC),
 ];
@@ -105,7 +118,7 @@ pub struct AttachmentSigned<'a> {
 mod sql {
     //language=sql
     const SQL: &str = r#"
-with account_attachments(account, attachmentId) as (
+account_attachments(account, attachmentId) as (
     select a.name, ta.attachmentId from accounts a 
     inner join account_transactions tx on tx.account = a.name
     inner join transaction_attachments ta on ta.transactionId = tx.id
@@ -124,11 +137,10 @@ where
     and (?4 is null or created <= ?4)
     and (?5 is null or id in (select trim(value) from json_each(?5)))
     and (
-        ?6 is null 
-        or json_array_length(?6) == 0
+        (ifnull(json_array_length(?6), 0) == 0 and ifnull(json_array_length(?8), 0) == 0)
         or id in (
             select aa.attachmentId from account_attachments aa
-            where aa.account collate nocase in (select trim(value) from json_each(?6))
+            where aa.account collate nocase in (select account from input_accounts)
         )
     )
     and (
@@ -138,39 +150,81 @@ where
             select ta.attachmentId from transaction_attachments ta
             inner join transactions t on t.id = ta.transactionId
             inner join transaction_tags tt on tt.transactionId = t.id
-            where tt.tag collate nocase in (select trim(value) from json_each(?7))
+            inner join tag_ancestors tg on tg.tag = tt.tag
+            where tg.ancestor collate nocase in (select trim(value) from json_each(?7))
         )
     )
 "#;
 
+    //language=sql
+    const TRANSACTION_FILTER_SQL: &str = r#"
+select ta.attachmentId from transaction_attachments ta
+inner join transactions t on t.id = ta.transactionId
+where"#;
+
     use crate::{
         bind_sqlite_args,
-        service::{query::create_paginated_query, PaginatedResponse, Result},
+        service::{
+            account_group::input_accounts_sql,
+            filter::Filter,
+            query::{create_paginated_query, Page, PageRequest},
+            PaginatedResponse, Result,
+        },
         state::AppState,
     };
 
     use super::{Attachment, Input};
 
     pub async fn execute(state: &AppState, input: Input) -> Result<PaginatedResponse<Attachment>> {
-        let (data, (total,)) = create_paginated_query(
+        let mut args = bind_sqlite_args!(
+            input.with_data,
+            &input.q,
+            &input.from,
+            &input.to,
+            &input.includes,
+            &input.accounts,
+            &input.tags,
+            &input.account_groups
+        );
+        let filter = Filter::compile(
+            input.query.as_deref().unwrap_or_default(),
+            args.next_index(),
+        )?;
+        let mut sql = format!(
+            "with input_accounts as ({}), {SQL}",
+            input_accounts_sql(6, 8, "0")
+        );
+        if !filter.is_empty() {
+            sql = format!(
+                "{sql} and id in ({TRANSACTION_FILTER_SQL} {})",
+                filter.sql()
+            );
+        }
+        filter.bind(&mut args);
+
+        let Page {
+            items,
+            aggregates,
+            next_cursor,
+        } = create_paginated_query::<_, _, (i64,)>(
             &state.conn,
-            SQL,
-            bind_sqlite_args!(
-                input.with_data,
-                &input.q,
-                &input.from,
-                &input.to,
-                &input.includes,
-                &input.accounts,
-                &input.tags
-            ),
-            input.limit,
-            input.offset,
-            &input.sorts,
-            "COUNT(*)",
+            &sql,
+            args,
+            PageRequest {
+                limit: input.limit,
+                offset: input.offset,
+                cursor: input.cursor.as_deref(),
+                sorts: &input.sorts,
+                key: "id",
+                aggregate_select: input.with_total.then_some("COUNT(*)"),
+            },
         )
         .await?;
-        Ok(PaginatedResponse { data, total })
+        Ok(PaginatedResponse {
+            data: items,
+            total: aggregates.map(|(total,)| total),
+            next_cursor,
+        })
     }
 }
 
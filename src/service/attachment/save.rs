@@ -25,7 +25,7 @@ pub async fn save(
     data: &[u8],
     name: &str,
 ) -> crate::service::Result<String> {
-    let hash_code = sodiumoxide::crypto::hash::hash(&data);
+    let hash_code = sodiumoxide::crypto::hash::hash(data);
 
     let mut tx = state.conn.begin().await?;
 
@@ -52,7 +52,7 @@ async fn attachment_rw_works() {
 
     assert_eq!(second_save_output, id);
 
-    let (mime, actual_data) = get::get(&app_state, &id, output.signed_id.0.as_ref())
+    let (mime, actual_data) = get::get(&app_state, output.signed_id.0.as_ref())
         .await
         .expect("To retrieve attachment");
 
@@ -97,7 +97,7 @@ async fn list_by_account_works() {
     .await
     .expect("To create a transaction");
 
-    let PaginatedResponse { total, data } = list::execute(
+    let PaginatedResponse { total, data, .. } = list::execute(
         app_state.clone(),
         list::Input {
             accounts: Some(Json(vec!["account 1".to_string()])),
@@ -109,7 +109,7 @@ async fn list_by_account_works() {
     .expect("To list")
     .0;
 
-    assert_eq!(total as usize, attachments.len());
+    assert_eq!(total, Some(attachments.len() as i64));
     assert_eq!(
         data.into_iter()
             .map(|a| a.attachment.id)
@@ -118,7 +118,7 @@ async fn list_by_account_works() {
         attachments.clone().into_iter().sorted().collect_vec()
     );
 
-    let PaginatedResponse { total, data } = list::execute(
+    let PaginatedResponse { total, data, .. } = list::execute(
         app_state.clone(),
         list::Input {
             accounts: Some(Json(vec!["account 2 ".to_string()])),
@@ -130,7 +130,7 @@ async fn list_by_account_works() {
     .expect("To list")
     .0;
 
-    assert_eq!(total as usize, attachments.len());
+    assert_eq!(total, Some(attachments.len() as i64));
     assert_eq!(
         data.into_iter()
             .map(|a| a.attachment.id)
@@ -138,4 +138,36 @@ async fn list_by_account_works() {
             .collect_vec(),
         attachments.clone().into_iter().sorted().collect_vec()
     );
+
+    sqlx::query(
+        "insert into account_groups (groupName, accountName) values ('Group', 'ACCOUNT 2')",
+    )
+    .execute(&app_state.conn)
+    .await
+    .expect("To add group");
+    let PaginatedResponse { total, .. } = list::execute(
+        app_state.clone(),
+        list::Input {
+            account_groups: Some(Json(vec!["group".to_string()])),
+            ..Default::default()
+        }
+        .into(),
+    )
+    .await
+    .expect("To list")
+    .0;
+    assert_eq!(total, Some(attachments.len() as i64));
+
+    let PaginatedResponse { total, .. } = list::execute(
+        app_state.clone(),
+        list::Input {
+            account_groups: Some(Json(vec!["Other group".to_string()])),
+            ..Default::default()
+        }
+        .into(),
+    )
+    .await
+    .expect("To list")
+    .0;
+    assert_eq!(total, Some(0));
 }
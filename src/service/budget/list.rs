@@ -0,0 +1,12 @@
+use axum::extract::{Json, State};
+
+use crate::{service::Result, state::AppState};
+
+use super::model::Budget;
+
+pub async fn execute(state: State<AppState>) -> Result<Json<Vec<Budget>>> {
+    let budgets = sqlx::query_as("select * from budgets order by name collate nocase, id")
+        .fetch_all(&state.conn)
+        .await?;
+    Ok(Json::from(budgets))
+}
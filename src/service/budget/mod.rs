@@ -0,0 +1,24 @@
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
+
+use crate::state::AppState;
+
+mod delete;
+mod list;
+pub mod model;
+pub mod save;
+
+#[cfg(test)]
+mod test;
+
+pub fn router() -> Router<AppState> {
+    Router::new().nest(
+        "/api/budgets",
+        Router::new()
+            .route("/", get(list::execute))
+            .route("/", post(save::execute))
+            .route("/", delete(delete::execute)),
+    )
+}
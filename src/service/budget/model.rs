@@ -0,0 +1,63 @@
+use chrono::{Days, Months, NaiveDate};
+
+#[derive(serde::Serialize, serde::Deserialize, sqlx::Type, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Period {
+    Monthly,
+    Yearly,
+    Custom,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+#[sqlx(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct Budget {
+    /// Assigned on saving a new budget.
+    #[serde(default)]
+    pub id: Option<i64>,
+    pub name: String,
+    /// The account budgeted for, including the accounts under it. Exclusive with `account_group`.
+    pub account: Option<String>,
+    pub account_group: Option<String>,
+    pub period: Period,
+    /// The amount to spend in each period, in cents.
+    pub amount: i64,
+    pub start_date: NaiveDate,
+    /// The last day of the budget, which is required for [`Period::Custom`].
+    pub end_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub rollover: bool,
+}
+
+impl Budget {
+    /// The periods of this budget, as inclusive date ranges, starting on or before `until`.
+    ///
+    /// Monthly and yearly periods start on the same day of the month as `start_date`, or the
+    /// last day of shorter months. The last period is cut short by `end_date`.
+    pub fn periods(&self, until: NaiveDate) -> Vec<(NaiveDate, NaiveDate)> {
+        let step = match self.period {
+            Period::Custom => {
+                return match self.end_date {
+                    Some(end) if self.start_date <= until => vec![(self.start_date, end)],
+                    _ => vec![],
+                }
+            }
+            Period::Monthly => 1,
+            Period::Yearly => 12,
+        };
+
+        let last = self.end_date.map_or(until, |end| end.min(until));
+        let start_of = |i: u32| self.start_date.checked_add_months(Months::new(i * step));
+        let mut periods = Vec::new();
+        for i in 0.. {
+            let (Some(start), Some(next)) = (start_of(i), start_of(i + 1)) else {
+                break;
+            };
+            if start > last {
+                break;
+            }
+            let end = next - Days::new(1);
+            periods.push((start, self.end_date.map_or(end, |e| e.min(end))));
+        }
+        periods
+    }
+}
@@ -0,0 +1,77 @@
+use std::borrow::Cow;
+
+use axum::extract::{Json, State};
+
+use crate::{
+    service::{Error, GenericUpdateResponse, Result},
+    state::AppState,
+};
+
+use super::model::{Budget, Period};
+
+//language=sql
+const SQL: &str = r#"
+insert into budgets (id, name, account, accountGroup, period, amount, startDate, endDate, rollover)
+values (?1, trim(?2), trim(?3), trim(?4), ?5, ?6, ?7, ?8, ?9)
+on conflict (id) do update set
+    name = excluded.name,
+    account = excluded.account,
+    accountGroup = excluded.accountGroup,
+    period = excluded.period,
+    amount = excluded.amount,
+    startDate = excluded.startDate,
+    endDate = excluded.endDate,
+    rollover = excluded.rollover
+"#;
+
+fn validate(budget: &Budget) -> Result<()> {
+    let invalid = |msg: &str| {
+        Err(Error::InvalidArgument(Cow::Owned(format!(
+            "Budget {}: {msg}",
+            budget.name
+        ))))
+    };
+
+    let is_blank = |name: &Option<String>| name.as_deref().is_some_and(|n| n.trim().is_empty());
+    if is_blank(&budget.account) || is_blank(&budget.account_group) {
+        return invalid("account and account group names can't be empty");
+    }
+    if budget.account.is_some() == budget.account_group.is_some() {
+        return invalid("exactly one of account and account group is required");
+    }
+    match budget.end_date {
+        None if budget.period == Period::Custom => invalid("a custom budget needs an end date"),
+        Some(end) if end < budget.start_date => invalid("it ends before it starts"),
+        _ => Ok(()),
+    }
+}
+
+pub async fn execute(
+    state: State<AppState>,
+    Json(budgets): Json<Vec<Budget>>,
+) -> Result<Json<GenericUpdateResponse>> {
+    let mut tx = state.conn.begin().await?;
+    let mut num_affected: u64 = 0;
+
+    for budget in budgets {
+        validate(&budget)?;
+        num_affected += sqlx::query(SQL)
+            .bind(budget.id)
+            .bind(budget.name)
+            .bind(budget.account)
+            .bind(budget.account_group)
+            .bind(budget.period)
+            .bind(budget.amount)
+            .bind(budget.start_date)
+            .bind(budget.end_date)
+            .bind(budget.rollover)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    }
+
+    tx.commit().await?;
+    Ok(Json::from(GenericUpdateResponse {
+        num_affected: num_affected as usize,
+    }))
+}
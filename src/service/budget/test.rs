@@ -0,0 +1,113 @@
+use axum::extract::{Json, State};
+use chrono::NaiveDate;
+
+use crate::state::AppState;
+
+use super::model::{Budget, Period};
+use super::*;
+
+fn date(v: &str) -> NaiveDate {
+    v.parse().unwrap()
+}
+
+fn budget(period: Period, start: &str, end: Option<&str>) -> Budget {
+    Budget {
+        id: None,
+        name: "Food".to_string(),
+        account: Some("Expenses:Food".to_string()),
+        account_group: None,
+        period,
+        amount: 100,
+        start_date: date(start),
+        end_date: end.map(date),
+        rollover: false,
+    }
+}
+
+#[test]
+fn periods_work() {
+    let periods = |b: Budget, until: &str| {
+        b.periods(date(until))
+            .into_iter()
+            .map(|(start, end)| (start.to_string(), end.to_string()))
+            .collect::<Vec<_>>()
+    };
+    let p = |start: &str, end: &str| (start.to_string(), end.to_string());
+
+    assert_eq!(
+        periods(
+            budget(Period::Monthly, "2020-01-31", Some("2020-04-15")),
+            "2021-01-01"
+        ),
+        vec![
+            p("2020-01-31", "2020-02-28"),
+            p("2020-02-29", "2020-03-30"),
+            p("2020-03-31", "2020-04-15"),
+        ]
+    );
+    assert_eq!(
+        periods(budget(Period::Yearly, "2020-07-01", None), "2021-07-01"),
+        vec![p("2020-07-01", "2021-06-30"), p("2021-07-01", "2022-06-30")]
+    );
+    assert_eq!(
+        periods(
+            budget(Period::Custom, "2020-01-10", Some("2020-01-20")),
+            "2020-01-01"
+        ),
+        vec![]
+    );
+}
+
+#[tokio::test]
+async fn budget_crud_works() {
+    let state = State(AppState::new_test().await);
+
+    let _ = save::execute(
+        state.clone(),
+        Json(vec![budget(Period::Monthly, "2020-01-01", None)]),
+    )
+    .await
+    .expect("To save");
+
+    let Json(budgets) = list::execute(state.clone()).await.expect("To list");
+    assert_eq!(budgets.len(), 1);
+    let mut saved = budgets[0].clone();
+    assert!(saved.id.is_some());
+
+    saved.amount = 200;
+    let _ = save::execute(state.clone(), Json(vec![saved.clone()]))
+        .await
+        .expect("To update");
+    assert_eq!(
+        list::execute(state.clone()).await.unwrap().0,
+        vec![saved.clone()]
+    );
+
+    for invalid in [
+        budget(Period::Custom, "2020-01-01", None),
+        budget(Period::Monthly, "2020-01-01", Some("2019-01-01")),
+        Budget {
+            account_group: Some("Living costs".to_string()),
+            ..budget(Period::Monthly, "2020-01-01", None)
+        },
+        Budget {
+            account: Some("  ".to_string()),
+            ..budget(Period::Monthly, "2020-01-01", None)
+        },
+        Budget {
+            account: None,
+            account_group: Some("".to_string()),
+            ..budget(Period::Monthly, "2020-01-01", None)
+        },
+    ] {
+        assert!(save::execute(state.clone(), Json(vec![invalid]))
+            .await
+            .is_err());
+    }
+
+    let _ = delete::execute(state.clone(), Json(vec![saved.id.unwrap()]))
+        .await
+        .expect("To delete");
+    assert!(list::execute(state.clone()).await.unwrap().0.is_empty());
+    let _ = router();
+}
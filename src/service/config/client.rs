@@ -1,4 +1,36 @@
-const KEY: &'static str = "client";
+use chrono::Datelike;
+
+const KEY: &str = "client";
+
+/// The client config splitting account names into levels, e.g. "Expenses:Food".
+pub const ACCOUNT_SEPARATOR: &str = "accountSeparator";
+const DEFAULT_ACCOUNT_SEPARATOR: &str = ":";
+
+pub async fn account_separator(conn: &sqlx::SqlitePool) -> anyhow::Result<String> {
+    Ok(super::get(KEY, Some(ACCOUNT_SEPARATOR), conn)
+        .await?
+        .unwrap_or_else(|| DEFAULT_ACCOUNT_SEPARATOR.to_string()))
+}
+
+/// The client config for the day fiscal years start on, as "MM-DD".
+pub const FISCAL_YEAR_START: &str = "fiscalYearStart";
+
+/// The month and day fiscal years start on, which is 1 January unless configured.
+pub async fn fiscal_year_start(conn: &sqlx::SqlitePool) -> crate::service::Result<(u32, u32)> {
+    let Some(value) = super::get(KEY, Some(FISCAL_YEAR_START), conn).await? else {
+        return Ok((1, 1));
+    };
+    // Days past the 28th would not exist in every year
+    chrono::NaiveDate::parse_from_str(&format!("2001-{value}"), "%Y-%m-%d")
+        .ok()
+        .filter(|d| d.day() <= 28)
+        .map(|d| (d.month(), d.day()))
+        .ok_or_else(|| {
+            crate::service::Error::InvalidArgument(
+                format!("Invalid {FISCAL_YEAR_START} config {value}").into(),
+            )
+        })
+}
 
 pub mod get {
     use crate::state::AppState;
@@ -0,0 +1,38 @@
+use axum::extract::{Json, State};
+use chrono::NaiveDate;
+
+use crate::{service::Result, state::AppState};
+
+use super::super::model::Allocation;
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// Only the allocations to or from this envelope.
+    pub envelope: Option<String>,
+}
+
+//language=sql
+const SQL: &str = r#"
+select * from envelope_allocations
+where (?1 is null or allocDate >= ?1)
+  and (?2 is null or allocDate <= ?2)
+  and (?3 is null or trim(?3) collate nocase in (fromEnvelope, toEnvelope))
+order by allocDate, id
+"#;
+
+pub async fn execute(
+    state: State<AppState>,
+    Json(Input { from, to, envelope }): Json<Input>,
+) -> Result<Json<Vec<Allocation>>> {
+    Ok(Json::from(
+        sqlx::query_as(SQL)
+            .bind(from)
+            .bind(to)
+            .bind(envelope)
+            .fetch_all(&state.conn)
+            .await?,
+    ))
+}
@@ -0,0 +1,81 @@
+use std::borrow::Cow;
+
+use axum::extract::{Json, State};
+
+use crate::{
+    service::{Error, GenericUpdateResponse, Result},
+    state::AppState,
+};
+
+use super::super::model::Allocation;
+
+//language=sql
+const SQL: &str = r#"
+insert into envelope_allocations (id, allocDate, fromEnvelope, toEnvelope, amount, note)
+values (?1, ?2, trim(?3), trim(?4), ?5, ?6)
+on conflict (id) do update set
+    allocDate = excluded.allocDate,
+    fromEnvelope = excluded.fromEnvelope,
+    toEnvelope = excluded.toEnvelope,
+    amount = excluded.amount,
+    note = excluded.note
+"#;
+
+pub async fn execute(
+    state: State<AppState>,
+    Json(allocations): Json<Vec<Allocation>>,
+) -> Result<Json<GenericUpdateResponse>> {
+    let mut tx = state.conn.begin().await?;
+    let mut num_affected: u64 = 0;
+
+    for Allocation {
+        id,
+        alloc_date,
+        from_envelope,
+        to_envelope,
+        amount,
+        note,
+    } in allocations
+    {
+        let same = match (&from_envelope, &to_envelope) {
+            (Some(from), Some(to)) => from.trim().eq_ignore_ascii_case(to.trim()),
+            (None, None) => true,
+            _ => false,
+        };
+        if same {
+            return Err(Error::InvalidArgument(
+                "An allocation moves money between two different envelopes".into(),
+            ));
+        }
+
+        for envelope in from_envelope.iter().chain(to_envelope.iter()) {
+            let (exists,): (bool,) =
+                sqlx::query_as("select exists (select 1 from envelopes where name = trim(?))")
+                    .bind(envelope)
+                    .fetch_one(&mut *tx)
+                    .await?;
+            if !exists {
+                return Err(Error::InvalidArgument(Cow::Owned(format!(
+                    "Envelope {} doesn't exist",
+                    envelope.trim()
+                ))));
+            }
+        }
+
+        num_affected += sqlx::query(SQL)
+            .bind(id)
+            .bind(alloc_date)
+            .bind(from_envelope)
+            .bind(to_envelope)
+            .bind(amount)
+            .bind(note)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    }
+
+    tx.commit().await?;
+    Ok(Json::from(GenericUpdateResponse {
+        num_affected: num_affected as usize,
+    }))
+}
@@ -0,0 +1,64 @@
+use axum::extract::{Json, State};
+
+use crate::{
+    service::{GenericUpdateResponse, Result},
+    sqlx_ext,
+    state::AppState,
+};
+
+use super::summary::envelopes;
+
+//language=sql
+const POOL_ALLOCATIONS_SQL: &str = r#"
+with deleted as (select trim(value) as name from json_each(?1))
+delete from envelope_allocations
+where (fromEnvelope is not null or toEnvelope is not null)
+  and (fromEnvelope is null or fromEnvelope collate nocase in deleted)
+  and (toEnvelope is null or toEnvelope collate nocase in deleted)
+"#;
+
+//language=sql
+const SPENT_SQL: &str = r#"
+insert into envelope_allocations (allocDate, fromEnvelope, toEnvelope, amount, note)
+values (?1, null, null, ?2, ?3)
+"#;
+
+/// Deletes the envelopes, returning what is left in them to unassigned income. Their allocations
+/// with other envelopes become ones with unassigned income, so those envelopes keep their money.
+pub async fn execute(
+    state: State<AppState>,
+    Json(names): Json<Vec<String>>,
+) -> Result<Json<GenericUpdateResponse>> {
+    let names = sqlx_ext::Json(names);
+    let mut tx = state.conn.begin().await?;
+
+    let deleted: Vec<_> = envelopes(&mut *tx, None, None)
+        .await?
+        .into_iter()
+        .filter(|e| names.iter().any(|n| n.trim().eq_ignore_ascii_case(&e.name)))
+        .collect();
+
+    // Undo their allocations with unassigned income, and keep what they spent spent.
+    sqlx::query(POOL_ALLOCATIONS_SQL)
+        .bind(&names)
+        .execute(&mut *tx)
+        .await?;
+    let today = chrono::Local::now().date_naive();
+    for envelope in deleted.iter().filter(|e| e.spent != 0) {
+        sqlx::query(SPENT_SQL)
+            .bind(today)
+            .bind(envelope.spent)
+            .bind(format!("Spent from deleted envelope {}", envelope.name))
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let output: GenericUpdateResponse =
+        sqlx::query("delete from envelopes where name in (select trim(value) from json_each(?))")
+            .bind(&names)
+            .execute(&mut *tx)
+            .await?
+            .into();
+    tx.commit().await?;
+    Ok(output.into())
+}
@@ -0,0 +1,13 @@
+use axum::extract::{Json, State};
+
+use crate::{service::Result, state::AppState};
+
+pub async fn execute(state: State<AppState>) -> Result<Json<Vec<String>>> {
+    let accounts: Vec<(String,)> =
+        sqlx::query_as("select account from envelope_income_accounts order by account")
+            .fetch_all(&state.conn)
+            .await?;
+    Ok(Json::from(
+        accounts.into_iter().map(|(a,)| a).collect::<Vec<_>>(),
+    ))
+}
@@ -0,0 +1,28 @@
+use axum::extract::{Json, State};
+
+use crate::{
+    service::{GenericUpdateResponse, Result},
+    sqlx_ext,
+    state::AppState,
+};
+
+/// Replaces the income accounts with the given ones.
+pub async fn execute(
+    state: State<AppState>,
+    Json(accounts): Json<Vec<String>>,
+) -> Result<Json<GenericUpdateResponse>> {
+    let mut tx = state.conn.begin().await?;
+    sqlx::query("delete from envelope_income_accounts")
+        .execute(&mut *tx)
+        .await?;
+    let output: GenericUpdateResponse = sqlx::query(
+        "insert or ignore into envelope_income_accounts (account) \
+         select trim(value) from json_each(?) where trim(value) != ''",
+    )
+    .bind(sqlx_ext::Json(accounts))
+    .execute(&mut *tx)
+    .await?
+    .into();
+    tx.commit().await?;
+    Ok(output.into())
+}
@@ -0,0 +1,22 @@
+use axum::extract::{Json, State};
+
+use crate::{service::Result, state::AppState};
+
+use super::model::Envelope;
+
+//language=sql
+const SQL: &str = r#"
+select e.name,
+       (select json_group_array(ea.account)
+        from (select account from envelope_accounts
+              where envelope = e.name
+              order by account collate nocase) ea) as accounts
+from envelopes e
+order by e.name
+"#;
+
+pub async fn execute(state: State<AppState>) -> Result<Json<Vec<Envelope>>> {
+    Ok(Json::from(
+        sqlx::query_as(SQL).fetch_all(&state.conn).await?,
+    ))
+}
@@ -0,0 +1,33 @@
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
+
+use crate::state::AppState;
+
+pub mod allocation;
+mod delete;
+pub mod income_accounts;
+mod list;
+pub mod model;
+pub mod save;
+pub mod summary;
+
+#[cfg(test)]
+mod test;
+
+pub fn router() -> Router<AppState> {
+    Router::new().nest(
+        "/api/envelopes",
+        Router::new()
+            .route("/", get(list::execute))
+            .route("/", post(save::execute))
+            .route("/", delete(delete::execute))
+            .route("/allocations", post(allocation::save::execute))
+            .route("/allocations", delete(allocation::delete::execute))
+            .route("/allocations/list", post(allocation::list::execute))
+            .route("/incomeAccounts", get(income_accounts::get::execute))
+            .route("/incomeAccounts", post(income_accounts::save::execute))
+            .route("/summary", post(summary::execute)),
+    )
+}
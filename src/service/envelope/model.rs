@@ -0,0 +1,29 @@
+use chrono::NaiveDate;
+
+use crate::sqlx_ext::Json;
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+#[sqlx(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct Envelope {
+    pub name: String,
+    /// The accounts drawing from this envelope, including the accounts under them.
+    pub accounts: Json<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+#[sqlx(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct Allocation {
+    /// Assigned on saving a new allocation.
+    #[serde(default)]
+    pub id: Option<i64>,
+    pub alloc_date: NaiveDate,
+    /// The envelope the money comes from, or `None` for unassigned income.
+    pub from_envelope: Option<String>,
+    /// The envelope the money goes to, or `None` to return it to unassigned income. With neither
+    /// envelope, it is money spent from a deleted envelope, gone from unassigned income.
+    pub to_envelope: Option<String>,
+    pub amount: i64,
+    pub note: Option<String>,
+}
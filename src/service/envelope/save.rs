@@ -0,0 +1,61 @@
+use std::borrow::Cow;
+
+use axum::extract::{Json, State};
+
+use crate::{
+    service::{Error, GenericUpdateResponse, Result},
+    state::AppState,
+};
+
+use super::model::Envelope;
+
+//language=sql
+const TAKEN_SQL: &str = r#"
+select account, envelope from envelope_accounts
+where account in (select trim(value) from json_each(?1))
+  and envelope != trim(?2)
+"#;
+
+/// Creates the envelopes, or replaces the accounts of existing ones.
+pub async fn execute(
+    state: State<AppState>,
+    Json(envelopes): Json<Vec<Envelope>>,
+) -> Result<Json<GenericUpdateResponse>> {
+    let mut tx = state.conn.begin().await?;
+
+    for Envelope { name, accounts } in &envelopes {
+        let taken: Option<(String, String)> = sqlx::query_as(TAKEN_SQL)
+            .bind(accounts)
+            .bind(name)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if let Some((account, envelope)) = taken {
+            return Err(Error::InvalidArgument(Cow::Owned(format!(
+                "Account {account} already draws from envelope {envelope}"
+            ))));
+        }
+
+        sqlx::query("insert or ignore into envelopes (name) values (trim(?))")
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("delete from envelope_accounts where envelope = trim(?)")
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "insert or ignore into envelope_accounts (account, envelope) \
+             select trim(value), trim(?2) from json_each(?1) where trim(value) != ''",
+        )
+        .bind(accounts)
+        .bind(name)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(GenericUpdateResponse {
+        num_affected: envelopes.len(),
+    }
+    .into())
+}
@@ -0,0 +1,131 @@
+use axum::extract::{Json, State};
+use chrono::NaiveDate;
+
+use crate::{service::Result, state::AppState};
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    /// When envelope budgeting started; income, spending and allocations before it are ignored.
+    pub from: Option<NaiveDate>,
+    pub as_of: Option<NaiveDate>,
+}
+
+#[derive(serde::Serialize, sqlx::FromRow, Debug, PartialEq, Eq)]
+#[sqlx(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct EnvelopeBalance {
+    pub name: String,
+    /// The net amount moved into the envelope.
+    pub allocated: i64,
+    /// The spending on the envelope's accounts, less refunds.
+    pub spent: i64,
+    pub available: i64,
+}
+
+#[derive(serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+    /// The income on the income accounts.
+    pub income: i64,
+    /// The income not yet allocated to any envelope.
+    pub unassigned: i64,
+    pub envelopes: Vec<EnvelopeBalance>,
+}
+
+//language=sql
+const ENVELOPES_SQL: &str = r#"
+with mapped as (
+    select account, envelope
+    from (
+        select aa.account, ea.envelope,
+               row_number() over (partition by lower(aa.account) order by length(ea.account) desc) as n
+        from envelope_accounts ea
+        inner join account_ancestors aa on aa.ancestor = ea.account collate nocase
+    )
+    where n = 1
+),
+spent as (
+    select m.envelope, sum(ds.total) as total
+    from daily_sum ds
+    inner join mapped m on m.account = ds.account collate nocase
+    where (?1 is null or ds.transDate >= ?1)
+      and (?2 is null or ds.transDate <= ?2)
+    group by m.envelope collate nocase
+),
+allocated as (
+    select envelope, sum(amount) as total
+    from (
+        select toEnvelope as envelope, amount, allocDate from envelope_allocations
+        union all
+        select fromEnvelope, -amount, allocDate from envelope_allocations
+    )
+    where envelope is not null
+      and (?1 is null or allocDate >= ?1)
+      and (?2 is null or allocDate <= ?2)
+    group by envelope collate nocase
+)
+select e.name,
+       coalesce(a.total, 0) as allocated,
+       coalesce(s.total, 0) as spent,
+       coalesce(a.total, 0) - coalesce(s.total, 0) as available
+from envelopes e
+left join allocated a on a.envelope = e.name
+left join spent s on s.envelope = e.name
+order by e.name
+"#;
+
+//language=sql
+const POOL_SQL: &str = r#"
+select
+    (select -coalesce(sum(ds.total), 0)
+     from daily_sum ds
+     where ds.account collate nocase in (
+             select aa.account
+             from envelope_income_accounts i
+             inner join account_ancestors aa on aa.ancestor = i.account collate nocase
+         )
+       and (?1 is null or ds.transDate >= ?1)
+       and (?2 is null or ds.transDate <= ?2)),
+    (select coalesce(sum(case
+                             when fromEnvelope is null then amount
+                             when toEnvelope is null then -amount
+                             else 0
+                         end), 0)
+     from envelope_allocations
+     where (?1 is null or allocDate >= ?1)
+       and (?2 is null or allocDate <= ?2))
+"#;
+
+/// The money allocated to, spent from and available in each envelope from `from` to `as_of`.
+pub async fn envelopes<'e>(
+    conn: impl sqlx::SqliteExecutor<'e>,
+    from: Option<NaiveDate>,
+    as_of: Option<NaiveDate>,
+) -> Result<Vec<EnvelopeBalance>> {
+    Ok(sqlx::query_as(ENVELOPES_SQL)
+        .bind(from)
+        .bind(as_of)
+        .fetch_all(conn)
+        .await?)
+}
+
+/// The money available in each envelope, and the income left to allocate.
+pub async fn execute(
+    state: State<AppState>,
+    Json(Input { from, as_of }): Json<Input>,
+) -> Result<Json<Summary>> {
+    let envelopes = envelopes(&state.conn, from, as_of).await?;
+
+    let (income, allocated): (i64, i64) = sqlx::query_as(POOL_SQL)
+        .bind(from)
+        .bind(as_of)
+        .fetch_one(&state.conn)
+        .await?;
+
+    Ok(Json::from(Summary {
+        income,
+        unassigned: income - allocated,
+        envelopes,
+    }))
+}
@@ -0,0 +1,182 @@
+use axum::extract::{Json, State};
+
+use crate::service::{account::test::new_transaction, transaction};
+use crate::sqlx_ext;
+use crate::state::AppState;
+
+use super::model::{Allocation, Envelope};
+use super::*;
+
+fn envelope(name: &str, accounts: &[&str]) -> Envelope {
+    Envelope {
+        name: name.to_string(),
+        accounts: sqlx_ext::Json(accounts.iter().map(|a| a.to_string()).collect()),
+    }
+}
+
+fn allocation(from: Option<&str>, to: Option<&str>, amount: i64) -> Allocation {
+    Allocation {
+        id: None,
+        alloc_date: "2020-01-02".parse().unwrap(),
+        from_envelope: from.map(str::to_string),
+        to_envelope: to.map(str::to_string),
+        amount,
+        note: None,
+    }
+}
+
+async fn summary(
+    state: &State<AppState>,
+    input: summary::Input,
+) -> (i64, i64, Vec<(String, i64, i64, i64)>) {
+    let Json(summary) = summary::execute(state.clone(), Json(input))
+        .await
+        .expect("To summarise");
+    (
+        summary.income,
+        summary.unassigned,
+        summary
+            .envelopes
+            .into_iter()
+            .map(|e| (e.name, e.allocated, e.spent, e.available))
+            .collect(),
+    )
+}
+
+#[tokio::test]
+async fn envelopes_work() {
+    let state = State(AppState::new_test().await);
+    let _ = transaction::save::execute(
+        state.clone(),
+        Json(vec![
+            new_transaction("1", "Income:Salary", "Bank", 3000, "2020-01-01"),
+            new_transaction("2", "Bank", "Expenses:Food", 200, "2020-01-05"),
+            new_transaction("3", "Bank", "Expenses:Food:Takeaway", 50, "2020-01-06"),
+            new_transaction("4", "Bank", "Expenses:Rent", 1000, "2020-01-07"),
+            new_transaction("5", "Expenses:Food", "Bank", 20, "2020-01-08"),
+        ]),
+    )
+    .await
+    .expect("To save transactions");
+
+    let _ = income_accounts::save::execute(state.clone(), Json(vec!["Income".to_string()]))
+        .await
+        .expect("To save income accounts");
+    let _ = save::execute(
+        state.clone(),
+        Json(vec![
+            envelope("Food", &["Expenses:Food"]),
+            envelope("Takeaway", &["Expenses:Food:Takeaway"]),
+            envelope("Rent", &["Expenses:Rent"]),
+        ]),
+    )
+    .await
+    .expect("To save envelopes");
+    assert!(save::execute(
+        state.clone(),
+        Json(vec![envelope("Eating out", &["expenses:food:takeaway"])])
+    )
+    .await
+    .is_err());
+
+    let _ = allocation::save::execute(
+        state.clone(),
+        Json(vec![
+            allocation(None, Some("Food"), 300),
+            allocation(None, Some("Rent"), 1100),
+            allocation(None, Some("Takeaway"), 40),
+            allocation(Some("Food"), Some("Takeaway"), 30),
+            allocation(Some("Rent"), None, 100),
+        ]),
+    )
+    .await
+    .expect("To save allocations");
+
+    for invalid in [
+        allocation(None, Some("Holiday"), 10),
+        allocation(Some("food"), Some("Food"), 10),
+    ] {
+        assert!(matches!(
+            allocation::save::execute(state.clone(), Json(vec![invalid])).await,
+            Err(crate::service::Error::InvalidArgument(_))
+        ));
+    }
+
+    // Takeaway spending comes out of its own envelope rather than Food's.
+    assert_eq!(
+        summary(&state, Default::default()).await,
+        (
+            3000,
+            1660,
+            vec![
+                ("Food".to_string(), 270, 180, 90),
+                ("Rent".to_string(), 1000, 1000, 0),
+                ("Takeaway".to_string(), 70, 50, 20),
+            ]
+        )
+    );
+    assert_eq!(
+        summary(
+            &state,
+            summary::Input {
+                from: None,
+                as_of: "2020-01-05".parse().ok(),
+            }
+        )
+        .await
+        .2[0],
+        ("Food".to_string(), 270, 200, 70)
+    );
+
+    let Json(allocations) = allocation::list::execute(
+        state.clone(),
+        Json(allocation::list::Input {
+            envelope: Some("takeaway".to_string()),
+            ..Default::default()
+        }),
+    )
+    .await
+    .expect("To list allocations");
+    assert_eq!(allocations.len(), 2);
+
+    // Deleting envelopes returns what is left in them to unassigned income, and the envelopes
+    // they moved money to keep it.
+    let _ = delete::execute(
+        state.clone(),
+        Json(vec!["Rent".to_string(), "food ".to_string()]),
+    )
+    .await
+    .expect("To delete");
+    assert_eq!(
+        summary(&state, Default::default()).await,
+        (3000, 1750, vec![("Takeaway".to_string(), 70, 50, 20)])
+    );
+    assert_eq!(
+        list::execute(state.clone()).await.expect("To list").0,
+        vec![envelope("Takeaway", &["Expenses:Food:Takeaway"])]
+    );
+    let Json(allocations) = allocation::list::execute(
+        state.clone(),
+        Json(allocation::list::Input {
+            envelope: Some("takeaway".to_string()),
+            ..Default::default()
+        }),
+    )
+    .await
+    .expect("To list allocations");
+    assert_eq!(
+        allocations
+            .iter()
+            .map(|a| (a.from_envelope.as_deref(), a.amount))
+            .collect::<Vec<_>>(),
+        vec![(None, 40), (None, 30)]
+    );
+    assert_eq!(
+        income_accounts::get::execute(state.clone())
+            .await
+            .unwrap()
+            .0,
+        vec!["Income".to_string()]
+    );
+    let _ = router();
+}
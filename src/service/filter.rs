@@ -0,0 +1,587 @@
+//! A small filter language for transactions, for example:
+//!
+//! `amount>100 account:"Groceries" tag:holiday -tag:work date:2024-01..2024-03 desc:~uber`
+//!
+//! Terms are separated by whitespace and all of them must match. A term is either a bare word,
+//! which searches the description, or `field<op>value` where `op` is one of `:`, `=`, `>`, `>=`,
+//! `<` or `<=`. A leading `-` negates a term, a `~` before the value turns an exact match into a
+//! substring match, and `a..b` gives an inclusive range for amounts and dates. Values can be
+//! double quoted to include spaces.
+//!
+//! The compiled SQL refers to the transaction as `t` and only ever binds values as parameters.
+
+use std::borrow::Cow;
+use std::convert::TryFrom;
+
+use chrono::NaiveDate;
+
+use super::query::QueryArgs;
+use super::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Description,
+    Account,
+    FromAccount,
+    ToAccount,
+    Tag,
+    Amount,
+    Date,
+}
+
+impl Field {
+    fn parse(name: &str) -> Result<Self> {
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "desc" | "description" => Field::Description,
+            "account" => Field::Account,
+            "from" => Field::FromAccount,
+            "to" => Field::ToAccount,
+            "tag" => Field::Tag,
+            "amount" => Field::Amount,
+            "date" => Field::Date,
+            _ => return Err(invalid(format!("Unknown field `{name}`"))),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Term<'a> {
+    negated: bool,
+    field: Field,
+    op: Op,
+    contains: bool,
+    quoted: bool,
+    value: Cow<'a, str>,
+}
+
+fn invalid(msg: String) -> Error {
+    Error::InvalidArgument(Cow::Owned(msg))
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn quoted(&mut self) -> Result<Cow<'a, str>> {
+        let start = self.pos;
+        let mut value = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(Cow::Owned(value));
+                }
+                '\\' => match chars.next() {
+                    Some((_, c)) => value.push(c),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        Err(invalid(format!(
+            "Unterminated quote at position {}",
+            start - 1
+        )))
+    }
+
+    fn op(&mut self) -> Option<Op> {
+        [
+            (">=", Op::Gte),
+            ("<=", Op::Lte),
+            (":", Op::Eq),
+            ("=", Op::Eq),
+            (">", Op::Gt),
+            ("<", Op::Lt),
+        ]
+        .iter()
+        .find(|(s, _)| self.eat(s))
+        .map(|(_, op)| *op)
+    }
+
+    fn value(&mut self) -> Result<(Cow<'a, str>, bool)> {
+        if self.eat("\"") {
+            Ok((self.quoted()?, true))
+        } else {
+            Ok((
+                Cow::Borrowed(self.take_while(|c| !c.is_whitespace())),
+                false,
+            ))
+        }
+    }
+
+    fn next_term(&mut self) -> Result<Option<Term<'a>>> {
+        self.skip_whitespace();
+        if self.rest().is_empty() {
+            return Ok(None);
+        }
+
+        let negated = self.rest().starts_with('-')
+            && self.rest()[1..].starts_with(|c: char| !c.is_whitespace())
+            && self.eat("-");
+
+        let start = self.pos;
+        let name = self.take_while(|c| c.is_ascii_alphabetic());
+        if !name.is_empty() {
+            if let Some(op) = self.op() {
+                let field = Field::parse(name)?;
+                let contains = self.eat("~");
+                let (value, quoted) = self.value()?;
+                if value.is_empty() {
+                    return Err(invalid(format!("Missing value for `{name}`")));
+                }
+                return Ok(Some(Term {
+                    negated,
+                    field,
+                    op,
+                    contains,
+                    quoted,
+                    value,
+                }));
+            }
+        }
+
+        self.pos = start;
+        let (value, quoted) = self.value()?;
+        Ok(Some(Term {
+            negated,
+            field: Field::Description,
+            op: Op::Eq,
+            contains: true,
+            quoted,
+            value,
+        }))
+    }
+}
+
+fn parse(input: &str) -> Result<Vec<Term<'_>>> {
+    let mut lexer = Lexer { input, pos: 0 };
+    let mut terms = Vec::new();
+    while let Some(term) = lexer.next_term()? {
+        terms.push(term);
+    }
+    Ok(terms)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Param {
+    Text(String),
+    Integer(i64),
+}
+
+/// A compiled filter expression, ready to be embedded into a `where` clause.
+#[derive(Debug, Default)]
+pub struct Filter {
+    sql: String,
+    params: Vec<Param>,
+    first_param: usize,
+}
+
+impl Filter {
+    /// Compiles `input`, numbering its parameters from `?{first_param}` so that it can be
+    /// appended to a statement that already uses `?1` to `?{first_param - 1}`.
+    pub fn compile(input: &str, first_param: usize) -> Result<Self> {
+        let mut filter = Self {
+            first_param,
+            ..Default::default()
+        };
+
+        let mut conditions = Vec::new();
+        for term in parse(input)? {
+            let condition = filter.condition(&term)?;
+            conditions.push(if term.negated {
+                format!("not ({condition})")
+            } else {
+                condition
+            });
+        }
+
+        filter.sql = conditions.join(" and ");
+        Ok(filter)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sql.is_empty()
+    }
+
+    /// The SQL condition, or `1` if the expression was empty.
+    pub fn sql(&self) -> &str {
+        if self.is_empty() {
+            "1"
+        } else {
+            &self.sql
+        }
+    }
+
+    pub fn bind(self, args: &mut QueryArgs<'_>) {
+        debug_assert_eq!(args.next_index(), self.first_param);
+        for p in self.params {
+            match p {
+                Param::Text(v) => args.add(v),
+                Param::Integer(v) => args.add(v),
+            };
+        }
+    }
+
+    fn param(&mut self, p: Param) -> String {
+        self.params.push(p);
+        format!("?{}", self.first_param + self.params.len() - 1)
+    }
+
+    /// Matches any of `columns` against the term's value.
+    fn text(&mut self, columns: &[&str], term: &Term) -> Result<String> {
+        if term.op != Op::Eq {
+            return Err(invalid(format!(
+                "Comparisons only apply to amount and date, not `{}`",
+                term.value
+            )));
+        }
+
+        let conditions = if term.contains {
+            let p = self.param(Param::Text(format!("%{}%", escape_like(&term.value))));
+            columns
+                .iter()
+                .map(|column| format!("{column} like {p} escape '\\'"))
+                .collect::<Vec<_>>()
+        } else {
+            let p = self.param(Param::Text(term.value.trim().to_string()));
+            columns
+                .iter()
+                .map(|column| format!("{column} = {p} collate nocase"))
+                .collect::<Vec<_>>()
+        };
+
+        Ok(match conditions.as_slice() {
+            [condition] => condition.clone(),
+            _ => format!("({})", conditions.join(" or ")),
+        })
+    }
+
+    /// Like [`Self::text`], but an exact account name also matches the accounts under it.
+    fn account(&mut self, columns: &[&str], term: &Term) -> Result<String> {
+        if term.contains || term.op != Op::Eq {
+            return self.text(columns, term);
+        }
+
+        let p = self.param(Param::Text(term.value.trim().to_string()));
+        let conditions = columns
+            .iter()
+            .map(|column| {
+                format!(
+                    "{column} collate nocase in \
+                     (select account from account_ancestors where ancestor = {p} collate nocase)"
+                )
+            })
+            .collect::<Vec<_>>();
+
+        Ok(match conditions.as_slice() {
+            [condition] => condition.clone(),
+            _ => format!("({})", conditions.join(" or ")),
+        })
+    }
+
+    fn range<T>(
+        &mut self,
+        column: &str,
+        term: &Term,
+        parse: impl Fn(&str) -> Result<(T, T)>,
+        to_param: impl Fn(T) -> Param,
+    ) -> Result<String> {
+        if term.contains {
+            return Err(invalid(format!(
+                "`~` is not supported for `{}`",
+                term.value
+            )));
+        }
+
+        if let (Op::Eq, false, Some((lower, upper))) =
+            (term.op, term.quoted, term.value.split_once(".."))
+        {
+            let mut conditions = Vec::new();
+            if !lower.is_empty() {
+                let p = self.param(to_param(parse(lower)?.0));
+                conditions.push(format!("{column} >= {p}"));
+            }
+            if !upper.is_empty() {
+                let p = self.param(to_param(parse(upper)?.1));
+                conditions.push(format!("{column} <= {p}"));
+            }
+            if conditions.is_empty() {
+                return Err(invalid("Empty range".to_string()));
+            }
+            return Ok(conditions.join(" and "));
+        }
+
+        let (lower, upper) = parse(&term.value)?;
+        Ok(match term.op {
+            Op::Eq => {
+                let lower = self.param(to_param(lower));
+                let upper = self.param(to_param(upper));
+                format!("{column} between {lower} and {upper}")
+            }
+            Op::Gt => format!("{column} > {}", self.param(to_param(upper))),
+            Op::Gte => format!("{column} >= {}", self.param(to_param(lower))),
+            Op::Lt => format!("{column} < {}", self.param(to_param(lower))),
+            Op::Lte => format!("{column} <= {}", self.param(to_param(upper))),
+        })
+    }
+
+    fn condition(&mut self, term: &Term) -> Result<String> {
+        match term.field {
+            Field::Description => self.text(&["t.description"], term),
+            Field::FromAccount => self.account(&["trim(t.fromAccount)"], term),
+            Field::ToAccount => self.account(&["trim(t.toAccount)"], term),
+            Field::Account => self.account(&["trim(t.fromAccount)", "trim(t.toAccount)"], term),
+            Field::Tag => {
+                let tag = if term.contains || term.op != Op::Eq {
+                    self.text(&["tt.tag"], term)?
+                } else {
+                    let p = self.param(Param::Text(term.value.trim().to_string()));
+                    format!(
+                        "tt.tag in (select tag from tag_ancestors where ancestor = {p} collate nocase)"
+                    )
+                };
+                Ok(format!(
+                    "exists (select 1 from transaction_tags tt where tt.transactionId = t.id and {tag})"
+                ))
+            }
+            Field::Amount => self.range(
+                "t.amount",
+                term,
+                |v| parse_amount(v).map(|v| (v, v)),
+                Param::Integer,
+            ),
+            Field::Date => self.range("t.transDate", term, parse_date, |d| {
+                Param::Text(d.to_string())
+            }),
+        }
+    }
+}
+
+fn escape_like(v: &str) -> String {
+    let mut escaped = String::with_capacity(v.len());
+    for c in v.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Parses a dollar amount such as `12`, `-3.5` or `1,200.25` into cents.
+fn parse_amount(v: &str) -> Result<i64> {
+    let err = || invalid(format!("Invalid amount `{v}`"));
+    let cleaned = v.trim_start_matches('$').replace(',', "");
+    let (negative, cleaned) = match cleaned.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, cleaned.as_str()),
+    };
+    let (dollars, cents) = cleaned.split_once('.').unwrap_or((cleaned, ""));
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if dollars.is_empty() && cents.is_empty()
+        || cents.len() > 2
+        || !digits(dollars)
+        || !digits(cents)
+    {
+        return Err(err());
+    }
+
+    let dollars: i64 = if dollars.is_empty() {
+        0
+    } else {
+        dollars.parse().map_err(|_| err())?
+    };
+    let cents: i64 = if cents.is_empty() {
+        0
+    } else {
+        format!("{cents:0<2}").parse().map_err(|_| err())?
+    };
+
+    let amount = dollars
+        .checked_mul(100)
+        .and_then(|v| v.checked_add(cents))
+        .ok_or_else(err)?;
+    Ok(if negative { -amount } else { amount })
+}
+
+/// Parses `2024`, `2024-03` or `2024-03-15` into the first and last day it covers.
+fn parse_date(v: &str) -> Result<(NaiveDate, NaiveDate)> {
+    let err = || invalid(format!("Invalid date `{v}`"));
+    let parts = v
+        .split('-')
+        .map(|p| p.parse::<u32>().map_err(|_| err()))
+        .collect::<Result<Vec<_>>>()?;
+
+    let date = |y: u32, m: u32, d: u32| {
+        i32::try_from(y)
+            .ok()
+            .and_then(|y| NaiveDate::from_ymd_opt(y, m, d))
+            .ok_or_else(err)
+    };
+
+    match parts.as_slice() {
+        [y] => Ok((date(*y, 1, 1)?, date(*y, 12, 31)?)),
+        [y, m] => {
+            let start = date(*y, *m, 1)?;
+            let next = if *m == 12 {
+                date(y + 1, 1, 1)?
+            } else {
+                date(*y, m + 1, 1)?
+            };
+            Ok((start, next.pred_opt().ok_or_else(err)?))
+        }
+        [y, m, d] => {
+            let d = date(*y, *m, *d)?;
+            Ok((d, d))
+        }
+        _ => Err(err()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(input: &str) -> Filter {
+        Filter::compile(input, 1).expect("To compile")
+    }
+
+    #[test]
+    fn parse_works() {
+        let terms = parse(r#"-tag:work desc:~"uber eats" amount>=10 lunch"#).expect("To parse");
+        assert_eq!(
+            terms,
+            vec![
+                Term {
+                    negated: true,
+                    field: Field::Tag,
+                    op: Op::Eq,
+                    contains: false,
+                    quoted: false,
+                    value: "work".into(),
+                },
+                Term {
+                    negated: false,
+                    field: Field::Description,
+                    op: Op::Eq,
+                    contains: true,
+                    quoted: true,
+                    value: "uber eats".into(),
+                },
+                Term {
+                    negated: false,
+                    field: Field::Amount,
+                    op: Op::Gte,
+                    contains: false,
+                    quoted: false,
+                    value: "10".into(),
+                },
+                Term {
+                    negated: false,
+                    field: Field::Description,
+                    op: Op::Eq,
+                    contains: true,
+                    quoted: false,
+                    value: "lunch".into(),
+                },
+            ]
+        );
+
+        assert!(parse(r#"desc:"unterminated"#).is_err());
+        assert!(parse("colour:red").is_err());
+        assert!(parse("tag:").is_err());
+    }
+
+    #[test]
+    fn compile_works() {
+        let filter = compile(r#"amount>100 account:"Groceries" -tag:work"#);
+        assert_eq!(
+            filter.sql(),
+            "t.amount > ?1 \
+            and (trim(t.fromAccount) collate nocase in \
+            (select account from account_ancestors where ancestor = ?2 collate nocase) \
+            or trim(t.toAccount) collate nocase in \
+            (select account from account_ancestors where ancestor = ?2 collate nocase)) \
+            and not (exists (select 1 from transaction_tags tt where tt.transactionId = t.id \
+            and tt.tag in (select tag from tag_ancestors where ancestor = ?3 collate nocase)))"
+        );
+        assert_eq!(
+            filter.params,
+            vec![
+                Param::Integer(10000),
+                Param::Text("Groceries".into()),
+                Param::Text("work".into()),
+            ]
+        );
+
+        let filter = compile("date:2024-01..2024-02 desc:~50%");
+        assert_eq!(
+            filter.sql(),
+            "t.transDate >= ?1 and t.transDate <= ?2 and t.description like ?3 escape '\\'"
+        );
+        assert_eq!(
+            filter.params,
+            vec![
+                Param::Text("2024-01-01".into()),
+                Param::Text("2024-02-29".into()),
+                Param::Text("%50\\%%".into()),
+            ]
+        );
+
+        assert_eq!(compile("  ").sql(), "1");
+        assert!(Filter::compile("amount:~5", 1).is_err());
+        assert!(Filter::compile("tag>5", 1).is_err());
+        assert!(Filter::compile("date:2024-13", 1).is_err());
+    }
+
+    #[test]
+    fn parse_amount_works() {
+        assert_eq!(parse_amount("100").unwrap(), 10000);
+        assert_eq!(parse_amount("1,200.5").unwrap(), 120050);
+        assert_eq!(parse_amount("-0.05").unwrap(), -5);
+        assert_eq!(parse_amount("$3").unwrap(), 300);
+        assert!(parse_amount("1.234").is_err());
+        assert!(parse_amount("abc").is_err());
+        assert!(parse_amount("1.-5").is_err());
+        assert!(parse_amount("1.+5").is_err());
+        assert!(parse_amount("--5").is_err());
+        assert!(parse_amount("+5").is_err());
+    }
+}
@@ -1,3 +1,3 @@
 use crate::state::AppState;
 
-pub async fn execute(state: AppState) {}
+pub async fn execute(_state: AppState) {}
@@ -107,7 +107,7 @@ impl CredentialsConfig {
         Signed(Cow::from(base64::encode(result, DEFAULT_VARIANT)))
     }
 
-    pub fn verify(&self, input: &Signed) -> Option<Asset> {
+    pub fn verify(&self, input: &Signed) -> Option<Asset<'_>> {
         let token = base64::decode(input.as_bytes(), DEFAULT_VARIANT).ok()?;
         if token.len() < crypto::auth::TAGBYTES {
             return None;
@@ -136,10 +136,10 @@ impl CredentialsConfig {
             crypto::pwhash::pwhash_interactive(pw.as_bytes()).unwrap(),
             DEFAULT_VARIANT,
         );
-        return Self {
+        Self {
             password_hashed,
             signing_key,
-        };
+        }
     }
 }
 
@@ -156,7 +156,7 @@ mod tests {
         assert!(c.verify(&token).is_some());
         assert_eq!(c.verify_password("12345"), Some(()));
         assert_eq!(c.verify_password("123456"), None);
-        let token = format!("{}1", token.to_string());
+        let token = format!("{}1", *token);
         assert_eq!(c.verify(&Signed(Cow::from(token))), None);
     }
 }
@@ -10,17 +10,14 @@ async fn login_works() {
     let state = State(AppState::new_test().await);
 
     // Verify should pass when no password set
-    assert_eq!(
-        verify::query(
-            &state,
-            verify::Input {
-                token: Signed(Cow::from("")),
-            },
-        )
-        .await
-        .expect("To verify"),
-        true
-    );
+    assert!(verify::query(
+        &state,
+        verify::Input {
+            token: Signed(Cow::from("")),
+        },
+    )
+    .await
+    .expect("To verify"));
 
     // Update without password
     let token = update::execute(
@@ -39,30 +36,24 @@ async fn login_works() {
     assert!(!token.is_empty());
 
     // Verify should not pass without token
-    assert_eq!(
-        verify::query(
-            &state,
-            verify::Input {
-                token: Signed(Cow::from("")),
-            }
-        )
-        .await
-        .expect("To verify"),
-        false
-    );
+    assert!(!verify::query(
+        &state,
+        verify::Input {
+            token: Signed(Cow::from("")),
+        }
+    )
+    .await
+    .expect("To verify"));
 
     // Verify should pass with token
-    assert_eq!(
-        verify::query(
-            &state,
-            verify::Input {
-                token: Signed(Cow::from(token.clone())),
-            }
-        )
-        .await
-        .expect("To verify"),
-        true,
-    );
+    assert!(verify::query(
+        &state,
+        verify::Input {
+            token: Signed(token.clone()),
+        }
+    )
+    .await
+    .expect("To verify"));
 
     // Update should not pass without correct old password
     assert!(update::execute(
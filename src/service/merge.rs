@@ -0,0 +1,100 @@
+//! The parts of renaming and merging that accounts and tags share.
+
+use sqlx::{sqlite::SqliteRow, FromRow, Sqlite, Transaction};
+
+use crate::{
+    service::{Error, Result},
+    sqlx_ext,
+};
+
+/// The trimmed `sources` and `target` of merging `kind`s, e.g. "account", none of them empty.
+pub fn names<'a>(
+    kind: &str,
+    sources: &[String],
+    target: &'a str,
+) -> Result<(sqlx_ext::Json<Vec<String>>, &'a str)> {
+    let target = target.trim();
+    if target.is_empty() {
+        return Err(Error::InvalidArgument(
+            format!("The target {kind} can't be empty").into(),
+        ));
+    }
+
+    let sources: Vec<String> = sources
+        .iter()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if sources.is_empty() {
+        return Err(Error::InvalidArgument(
+            format!("At least one source {kind} is needed").into(),
+        ));
+    }
+    Ok((sqlx_ext::Json(sources), target))
+}
+
+/// Checks `from` can be renamed to `to`, where `exists_sql` selects whether its only parameter
+/// names an existing `kind`, e.g. "Account". Renaming into an existing name is a merge, except
+/// for changing the case of the same name.
+pub async fn check_rename(
+    tx: &mut Transaction<'_, Sqlite>,
+    kind: &str,
+    exists_sql: &str,
+    from: &str,
+    to: &str,
+) -> Result<()> {
+    let exists =
+        |name: &str| sqlx::query_as::<_, (bool,)>(exists_sql).bind(name.trim().to_string());
+
+    let (from_exists,) = exists(from).fetch_one(&mut **tx).await?;
+    if !from_exists {
+        return Err(Error::ResourceNotFound);
+    }
+
+    let (to_exists,) = exists(to).fetch_one(&mut **tx).await?;
+    if to_exists && !from.trim().eq_ignore_ascii_case(to.trim()) {
+        return Err(Error::InvalidArgument(
+            format!("{kind} {} already exists, merge into it instead", to.trim()).into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Takes the metadata of `sources` and `target` out of `table`, keyed by `name`, for the merged
+/// metadata to be saved under `target` in their place. The target's comes first, so it is kept
+/// when there is one, or else the first source's.
+///
+/// Returns how many rows were taken out, and the rows.
+pub async fn take_metas<M>(
+    tx: &mut Transaction<'_, Sqlite>,
+    table: &str,
+    sources: &sqlx_ext::Json<Vec<String>>,
+    target: &str,
+) -> Result<(u64, Vec<M>)>
+where
+    M: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+{
+    let metas: Vec<M> = sqlx::query_as(&format!(
+        "select * from {table} \
+         where name in (select trim(value) from json_each(?1) union select trim(?2)) \
+         order by name = trim(?2) desc"
+    ))
+    .bind(sources)
+    .bind(target)
+    .fetch_all(&mut **tx)
+    .await?;
+    if metas.is_empty() {
+        return Ok((0, metas));
+    }
+
+    let taken = sqlx::query(&format!(
+        "delete from {table} \
+         where name in (select trim(value) from json_each(?1) union select trim(?2))"
+    ))
+    .bind(sources)
+    .bind(target)
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+    Ok((taken, metas))
+}
@@ -1,14 +1,21 @@
 pub mod account;
 pub mod account_group;
+pub mod alert;
 pub mod attachment;
+pub mod budget;
 pub mod config;
+pub mod envelope;
 mod error;
+mod filter;
+pub mod import;
 pub mod login;
+mod merge;
 mod query;
+pub mod recurring;
 pub mod report;
+pub mod rule;
 pub mod tag;
 pub mod transaction;
-pub mod import;
 
 pub use error::Error;
 use itertools::Itertools;
@@ -40,25 +47,8 @@ where
     F: ToSQL,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut generated_fields = Vec::with_capacity(self.0.as_ref().len());
-
-        for Sort { field, order } in self.0.as_ref() {
-            let field = match field.to_sql() {
-                Some(v) => v,
-                None => continue,
-            };
-
-            match generated_fields.binary_search(&field) {
-                Ok(_) => {
-                    log::warn!("Field {field:?} already generated");
-                    continue;
-                }
-                Err(index) => {
-                    generated_fields.insert(index, field);
-                }
-            };
-
-            if generated_fields.len() > 1 {
+        for (i, (field, order)) in unique_sorts(self.0.as_ref()).into_iter().enumerate() {
+            if i > 0 {
                 f.write_str(", ")?;
             } else {
                 f.write_str("order by ")?;
@@ -70,6 +60,33 @@ where
     }
 }
 
+/// The SQL of each sort field in order, skipping fields without SQL and repeated fields.
+pub fn unique_sorts<F: ToSQL>(sorts: &[Sort<F>]) -> Vec<(&str, SortOrder)> {
+    let mut generated_fields = Vec::with_capacity(sorts.len());
+    let mut result = Vec::with_capacity(sorts.len());
+
+    for Sort { field, order } in sorts {
+        let field = match field.to_sql() {
+            Some(v) => v,
+            None => continue,
+        };
+
+        match generated_fields.binary_search(&field) {
+            Ok(_) => {
+                log::warn!("Field {field:?} already generated");
+                continue;
+            }
+            Err(index) => {
+                generated_fields.insert(index, field);
+            }
+        };
+
+        result.push((field, *order));
+    }
+
+    result
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
 pub struct Sort<F> {
     pub field: F,
@@ -83,18 +100,27 @@ impl<F> Sort<F> {
 }
 
 #[derive(Serialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct PaginatedResponse<T> {
     pub data: Vec<T>,
-    pub total: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 impl<T> PaginatedResponse<T> {
     pub fn map<R>(self, f: impl Fn(T) -> R) -> PaginatedResponse<R> {
-        let PaginatedResponse { data, total } = self;
-        return PaginatedResponse {
+        let PaginatedResponse {
+            data,
+            total,
+            next_cursor,
+        } = self;
+        PaginatedResponse {
             data: data.into_iter().map(f).collect_vec(),
             total,
-        };
+            next_cursor,
+        }
     }
 }
 
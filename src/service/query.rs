@@ -1,24 +1,24 @@
-use std::{convert::TryInto, fmt::Display};
+use std::{borrow::Cow, convert::TryFrom, fmt::Display};
 
 use anyhow::Context;
+use sodiumoxide::base64;
 use sqlx::{
-    query_as_with,
+    query_as_with, query_with,
     sqlite::{SqliteArguments, SqliteRow},
-    FromRow, SqlitePool,
+    Arguments, Encode, FromRow, Row, Sqlite, SqlitePool, Type,
 };
 
-use super::{display_sorts_sql, Sort, SortOrder, ToSQL};
+use super::{unique_sorts, Error, Result, Sort, SortOrder, ToSQL};
 
 #[macro_export]
 macro_rules! bind_sqlite_args {
     ($arg1:expr $(,$arg:expr)*) => {
-        (|| {
-            use sqlx::Arguments;
-            let mut args = sqlx::sqlite::SqliteArguments::default();
+        {
+            let mut args = $crate::service::query::QueryArgs::default();
             args.add($arg1);
             $(args.add($arg);)*
             args
-        })()
+        }
     };
 }
 
@@ -31,15 +31,59 @@ impl SortOrder {
     }
 }
 
+/// Arguments for a statement that keeps count of how many have been bound, so that more
+/// conditions can be appended with `?N` parameters after them.
+#[derive(Default, Clone)]
+pub struct QueryArgs<'q> {
+    args: SqliteArguments<'q>,
+    len: usize,
+}
+
+impl<'q> QueryArgs<'q> {
+    /// Binds `value` and returns its parameter index.
+    pub fn add<T>(&mut self, value: T) -> usize
+    where
+        T: 'q + Send + Encode<'q, Sqlite> + Type<Sqlite>,
+    {
+        self.args.add(value);
+        self.len += 1;
+        self.len
+    }
+
+    /// The parameter index the next bound value will take.
+    pub fn next_index(&self) -> usize {
+        self.len + 1
+    }
+}
+
+/// Which page of a list to fetch.
+///
+/// Pages are addressed either by `offset`, or by an opaque `cursor` taken from the previous
+/// page's response. Cursors seek directly to the next row using the active sort fields, so they
+/// stay fast however deep the page is; `offset` is ignored when a cursor is given.
+pub struct PageRequest<'a, F> {
+    pub limit: i64,
+    pub offset: i64,
+    pub cursor: Option<&'a str>,
+    pub sorts: &'a [Sort<F>],
+    /// A column that uniquely identifies each row, used to break ties between sort values.
+    pub key: &'a str,
+    /// The select list of the aggregate query, or `None` to skip it.
+    pub aggregate_select: Option<&'a str>,
+}
+
+pub struct Page<Item, Aggregates> {
+    pub items: Vec<Item>,
+    pub aggregates: Option<Aggregates>,
+    pub next_cursor: Option<String>,
+}
+
 pub async fn create_paginated_query<F, Item, Aggregates>(
     c: &SqlitePool,
     sql: &str,
-    args: SqliteArguments<'_>,
-    limit: impl TryInto<usize>,
-    offset: impl TryInto<usize>,
-    sorts: impl AsRef<[Sort<F>]>,
-    aggregate_select: &str,
-) -> anyhow::Result<(Vec<Item>, Aggregates)>
+    mut args: QueryArgs<'_>,
+    page: PageRequest<'_, F>,
+) -> Result<Page<Item, Aggregates>>
 where
     Item: Send + for<'q> FromRow<'q, SqliteRow> + Unpin + 'static,
     Aggregates: Send + Unpin + for<'q> FromRow<'q, SqliteRow> + 'static,
@@ -47,33 +91,137 @@ where
 {
     let mut tx = c.begin().await?;
 
+    let mut fields = unique_sorts(page.sorts);
+    if fields.iter().all(|(field, _)| *field != page.key) {
+        fields.push((page.key, SortOrder::ASC));
+    }
+    let signature = fields
+        .iter()
+        .map(|(field, order)| format!("{field} {}", order.to_sqlite()))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    // Query the aggregate
+    let aggregates = match page.aggregate_select {
+        Some(aggregate_select) => {
+            let sql = format!("with cte as ({sql}) select {aggregate_select} from cte");
+            Some(
+                query_as_with::<_, Aggregates, _>(&sql, args.args.clone())
+                    .fetch_one(&mut *tx)
+                    .await
+                    .context("Querying aggregate")?,
+            )
+        }
+        None => None,
+    };
+
     // Query the list
-    let items: Vec<Item> = {
-        let limit_offset = LimitOffset {
-            limit: limit.try_into().ok(),
-            offset: offset.try_into().ok(),
-        };
-        let sql = format!(
-            "WITH cte AS ({sql}) SELECT * from cte {order} {limit_offset}",
-            order = display_sorts_sql(sorts)
-        );
-
-        query_as_with(&sql, args.clone())
-            .fetch_all(&mut *tx)
-            .await
-            .context("Querying list")?
+    let keyset = match page.cursor {
+        Some(cursor) => {
+            let values = decode_cursor(cursor, &signature)?;
+            keyset_sql(&fields, args.add(values))
+        }
+        None => Cow::Borrowed("1"),
     };
 
-    // Query the aggregate
-    let aggregates = {
-        let sql = format!("with cte as ({sql}) select {aggregate_select} from cte");
-        query_as_with::<_, Aggregates, _>(&sql, args)
-            .fetch_one(&mut *tx)
-            .await
-            .context("Querying aggregate")?
+    let limit_offset = LimitOffset {
+        limit: usize::try_from(page.limit).ok(),
+        offset: match page.cursor {
+            Some(_) => None,
+            None => usize::try_from(page.offset).ok(),
+        },
+    };
+
+    let sql = format!(
+        "WITH cte AS ({sql}) \
+         SELECT *, json_array({values}) AS _cursor FROM cte \
+         WHERE {keyset} ORDER BY {signature} {limit_offset}",
+        values = fields
+            .iter()
+            .map(|(field, _)| *field)
+            .collect::<Vec<_>>()
+            .join(", "),
+    );
+
+    let rows = query_with(&sql, args.args)
+        .fetch_all(&mut *tx)
+        .await
+        .context("Querying list")?;
+
+    let next_cursor = match (rows.last(), limit_offset.limit) {
+        (Some(row), Some(limit)) if rows.len() >= limit => {
+            let values: String = row.try_get("_cursor")?;
+            Some(encode_cursor(&signature, &values))
+        }
+        _ => None,
     };
 
-    Ok((items, aggregates))
+    let items = rows
+        .iter()
+        .map(Item::from_row)
+        .collect::<sqlx::Result<Vec<_>>>()?;
+
+    Ok(Page {
+        items,
+        aggregates,
+        next_cursor,
+    })
+}
+
+/// The condition for rows that sort strictly after the cursor values bound at `?{param}`,
+/// following SQLite's ordering where `null` comes first.
+fn keyset_sql(fields: &[(&str, SortOrder)], param: usize) -> Cow<'static, str> {
+    let value = |i: usize| format!("json_extract(?{param}, '$[{i}]')");
+
+    let conditions = fields
+        .iter()
+        .enumerate()
+        .map(|(i, (field, order))| {
+            let mut terms = fields[..i]
+                .iter()
+                .enumerate()
+                .map(|(j, (field, _))| format!("{field} is {}", value(j)))
+                .collect::<Vec<_>>();
+
+            terms.push(match order {
+                SortOrder::ASC => format!(
+                    "({field} > {v} or ({field} is not null and {v} is null))",
+                    v = value(i)
+                ),
+                SortOrder::DESC => format!(
+                    "({field} < {v} or ({field} is null and {v} is not null))",
+                    v = value(i)
+                ),
+            });
+            format!("({})", terms.join(" and "))
+        })
+        .collect::<Vec<_>>();
+
+    Cow::Owned(format!("({})", conditions.join(" or ")))
+}
+
+fn encode_cursor(signature: &str, values: &str) -> String {
+    base64::encode(
+        format!("{signature}\n{values}"),
+        base64::Variant::UrlSafeNoPadding,
+    )
+}
+
+fn decode_cursor(cursor: &str, signature: &str) -> Result<String> {
+    let invalid = || Error::InvalidArgument(Cow::Borrowed("Invalid cursor"));
+
+    let decoded = base64::decode(cursor, base64::Variant::UrlSafeNoPadding)
+        .ok()
+        .and_then(|v| String::from_utf8(v).ok())
+        .ok_or_else(invalid)?;
+
+    match decoded.split_once('\n') {
+        Some((s, values)) if s == signature => Ok(values.to_string()),
+        Some(_) => Err(Error::InvalidArgument(Cow::Borrowed(
+            "The cursor was created with different sorts",
+        ))),
+        None => Err(invalid()),
+    }
 }
 
 struct LimitOffset {
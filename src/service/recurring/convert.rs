@@ -0,0 +1,79 @@
+use axum::extract::{Json, State};
+use chrono::NaiveDate;
+
+use crate::{
+    service::{Error, Result},
+    state::AppState,
+};
+
+use super::{
+    detect::{detect, normalise, payment_key},
+    model::RecurringTransaction,
+    save::save,
+};
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    pub description: String,
+    pub from_account: String,
+    pub to_account: String,
+    /// The day it was detected as of, today when left out.
+    pub as_of: Option<NaiveDate>,
+}
+
+/// Turns a detected pattern into a recurring transaction, starting from the next occurrence
+/// expected at the latest amount. A pattern already turned into one gives that one back.
+pub async fn execute(
+    state: State<AppState>,
+    Json(Input {
+        description,
+        from_account,
+        to_account,
+        as_of,
+    }): Json<Input>,
+) -> Result<Json<RecurringTransaction>> {
+    let as_of = as_of.unwrap_or_else(|| chrono::Local::now().date_naive());
+    let description = normalise(&description);
+    let pattern = detect(&state.conn, as_of)
+        .await?
+        .into_iter()
+        .find(|p| {
+            normalise(&p.description) == description
+                && p.from_account.eq_ignore_ascii_case(from_account.trim())
+                && p.to_account.eq_ignore_ascii_case(to_account.trim())
+        })
+        .ok_or(Error::ResourceNotFound)?;
+
+    let key = payment_key(
+        &pattern.description,
+        &pattern.from_account,
+        &pattern.to_account,
+    );
+    let mut tx = state.conn.begin().await?;
+    let existing: Vec<RecurringTransaction> =
+        sqlx::query_as("select * from recurring_transactions order by id")
+            .fetch_all(&mut *tx)
+            .await?;
+    if let Some(existing) = existing
+        .into_iter()
+        .find(|r| payment_key(&r.description, &r.from_account, &r.to_account) == key)
+    {
+        return Ok(Json::from(existing));
+    }
+
+    let mut recurring = RecurringTransaction {
+        id: None,
+        description: pattern.description,
+        from_account: pattern.from_account,
+        to_account: pattern.to_account,
+        amount: pattern.last_amount,
+        cadence: pattern.cadence,
+        start_date: pattern.next_expected,
+        end_date: None,
+        tags: Default::default(),
+    };
+    recurring.id = save(&mut tx, vec![recurring.clone()]).await?.pop();
+    tx.commit().await?;
+    Ok(Json::from(recurring))
+}
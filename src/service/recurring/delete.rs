@@ -0,0 +1,21 @@
+use axum::extract::{Json, State};
+
+use crate::{
+    service::{GenericUpdateResponse, Result},
+    sqlx_ext,
+    state::AppState,
+};
+
+pub async fn execute(
+    state: State<AppState>,
+    Json(ids): Json<Vec<i64>>,
+) -> Result<Json<GenericUpdateResponse>> {
+    let output: GenericUpdateResponse = sqlx::query(
+        "delete from recurring_transactions where id in (select value from json_each(?))",
+    )
+    .bind(sqlx_ext::Json(ids))
+    .execute(&state.conn)
+    .await?
+    .into();
+    Ok(output.into())
+}
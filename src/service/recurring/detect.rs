@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+
+use axum::extract::{Json, State};
+use chrono::NaiveDate;
+
+use crate::{service::Result, state::AppState};
+
+use super::model::Cadence;
+
+/// How many times a transaction has to be seen before it counts as regular.
+const MIN_OCCURRENCES: usize = 3;
+
+/// How far, as a fraction of the one before, an amount may drift and still belong to a pattern.
+/// A single bigger step is taken as a change of price rather than a different payment.
+const MAX_AMOUNT_DRIFT: f64 = 0.2;
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    /// The day to detect as of, today when left out.
+    pub as_of: Option<NaiveDate>,
+}
+
+/// The latest change in amount, when it went up.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceIncrease {
+    pub date: NaiveDate,
+    pub previous_amount: i64,
+    pub amount: i64,
+}
+
+/// A transaction seen repeating at a regular [`Cadence`].
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Pattern {
+    /// The description of the latest occurrence.
+    pub description: String,
+    pub from_account: String,
+    pub to_account: String,
+    pub cadence: Cadence,
+    pub occurrences: usize,
+    pub average_amount: i64,
+    pub last_amount: i64,
+    pub last_seen: NaiveDate,
+    pub next_expected: NaiveDate,
+    pub price_increase: Option<PriceIncrease>,
+    /// The dates it was expected on but didn't happen, including the overdue ones.
+    pub missed: Vec<NaiveDate>,
+    /// Whether it still looks alive, i.e. at most one occurrence has been missed since it was
+    /// last seen.
+    pub active: bool,
+}
+
+/// The key descriptions are grouped by: lowercase words without the ones holding digits, which
+/// are usually dates and references that change every time.
+pub fn normalise(description: &str) -> String {
+    description
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty() && !w.chars().any(|c| c.is_numeric()))
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// What tells payments apart: the [`normalise`]d description and the lowercase accounts.
+pub type PaymentKey = (String, String, String);
+
+pub fn payment_key(description: &str, from_account: &str, to_account: &str) -> PaymentKey {
+    (
+        normalise(description),
+        from_account.trim().to_lowercase(),
+        to_account.trim().to_lowercase(),
+    )
+}
+
+/// The cadence a gap of `days` between two occurrences fits, if any.
+fn cadence_of(days: i64) -> Option<Cadence> {
+    match days {
+        5..=9 => Some(Cadence::Weekly),
+        12..=16 => Some(Cadence::Fortnightly),
+        26..=35 => Some(Cadence::Monthly),
+        85..=97 => Some(Cadence::Quarterly),
+        355..=375 => Some(Cadence::Yearly),
+        _ => None,
+    }
+}
+
+/// The average length of a period and how many days an occurrence may be off from it.
+fn period_days(cadence: Cadence) -> (f64, f64) {
+    match cadence {
+        Cadence::Weekly => (7.0, 2.0),
+        Cadence::Fortnightly => (14.0, 2.0),
+        Cadence::Monthly => (30.44, 4.0),
+        Cadence::Quarterly => (91.31, 6.0),
+        Cadence::Yearly => (365.25, 10.0),
+    }
+}
+
+/// How many periods a gap of `days` spans, when it is close enough to a whole number of them.
+fn periods_in(cadence: Cadence, days: i64) -> Option<u32> {
+    let (period, tolerance) = period_days(cadence);
+    let n = (days as f64 / period).round();
+    (n >= 1.0 && (days as f64 - n * period).abs() <= tolerance * n).then_some(n as u32)
+}
+
+fn median(values: &mut [i64]) -> i64 {
+    values.sort_unstable();
+    values[values.len() / 2]
+}
+
+/// An occurrence of a transaction: its date, description and amount.
+pub type Occurrence = (NaiveDate, String, i64);
+
+/// The accounts a kind of transaction moves money between, as first seen, and its occurrences.
+type Group = (String, String, Vec<Occurrence>);
+
+/// Looks for a regular pattern in the occurrences of one kind of transaction up to `as_of`, in
+/// date order.
+pub fn find_pattern(
+    from_account: &str,
+    to_account: &str,
+    occurrences: &[Occurrence],
+    as_of: NaiveDate,
+) -> Option<Pattern> {
+    if occurrences.len() < MIN_OCCURRENCES {
+        return None;
+    }
+
+    let gaps: Vec<i64> = occurrences
+        .windows(2)
+        .map(|w| (w[1].0 - w[0].0).num_days())
+        .collect();
+    let cadence = cadence_of(median(&mut gaps.clone()))?;
+    // Most of the gaps have to be whole periods, leaving room for the odd payment moved by a
+    // weekend. Gaps of several periods are missed occurrences.
+    let mut missed = Vec::new();
+    let mut fitting = 0;
+    for (w, gap) in occurrences.windows(2).zip(&gaps) {
+        if let Some(n) = periods_in(cadence, *gap) {
+            fitting += 1;
+            missed.extend((1..n).filter_map(|k| cadence.nth(w[0].0, k)));
+        }
+    }
+    if fitting * 3 < gaps.len() * 2 {
+        return None;
+    }
+
+    let drifts = occurrences.windows(2).map(|w| (w[0].2, w[1].2, w[1].0));
+    let steps = drifts
+        .clone()
+        .filter(|(a, b, _)| (b - a).abs() as f64 > a.abs() as f64 * MAX_AMOUNT_DRIFT)
+        .count();
+    if steps > 1 {
+        return None;
+    }
+    let price_increase = drifts
+        .rev()
+        .find(|(a, b, _)| a != b)
+        .filter(|(a, b, _)| b > a)
+        .map(|(previous_amount, amount, date)| PriceIncrease {
+            date,
+            previous_amount,
+            amount,
+        });
+
+    let (last_seen, description, last_amount) = occurrences.last()?.clone();
+    let (_, tolerance) = period_days(cadence);
+    missed.extend(
+        (1..)
+            .map_while(|k| cadence.nth(last_seen, k))
+            .take_while(|date| (as_of - *date).num_days() as f64 > tolerance),
+    );
+
+    let amounts: Vec<i64> = occurrences.iter().map(|(_, _, amount)| *amount).collect();
+    Some(Pattern {
+        description,
+        from_account: from_account.to_string(),
+        to_account: to_account.to_string(),
+        cadence,
+        occurrences: occurrences.len(),
+        average_amount: (amounts.iter().sum::<i64>() as f64 / amounts.len() as f64).round() as i64,
+        last_amount,
+        last_seen,
+        next_expected: cadence.nth(last_seen, 1)?,
+        price_increase,
+        missed,
+        active: cadence.nth(last_seen, 2).is_some_and(|date| date >= as_of),
+    })
+}
+
+//language=sql
+const SQL: &str = r#"
+select trim(fromAccount), trim(toAccount), transDate, description, amount
+from transactions
+where transDate <= ?
+order by transDate, updatedDate
+"#;
+
+/// Finds the transactions that repeat regularly up to `as_of`, grouped by accounts and
+/// [`normalise`]d description, most recently seen first.
+pub async fn detect(conn: &sqlx::SqlitePool, as_of: NaiveDate) -> Result<Vec<Pattern>> {
+    let rows: Vec<(String, String, NaiveDate, String, i64)> =
+        sqlx::query_as(SQL).bind(as_of).fetch_all(conn).await?;
+
+    let mut groups: BTreeMap<PaymentKey, Group> = BTreeMap::new();
+    for (from, to, date, description, amount) in rows {
+        let key = payment_key(&description, &from, &to);
+        groups
+            .entry(key)
+            .or_insert_with(|| (from, to, Vec::new()))
+            .2
+            .push((date, description, amount));
+    }
+
+    let mut patterns: Vec<Pattern> = groups
+        .values()
+        .filter_map(|(from, to, occurrences)| find_pattern(from, to, occurrences, as_of))
+        .collect();
+    patterns.sort_by(|a, b| {
+        b.last_seen
+            .cmp(&a.last_seen)
+            .then_with(|| a.description.cmp(&b.description))
+    });
+    Ok(patterns)
+}
+
+/// Lists the transactions that repeat regularly, such as subscriptions, salary and rent.
+pub async fn execute(
+    state: State<AppState>,
+    Json(Input { as_of }): Json<Input>,
+) -> Result<Json<Vec<Pattern>>> {
+    let as_of = as_of.unwrap_or_else(|| chrono::Local::now().date_naive());
+    Ok(Json::from(detect(&state.conn, as_of).await?))
+}
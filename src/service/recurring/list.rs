@@ -0,0 +1,12 @@
+use axum::extract::{Json, State};
+
+use crate::{service::Result, state::AppState};
+
+use super::model::RecurringTransaction;
+
+pub async fn execute(state: State<AppState>) -> Result<Json<Vec<RecurringTransaction>>> {
+    let recurring = sqlx::query_as("select * from recurring_transactions order by startDate, id")
+        .fetch_all(&state.conn)
+        .await?;
+    Ok(Json::from(recurring))
+}
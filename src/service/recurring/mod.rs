@@ -0,0 +1,28 @@
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
+
+use crate::state::AppState;
+
+mod convert;
+mod delete;
+pub mod detect;
+mod list;
+pub mod model;
+pub mod save;
+
+#[cfg(test)]
+mod test;
+
+pub fn router() -> Router<AppState> {
+    Router::new().nest(
+        "/api/recurring",
+        Router::new()
+            .route("/", get(list::execute))
+            .route("/", post(save::execute))
+            .route("/", delete(delete::execute))
+            .route("/detections", post(detect::execute))
+            .route("/detections/convert", post(convert::execute)),
+    )
+}
@@ -0,0 +1,63 @@
+use chrono::{Days, Months, NaiveDate};
+
+use crate::sqlx_ext::Json;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, sqlx::Type)]
+#[serde(rename_all = "camelCase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum Cadence {
+    Weekly,
+    Fortnightly,
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl Cadence {
+    /// The `n`th occurrence after `start`. Monthly and longer cadences keep to the day of the
+    /// month of `start`, or the last day of shorter months.
+    pub fn nth(self, start: NaiveDate, n: u32) -> Option<NaiveDate> {
+        match self {
+            Cadence::Weekly => start.checked_add_days(Days::new(7 * u64::from(n))),
+            Cadence::Fortnightly => start.checked_add_days(Days::new(14 * u64::from(n))),
+            Cadence::Monthly => start.checked_add_months(Months::new(n)),
+            Cadence::Quarterly => start.checked_add_months(Months::new(3 * n)),
+            Cadence::Yearly => start.checked_add_months(Months::new(12 * n)),
+        }
+    }
+
+    /// The occurrences from `start` that fall within `from..=to`.
+    pub fn occurrences(self, start: NaiveDate, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+        (0..)
+            .map_while(|n| self.nth(start, n))
+            .skip_while(|d| *d < from)
+            .take_while(|d| *d <= to)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+#[sqlx(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringTransaction {
+    /// Assigned on saving a new recurring transaction.
+    #[serde(default)]
+    pub id: Option<i64>,
+    pub description: String,
+    pub from_account: String,
+    pub to_account: String,
+    pub amount: i64,
+    pub cadence: Cadence,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub tags: Json<Vec<String>>,
+}
+
+impl RecurringTransaction {
+    /// The dates it occurs on within `from..=to`.
+    pub fn occurrences(&self, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+        let to = self.end_date.map_or(to, |end| end.min(to));
+        self.cadence.occurrences(self.start_date, from, to)
+    }
+}
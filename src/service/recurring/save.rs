@@ -0,0 +1,68 @@
+use std::borrow::Cow;
+
+use axum::extract::{Json, State};
+use sqlx::{Sqlite, Transaction};
+
+use crate::{
+    service::{Error, GenericUpdateResponse, Result},
+    state::AppState,
+};
+
+use super::model::RecurringTransaction;
+
+//language=sql
+const SQL: &str = r#"
+insert into recurring_transactions (id, description, fromAccount, toAccount, amount, cadence, startDate, endDate, tags)
+values (?1, ?2, trim(?3), trim(?4), ?5, ?6, ?7, ?8, ?9)
+on conflict (id) do update set
+    description = excluded.description,
+    fromAccount = excluded.fromAccount,
+    toAccount = excluded.toAccount,
+    amount = excluded.amount,
+    cadence = excluded.cadence,
+    startDate = excluded.startDate,
+    endDate = excluded.endDate,
+    tags = excluded.tags
+returning id
+"#;
+
+/// Saves the recurring transactions, returning their ids in the same order.
+pub async fn save(
+    tx: &mut Transaction<'_, Sqlite>,
+    recurring: Vec<RecurringTransaction>,
+) -> Result<Vec<i64>> {
+    let mut ids = Vec::with_capacity(recurring.len());
+    for r in recurring {
+        if r.end_date.is_some_and(|end| end < r.start_date) {
+            return Err(Error::InvalidArgument(Cow::Owned(format!(
+                "Recurring transaction {} ends before it starts",
+                r.description
+            ))));
+        }
+
+        let (id,): (i64,) = sqlx::query_as(SQL)
+            .bind(r.id)
+            .bind(r.description)
+            .bind(r.from_account)
+            .bind(r.to_account)
+            .bind(r.amount)
+            .bind(r.cadence)
+            .bind(r.start_date)
+            .bind(r.end_date)
+            .bind(r.tags)
+            .fetch_one(&mut **tx)
+            .await?;
+        ids.push(id);
+    }
+    Ok(ids)
+}
+
+pub async fn execute(
+    state: State<AppState>,
+    Json(recurring): Json<Vec<RecurringTransaction>>,
+) -> Result<Json<GenericUpdateResponse>> {
+    let mut tx = state.conn.begin().await?;
+    let num_affected = save(&mut tx, recurring).await?.len();
+    tx.commit().await?;
+    Ok(GenericUpdateResponse { num_affected }.into())
+}
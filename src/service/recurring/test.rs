@@ -0,0 +1,257 @@
+use axum::extract::{Json, State};
+use chrono::NaiveDate;
+
+use crate::service::{account::test::new_transaction, transaction};
+use crate::sqlx_ext;
+use crate::state::AppState;
+
+use super::detect::{detect, normalise, PriceIncrease};
+use super::model::{Cadence, RecurringTransaction};
+use super::*;
+
+fn date(s: &str) -> NaiveDate {
+    s.parse().expect("A date")
+}
+
+#[tokio::test]
+async fn recurring_works() {
+    let state = State(AppState::new_test().await);
+    let _ = router();
+
+    let rent = RecurringTransaction {
+        id: None,
+        description: "Rent".to_string(),
+        from_account: "Bank".to_string(),
+        to_account: "Expenses:Rent".to_string(),
+        amount: 300,
+        cadence: Cadence::Monthly,
+        start_date: date("2020-01-31"),
+        end_date: Some(date("2020-04-30")),
+        tags: sqlx_ext::Json(vec!["home".to_string()]),
+    };
+    let Json(saved) = save::execute(state.clone(), Json(vec![rent.clone()]))
+        .await
+        .expect("To save");
+    assert_eq!(saved.num_affected, 1);
+
+    let Json(listed) = list::execute(state.clone()).await.expect("To list");
+    let id = listed[0].id;
+    assert!(id.is_some());
+    assert_eq!(listed, vec![RecurringTransaction { id, ..rent.clone() }]);
+
+    // Months keep to the day they started on, or the last day of shorter months.
+    assert_eq!(
+        listed[0].occurrences(date("2020-02-01"), date("2020-12-31")),
+        vec![date("2020-02-29"), date("2020-03-31"), date("2020-04-30")]
+    );
+
+    let invalid = RecurringTransaction {
+        end_date: Some(date("2019-12-31")),
+        ..rent
+    };
+    assert!(save::execute(state.clone(), Json(vec![invalid]))
+        .await
+        .is_err());
+
+    let Json(deleted) = delete::execute(state.clone(), Json(vec![id.unwrap()]))
+        .await
+        .expect("To delete");
+    assert_eq!(deleted.num_affected, 1);
+}
+
+#[tokio::test]
+async fn detect_works() {
+    let state = State(AppState::new_test().await);
+
+    let transaction = |id: &str, description: &str, from, to, amount, date| {
+        let mut tx = new_transaction(id, from, to, amount, date);
+        tx.description = description.to_string();
+        tx
+    };
+    let _ = transaction::save::execute(
+        state.clone(),
+        Json(vec![
+            transaction(
+                "1",
+                "SALARY ACME 0128",
+                "Income",
+                "Bank",
+                5000,
+                "2020-01-28",
+            ),
+            transaction(
+                "2",
+                "SALARY ACME 0228",
+                "Income",
+                "Bank",
+                5100,
+                "2020-02-27",
+            ),
+            transaction(
+                "3",
+                "SALARY ACME 0328",
+                "Income",
+                "Bank",
+                5000,
+                "2020-03-30",
+            ),
+            transaction("4", "Coffee", "Bank", "Expenses:Food", 5, "2020-01-02"),
+            transaction("5", "Coffee", "Bank", "Expenses:Food", 5, "2020-01-05"),
+            transaction("6", "Coffee", "Bank", "Expenses:Food", 5, "2020-03-01"),
+            // A one-off amount isn't a change of price.
+            transaction("7", "Gym", "Bank", "Expenses:Gym", 20, "2020-02-01"),
+            transaction("8", "Gym", "Bank", "Expenses:Gym", 90, "2020-02-08"),
+            transaction("9", "Gym", "Bank", "Expenses:Gym", 20, "2020-02-15"),
+        ]),
+    )
+    .await
+    .expect("To save transactions");
+
+    assert_eq!(normalise("SALARY ACME 0128"), "salary acme");
+
+    let patterns = detect(&state.conn, date("2020-04-01"))
+        .await
+        .expect("To detect");
+    assert_eq!(patterns.len(), 1);
+    let salary = &patterns[0];
+    assert_eq!(salary.description, "SALARY ACME 0328");
+    assert_eq!(salary.cadence, Cadence::Monthly);
+    assert_eq!(salary.occurrences, 3);
+    assert_eq!(salary.average_amount, 5033);
+    assert_eq!(salary.last_seen, date("2020-03-30"));
+    assert_eq!(salary.next_expected, date("2020-04-30"));
+    assert_eq!(salary.price_increase, None);
+    assert!(salary.missed.is_empty());
+    assert!(salary.active);
+
+    // Only what happened up to the day counts.
+    assert!(detect(&state.conn, date("2020-03-01"))
+        .await
+        .expect("To detect")
+        .is_empty());
+}
+
+#[tokio::test]
+async fn subscriptions_work() {
+    let state = State(AppState::new_test().await);
+
+    let streaming = |id: &str, amount, date| {
+        let mut tx = new_transaction(id, "Credit card", "Expenses:Streaming", amount, date);
+        tx.description = format!("STREAMCO *{id}");
+        tx
+    };
+    let _ = transaction::save::execute(
+        state.clone(),
+        Json(vec![
+            streaming("1", 1000, "2020-01-05"),
+            streaming("2", 1000, "2020-02-05"),
+            streaming("3", 1100, "2020-04-06"),
+            streaming("4", 1100, "2020-05-05"),
+            new_transaction("5", "Bank", "Expenses:Gym", 2000, "2020-03-01"),
+            new_transaction("6", "Bank", "Expenses:Gym", 2000, "2020-04-01"),
+            new_transaction("7", "Bank", "Expenses:Gym", 2600, "2020-05-01"),
+            new_transaction("8", "Bank", "Expenses:Gym", 2600, "2020-06-01"),
+        ]),
+    )
+    .await
+    .expect("To save transactions");
+
+    let Json(patterns) = detect::execute(
+        state.clone(),
+        Json(detect::Input {
+            as_of: Some(date("2020-07-08")),
+        }),
+    )
+    .await
+    .expect("To detect");
+    assert_eq!(patterns.len(), 2);
+    // A rise bigger than the usual drift is still the same payment, at a new price.
+    let gym = &patterns[0];
+    assert_eq!(gym.to_account, "Expenses:Gym");
+    assert_eq!(gym.last_amount, 2600);
+    assert_eq!(
+        gym.price_increase,
+        Some(PriceIncrease {
+            date: date("2020-05-01"),
+            previous_amount: 2000,
+            amount: 2600,
+        })
+    );
+    assert!(gym.active);
+
+    let subscription = &patterns[1];
+    assert_eq!(subscription.cadence, Cadence::Monthly);
+    assert_eq!(subscription.average_amount, 1050);
+    assert_eq!(
+        subscription.price_increase,
+        Some(PriceIncrease {
+            date: date("2020-04-06"),
+            previous_amount: 1000,
+            amount: 1100,
+        })
+    );
+    // March never came, and June is overdue, but July is still within a few days.
+    assert_eq!(
+        subscription.missed,
+        vec![date("2020-03-05"), date("2020-06-05")]
+    );
+    assert!(!subscription.active);
+
+    let Json(recurring) = convert::execute(
+        state.clone(),
+        Json(convert::Input {
+            description: "streamco".to_string(),
+            from_account: "credit card".to_string(),
+            to_account: "Expenses:Streaming".to_string(),
+            as_of: Some(date("2020-05-10")),
+        }),
+    )
+    .await
+    .expect("To convert");
+    assert_eq!(
+        recurring,
+        RecurringTransaction {
+            id: recurring.id,
+            description: "STREAMCO *4".to_string(),
+            from_account: "Credit card".to_string(),
+            to_account: "Expenses:Streaming".to_string(),
+            amount: 1100,
+            cadence: Cadence::Monthly,
+            start_date: date("2020-06-05"),
+            end_date: None,
+            tags: Default::default(),
+        }
+    );
+    let Json(listed) = list::execute(state.clone()).await.expect("To list");
+    assert_eq!(listed, vec![recurring.clone()]);
+
+    // Converting it again gives back the same one.
+    let Json(again) = convert::execute(
+        state.clone(),
+        Json(convert::Input {
+            description: "STREAMCO".to_string(),
+            from_account: "Credit card".to_string(),
+            to_account: "expenses:streaming".to_string(),
+            as_of: Some(date("2020-05-10")),
+        }),
+    )
+    .await
+    .expect("To convert again");
+    assert_eq!(again, recurring);
+    let Json(listed) = list::execute(state.clone()).await.expect("To list");
+    assert_eq!(listed, vec![recurring]);
+
+    assert!(matches!(
+        convert::execute(
+            state.clone(),
+            Json(convert::Input {
+                description: "Something else".to_string(),
+                from_account: "Credit card".to_string(),
+                to_account: "Expenses:Streaming".to_string(),
+                as_of: None,
+            }),
+        )
+        .await,
+        Err(crate::service::Error::ResourceNotFound)
+    ));
+}
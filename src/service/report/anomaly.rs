@@ -0,0 +1,271 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use axum::extract::{Json, State};
+use chrono::{Datelike, Months, NaiveDate};
+
+use crate::{
+    service::{recurring::detect::normalise, Result},
+    sqlx_ext,
+    state::AppState,
+};
+
+/// How many months before each month its category total is compared with.
+const HISTORY_MONTHS: usize = 12;
+
+/// The smallest spread, as a fraction of the mean, that scores are measured against, so a value
+/// that never changed doesn't make the slightest change look extreme.
+const MIN_SPREAD: f64 = 0.1;
+
+#[derive(serde::Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Thresholds {
+    /// How many standard deviations from the mean a value has to be to stand out.
+    pub z_score: f64,
+    /// The smallest amount, in cents, a transaction with a new merchant stands out at.
+    pub new_merchant_amount: i64,
+    /// How many past values a score needs before it is trusted.
+    pub min_history: usize,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            z_score: 3.0,
+            new_merchant_amount: 10000,
+            min_history: 5,
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    #[serde(flatten)]
+    pub thresholds: Thresholds,
+    /// The accounts, with the ones under them, whose monthly totals are checked. Expense
+    /// accounts when left empty.
+    #[serde(default)]
+    pub categories: sqlx_ext::Json<Vec<String>>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, sqlx::Type, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[sqlx(rename_all = "camelCase")]
+pub enum Kind {
+    /// A transaction far outside the amounts its accounts usually see.
+    UnusualAmount,
+    /// A large transaction with a description never seen before.
+    NewMerchant,
+    /// A month a category spent far more than the months before.
+    CategorySpike,
+}
+
+#[derive(serde::Serialize, sqlx::FromRow, Clone, Debug, PartialEq)]
+#[sqlx(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct Anomaly {
+    pub kind: Kind,
+    /// What it is about: the transaction id, or the month and account of a category spike.
+    pub subject: String,
+    /// The day of the transaction, or the first day of the month.
+    pub date: NaiveDate,
+    pub description: String,
+    pub account: Option<String>,
+    pub amount: i64,
+    /// How many standard deviations it is from the mean, when it was measured.
+    pub score: Option<f64>,
+}
+
+/// Running statistics of the values seen so far.
+#[derive(Default, Clone, Copy)]
+struct Stats {
+    n: usize,
+    sum: f64,
+    sum_sq: f64,
+}
+
+impl Stats {
+    fn add(&mut self, value: f64) {
+        self.n += 1;
+        self.sum += value;
+        self.sum_sq += value * value;
+    }
+
+    /// The z-score of `value`, once at least `min_history` values have been seen.
+    fn z_score(&self, value: f64, min_history: usize) -> Option<f64> {
+        if self.n < min_history.max(1) {
+            return None;
+        }
+        let mean = self.sum / self.n as f64;
+        let std_dev = (self.sum_sq / self.n as f64 - mean * mean).max(0.0).sqrt();
+        let spread = std_dev.max(mean.abs() * MIN_SPREAD);
+        (spread > 0.0).then(|| (value - mean) / spread)
+    }
+}
+
+//language=sql
+const TRANSACTIONS_SQL: &str = r#"
+select id, description, trim(fromAccount), trim(toAccount), amount, transDate
+from transactions
+where transDate <= ?
+order by transDate, updatedDate
+"#;
+
+//language=sql
+const MONTHLY_SQL: &str = r#"
+select min(ds.account), strftime('%Y-%m-01', ds.transDate) as month, sum(ds.total)
+from daily_sum ds
+where ds.transDate <= ?2
+  and iif(
+    ifnull(json_array_length(?1), 0) > 0,
+    ds.account collate nocase in (
+        select aa.account
+        from json_each(?1) j
+        inner join account_ancestors aa on aa.ancestor = trim(j.value) collate nocase
+    ),
+    ds.account collate nocase in (select account from account_types where type = 'expense')
+  )
+group by lower(ds.account), month
+order by month
+"#;
+
+/// Finds the transactions and months from `from` to `to` that stand out from the history
+/// before them.
+pub async fn find(
+    conn: &sqlx::SqlitePool,
+    from: NaiveDate,
+    to: NaiveDate,
+    thresholds: Thresholds,
+    categories: &sqlx_ext::Json<Vec<String>>,
+) -> Result<Vec<Anomaly>> {
+    let Thresholds {
+        z_score,
+        new_merchant_amount,
+        min_history,
+    } = thresholds;
+    let mut anomalies = Vec::new();
+
+    let transactions: Vec<(String, String, String, String, i64, NaiveDate)> =
+        sqlx::query_as(TRANSACTIONS_SQL)
+            .bind(to)
+            .fetch_all(conn)
+            .await?;
+    // Money in and money out of an account are measured apart, so salary coming in doesn't make
+    // large payments out look usual.
+    let mut stats: HashMap<(String, bool), Stats> = HashMap::new();
+    let mut merchants: HashSet<String> = HashSet::new();
+    for (id, description, from_account, to_account, amount, date) in transactions {
+        let merchant = normalise(&description);
+        let accounts = [(&from_account, false), (&to_account, true)];
+        if date >= from {
+            let unusual = accounts
+                .iter()
+                .copied()
+                .filter_map(|(account, inflow)| {
+                    let stats = stats.get(&(account.to_lowercase(), inflow))?;
+                    Some((account, stats.z_score(amount as f64, min_history)?))
+                })
+                .filter(|(_, z)| z.abs() > z_score)
+                .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()));
+            if let Some((account, z)) = unusual {
+                anomalies.push(Anomaly {
+                    kind: Kind::UnusualAmount,
+                    subject: id.clone(),
+                    date,
+                    description: description.clone(),
+                    account: Some(account.to_string()),
+                    amount,
+                    score: Some(z),
+                });
+            }
+
+            if amount.abs() >= new_merchant_amount && !merchants.contains(&merchant) {
+                anomalies.push(Anomaly {
+                    kind: Kind::NewMerchant,
+                    subject: id.clone(),
+                    date,
+                    description: description.clone(),
+                    account: Some(to_account.clone()),
+                    amount,
+                    score: None,
+                });
+            }
+        }
+
+        for (account, inflow) in accounts {
+            stats
+                .entry((account.to_lowercase(), inflow))
+                .or_default()
+                .add(amount as f64);
+        }
+        merchants.insert(merchant);
+    }
+
+    let monthly: Vec<(String, NaiveDate, i64)> = sqlx::query_as(MONTHLY_SQL)
+        .bind(categories)
+        .bind(to)
+        .fetch_all(conn)
+        .await?;
+    let mut by_account: BTreeMap<String, (String, BTreeMap<NaiveDate, i64>)> = BTreeMap::new();
+    for (account, month, total) in monthly {
+        by_account
+            .entry(account.to_lowercase())
+            .or_insert_with(|| (account, BTreeMap::new()))
+            .1
+            .insert(month, total);
+    }
+    let first_month = from.with_day(1).unwrap_or(from);
+    for (account, totals) in by_account.values() {
+        let Some(start) = totals.keys().next().copied() else {
+            continue;
+        };
+        // Months without spending count as zero.
+        let months: Vec<(NaiveDate, i64)> = (0..)
+            .map_while(|n| start.checked_add_months(Months::new(n)))
+            .take_while(|month| *month <= to)
+            .map(|month| (month, totals.get(&month).copied().unwrap_or_default()))
+            .collect();
+        for (i, (month, total)) in months.iter().enumerate() {
+            if *month < first_month {
+                continue;
+            }
+            let mut history = Stats::default();
+            for (_, past) in &months[i.saturating_sub(HISTORY_MONTHS)..i] {
+                history.add(*past as f64);
+            }
+            match history.z_score(*total as f64, min_history) {
+                Some(z) if z > z_score => anomalies.push(Anomaly {
+                    kind: Kind::CategorySpike,
+                    subject: format!("{} {account}", month.format("%Y-%m")),
+                    date: *month,
+                    description: format!("{account} in {}", month.format("%Y-%m")),
+                    account: Some(account.clone()),
+                    amount: *total,
+                    score: Some(z),
+                }),
+                _ => {}
+            }
+        }
+    }
+
+    anomalies.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.subject.cmp(&b.subject)));
+    Ok(anomalies)
+}
+
+/// Flags amounts far outside an account's history, large first-time merchants and categories
+/// whose monthly total jumps.
+pub async fn execute(
+    state: State<AppState>,
+    Json(Input {
+        from,
+        to,
+        thresholds,
+        categories,
+    }): Json<Input>,
+) -> Result<Json<Vec<Anomaly>>> {
+    Ok(Json::from(
+        find(&state.conn, from, to, thresholds, &categories).await?,
+    ))
+}
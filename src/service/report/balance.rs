@@ -1,58 +1,179 @@
-use crate::{service::Result, sqlx_ext::Json, state::AppState};
+use std::collections::HashMap;
+
+use crate::{
+    service::{account_group::input_accounts_sql, Result},
+    sqlx_ext::Json,
+    state::AppState,
+};
 use axum::extract;
 use chrono::NaiveDate;
+use itertools::Itertools;
+
+use super::{sum::SeriesBy, Bucket, Bucketing, Frequency};
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct Input {
+    /// The first date to report, or the first date with transactions when left out.
     pub from: Option<NaiveDate>,
+    /// The last date to report, or the last date with transactions when left out.
     pub to: Option<NaiveDate>,
+    #[serde(default)]
+    pub freq: Frequency,
+    #[serde(default)]
     pub accounts: Json<Vec<String>>,
+    #[serde(default)]
+    pub account_groups: Json<Vec<String>>,
+    #[serde(default)]
+    pub series_by: SeriesBy,
 }
 
-#[derive(sqlx::FromRow, serde::Serialize)]
-#[sqlx(rename_all = "camelCase")]
+#[derive(serde::Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
 pub struct DataRow {
+    /// The balance at the end of `date`.
     pub balance: i64,
+    /// The last day of the bucket, or `to` for a bucket cut short.
     pub date: NaiveDate,
+    /// The bucket, cut short to fit `from` and `to`.
+    #[serde(flatten)]
+    pub bucket: Bucket,
+    /// The account or group name, when split by [`SeriesBy::Selection`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series: Option<String>,
 }
 
-const SQL: &'static str = r#"
-with recursive 
-    input_accounts(name) as (select trim(value) from json_each(?3)),
-    daily(date, total, i) as (
-        select transDate, sum(ds.total), row_number() over (order by transDate)
-        from daily_sum ds
-        inner join input_accounts ia on ia.name = ds.account collate nocase
-        where ?1 is null or transDate >= ?1 and ?2 is null or transDate <= ?2
-        group by transDate
-        order by transDate
-    ),
-    balance(balanceDate, i, balance) as (
-        select daily.date, 1, coalesce(sum(at.amount), 0)
-        from account_transactions at
-        inner join daily on daily.i = 1
-        inner join input_accounts ia on ia.name = at.account collate nocase
-        where transDate <= daily.date
-
-        union all
-
-        select daily.date, balance.i + 1, balance.balance + daily.total
-        from balance
-        inner join daily on daily.i = balance.i + 1
-    )
-select balanceDate date, balance
-from balance
+//language=sql
+const RANGE_SQL: &str = r#"
+select min(ds.transDate), max(ds.transDate)
+from daily_sum ds
+where ds.account collate nocase in (select account from input_accounts)
+"#;
+
+// Opening balances count from the day the account was opened, or from the start without one.
+//language=sql
+pub(super) const OPENING_SQL: &str = r#"
+select ia.series, cast(round(sum(b.total * ia.weight)) as integer)
+from (
+    select name as account, openingBalance as total
+    from accounts_meta
+    where openingBalance is not null
+      and (openedDate is null or openedDate < ?4)
+
+    union all
+
+    select account, total
+    from daily_sum
+    where transDate < ?4
+) b
+inner join input_accounts ia on ia.account = b.account collate nocase
+group by ia.series collate nocase
 "#;
 
+//language=sql
+const CHANGES_SQL: &str = r#"
+select ia.series, b.transDate, cast(round(sum(b.total * ia.weight)) as integer)
+from (
+    select account, transDate, total
+    from daily_sum
+
+    union all
+
+    select name, openedDate, openingBalance
+    from accounts_meta
+    where openingBalance is not null
+      and openedDate is not null
+) b
+inner join input_accounts ia on ia.account = b.account collate nocase
+where b.transDate >= ?4
+  and b.transDate <= ?5
+group by ia.series collate nocase, b.transDate
+order by b.transDate
+"#;
+
+/// The balances at the end of every bucket from `from` to `to`, carrying them over buckets
+/// without transactions.
 pub async fn execute(
     state: extract::State<AppState>,
-    extract::Json(Input { from, to, accounts }): extract::Json<Input>,
+    extract::Json(Input {
+        from,
+        to,
+        freq,
+        accounts,
+        account_groups,
+        series_by,
+    }): extract::Json<Input>,
 ) -> Result<extract::Json<Vec<DataRow>>> {
-    Ok(sqlx::query_as(SQL)
+    let split = series_by == SeriesBy::Selection;
+    let with_input_accounts = |sql: &str| {
+        format!(
+            "with input_accounts as ({}) {sql}",
+            input_accounts_sql(1, 2, "?3")
+        )
+    };
+
+    let (first, last): (Option<NaiveDate>, Option<NaiveDate>) =
+        sqlx::query_as(&with_input_accounts(RANGE_SQL))
+            .bind(&accounts)
+            .bind(&account_groups)
+            .bind(split)
+            .fetch_one(&state.conn)
+            .await?;
+    let (Some(from), Some(to)) = (from.or(first), to.or(last)) else {
+        return Ok(Vec::new().into());
+    };
+    let buckets = Bucketing::new(&state.conn, freq).await?.buckets(from, to)?;
+
+    let opening: Vec<(Option<String>, i64)> = sqlx::query_as(&with_input_accounts(OPENING_SQL))
+        .bind(&accounts)
+        .bind(&account_groups)
+        .bind(split)
         .bind(from)
-        .bind(to)
-        .bind(accounts)
         .fetch_all(&state.conn)
-        .await?
-        .into())
+        .await?;
+    let changes: Vec<(Option<String>, NaiveDate, i64)> =
+        sqlx::query_as(&with_input_accounts(CHANGES_SQL))
+            .bind(&accounts)
+            .bind(&account_groups)
+            .bind(split)
+            .bind(from)
+            .bind(to)
+            .fetch_all(&state.conn)
+            .await?;
+
+    // Every series requested gets a balance, even one without any transactions.
+    let series: Vec<Option<String>> = if split {
+        accounts
+            .iter()
+            .chain(account_groups.iter())
+            .map(|name| name.trim().to_string())
+            .unique_by(|name| name.to_lowercase())
+            .map(Some)
+            .collect()
+    } else {
+        vec![None]
+    };
+    let key = |series: &Option<String>| series.as_deref().map(str::to_lowercase);
+
+    let mut balances: HashMap<Option<String>, i64> = opening
+        .into_iter()
+        .map(|(series, balance)| (key(&series), balance))
+        .collect();
+    let mut changes = changes.into_iter().peekable();
+    let mut output = Vec::new();
+    for bucket in buckets {
+        while let Some((series, _, total)) = changes.next_if(|(_, date, _)| *date <= bucket.end) {
+            *balances.entry(key(&series)).or_default() += total;
+        }
+        for name in &series {
+            output.push(DataRow {
+                balance: balances.get(&key(name)).copied().unwrap_or_default(),
+                date: bucket.end,
+                bucket: bucket.clone(),
+                series: name.clone(),
+            });
+        }
+    }
+
+    Ok(output.into())
 }
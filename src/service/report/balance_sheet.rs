@@ -0,0 +1,96 @@
+use axum::extract::{self, Query};
+use chrono::NaiveDate;
+
+use crate::{
+    service::{config::client::account_separator, Result},
+    state::AppState,
+};
+
+use super::statement::{build_lines, totals, Line};
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    /// The date to report the balances at the end of, or the latest balances when left out.
+    pub as_of: Option<NaiveDate>,
+}
+
+#[derive(serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceSheet {
+    pub as_of: Option<NaiveDate>,
+    pub assets: Vec<Line>,
+    /// Liability accounts, with the amounts owed as positive amounts.
+    pub liabilities: Vec<Line>,
+    /// Equity accounts, with their amounts as positive amounts.
+    pub equity: Vec<Line>,
+    pub total_assets: i64,
+    pub total_liabilities: i64,
+    pub total_equity: i64,
+    /// The net income of the income and expense accounts up to `as_of`.
+    pub retained_earnings: i64,
+    /// Assets less liabilities.
+    pub net_worth: i64,
+}
+
+//language=sql
+const SQL: &str = r#"
+select min(b.account), t.type, sum(b.total)
+from (
+    select name as account, openingBalance as total
+    from accounts_meta
+    where openingBalance is not null
+      and (?1 is null or openedDate is null or openedDate <= ?1)
+
+    union all
+
+    select account, total
+    from daily_sum
+    where ?1 is null or transDate <= ?1
+) b
+inner join account_types t on t.account = b.account collate nocase
+group by b.account collate nocase
+"#;
+
+pub async fn execute(
+    state: extract::State<AppState>,
+    Query(Input { as_of }): Query<Input>,
+) -> Result<extract::Json<BalanceSheet>> {
+    let rows: Vec<(String, String, i64)> = sqlx::query_as(SQL)
+        .bind(as_of)
+        .fetch_all(&state.conn)
+        .await?;
+
+    let of_type = |account_type: &str, sign: i64| {
+        rows.iter()
+            .filter(|(_, t, _)| t == account_type)
+            .map(|(account, _, total)| (account.clone(), vec![total * sign]))
+            .collect::<Vec<_>>()
+    };
+
+    let separator = account_separator(&state.conn).await?;
+    let assets = build_lines(of_type("asset", 1), &separator, 1);
+    let liabilities = build_lines(of_type("liability", -1), &separator, 1);
+    let equity = build_lines(of_type("equity", -1), &separator, 1);
+    let total_assets = totals(&assets, 1)[0];
+    let total_liabilities = totals(&liabilities, 1)[0];
+    let total_equity = totals(&equity, 1)[0];
+    let retained_earnings = rows
+        .iter()
+        .filter(|(_, t, _)| t == "income" || t == "expense")
+        .map(|(_, _, total)| -total)
+        .sum();
+
+    Ok(BalanceSheet {
+        as_of,
+        assets,
+        liabilities,
+        equity,
+        total_assets,
+        total_liabilities,
+        total_equity,
+        retained_earnings,
+        net_worth: total_assets - total_liabilities,
+    }
+    .into())
+}
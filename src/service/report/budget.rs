@@ -0,0 +1,123 @@
+use axum::extract;
+use chrono::NaiveDate;
+
+use crate::{
+    service::{account_group::input_accounts_sql, budget::model::Budget, Result},
+    sqlx_ext::Json,
+    state::AppState,
+};
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// The budgets to report on, or all of them when left out.
+    #[serde(default)]
+    pub budgets: Option<Vec<i64>>,
+}
+
+#[derive(serde::Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Line {
+    pub budget_id: i64,
+    pub name: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    /// The unspent amount brought forward from the previous period, for budgets with rollover.
+    pub carried_over: i64,
+    /// The budget's amount plus `carried_over`.
+    pub budgeted: i64,
+    pub actual: i64,
+    /// What is left of `budgeted`, which is negative once overspent.
+    pub remaining: i64,
+    pub overspent: i64,
+    /// `actual` as a percentage of `budgeted`, or `None` when nothing is budgeted.
+    pub percent_used: Option<f64>,
+}
+
+//language=sql
+const SQL: &str = r#"
+select ds.transDate, cast(round(sum(ds.total * ia.weight)) as integer)
+from daily_sum ds
+inner join input_accounts ia on ia.account = ds.account collate nocase
+where ds.transDate >= ?3 and ds.transDate <= ?4
+group by ds.transDate
+order by ds.transDate
+"#;
+
+/// Compares each period of `budget` that overlaps `from..=to` with the actual spending.
+async fn lines(
+    state: &AppState,
+    budget: Budget,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<Line>> {
+    let periods = budget.periods(to);
+    let (Some(first), Some(last)) = (periods.first(), periods.last()) else {
+        return Ok(vec![]);
+    };
+
+    // Rollover carries over from the start of the budget, so the totals start there too.
+    let sql = format!(
+        "with input_accounts as ({}) {SQL}",
+        input_accounts_sql(1, 2, "false")
+    );
+    let daily: Vec<(NaiveDate, i64)> = sqlx::query_as(&sql)
+        .bind(Json(budget.account.iter().collect::<Vec<_>>()))
+        .bind(Json(budget.account_group.iter().collect::<Vec<_>>()))
+        .bind(first.0)
+        .bind(last.1)
+        .fetch_all(&state.conn)
+        .await?;
+
+    let mut carried_over = 0;
+    let mut lines = Vec::new();
+    for (start, end) in periods {
+        let actual: i64 = daily
+            .iter()
+            .filter(|(date, _)| (start..=end).contains(date))
+            .map(|(_, total)| total)
+            .sum();
+        let budgeted = budget.amount + carried_over;
+        let remaining = budgeted - actual;
+        carried_over = if budget.rollover { remaining.max(0) } else { 0 };
+
+        if end < from {
+            continue;
+        }
+        lines.push(Line {
+            budget_id: budget.id.unwrap_or_default(),
+            name: budget.name.clone(),
+            period_start: start,
+            period_end: end,
+            carried_over: budgeted - budget.amount,
+            budgeted,
+            actual,
+            remaining,
+            overspent: (-remaining).max(0),
+            percent_used: (budgeted != 0).then(|| actual as f64 * 100.0 / budgeted as f64),
+        });
+    }
+    Ok(lines)
+}
+
+pub async fn execute(
+    state: extract::State<AppState>,
+    extract::Json(Input { from, to, budgets }): extract::Json<Input>,
+) -> Result<extract::Json<Vec<Line>>> {
+    let budgets: Vec<Budget> = sqlx::query_as(
+        "select * from budgets \
+         where ?1 is null or id in (select value from json_each(?1)) \
+         order by name collate nocase, id",
+    )
+    .bind(budgets.map(Json))
+    .fetch_all(&state.conn)
+    .await?;
+
+    let mut output = Vec::new();
+    for budget in budgets {
+        output.extend(lines(&state, budget, from, to).await?);
+    }
+    Ok(output.into())
+}
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+
+use axum::extract;
+use chrono::NaiveDate;
+
+use crate::{
+    service::{config::client::account_separator, Result},
+    sqlx_ext::Json,
+    state::AppState,
+};
+
+#[derive(serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum FlowBy {
+    #[default]
+    Account,
+    /// Accounts in an account group count as the group, or as the group of the most specific
+    /// account when in several. Accounts outside any group keep their own names.
+    Group,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    #[serde(default)]
+    pub by: FlowBy,
+    /// Cuts account names down to this many levels, e.g. 1 turns "Expenses:Food" into "Expenses".
+    pub depth: Option<usize>,
+    /// Only the transactions with any of these tags, or the tags under them.
+    #[serde(default)]
+    pub tags: Json<Vec<String>>,
+    /// Leaves out the edges moving less than this.
+    #[serde(default)]
+    pub min_amount: i64,
+}
+
+#[derive(serde::Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Node {
+    pub name: String,
+    pub inflow: i64,
+    pub outflow: i64,
+}
+
+#[derive(serde::Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Edge {
+    pub source: String,
+    pub target: String,
+    pub amount: i64,
+    pub num_transactions: i64,
+}
+
+#[derive(serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Flow {
+    pub nodes: Vec<Node>,
+    /// Money moving both ways between two nodes is netted into one edge.
+    pub edges: Vec<Edge>,
+}
+
+//language=sql
+const SQL: &str = r#"
+with flows as (
+    select trim(t.fromAccount) as source, trim(t.toAccount) as target, t.amount
+    from transactions t
+    where (?1 is null or t.transDate >= ?1)
+      and (?2 is null or t.transDate <= ?2)
+      and (json_array_length(?3) = 0 or exists (
+          select 1 from transaction_tags tt
+          where tt.transactionId = t.id
+            and tt.tag in (
+                select tag from tag_ancestors
+                where ancestor collate nocase in (select trim(value) from json_each(?3))
+            )
+      ))
+)
+select {SOURCE}, {TARGET}, sum(amount), count(*)
+from flows f
+group by 1 collate nocase, 2 collate nocase
+"#;
+
+/// The group an account counts as for [`FlowBy::Group`], or the account itself.
+fn group_sql(account: &str) -> String {
+    format!(
+        "coalesce((select ga.groupName from account_group_accounts ga \
+                   inner join account_ancestors aa on aa.ancestor = ga.accountName collate nocase \
+                   where aa.account = {account} collate nocase \
+                   order by length(ga.accountName) desc, ga.groupName limit 1), {account})"
+    )
+}
+
+fn truncate(name: String, separator: &str, depth: Option<usize>) -> String {
+    match depth {
+        Some(depth) if depth > 0 && !separator.is_empty() => name
+            .split(separator)
+            .take(depth)
+            .collect::<Vec<_>>()
+            .join(separator),
+        _ => name,
+    }
+}
+
+pub async fn execute(
+    state: extract::State<AppState>,
+    extract::Json(Input {
+        from,
+        to,
+        by,
+        depth,
+        tags,
+        min_amount,
+    }): extract::Json<Input>,
+) -> Result<extract::Json<Flow>> {
+    let sql = match by {
+        FlowBy::Account => SQL
+            .replace("{SOURCE}", "f.source")
+            .replace("{TARGET}", "f.target"),
+        FlowBy::Group => SQL
+            .replace("{SOURCE}", &group_sql("f.source"))
+            .replace("{TARGET}", &group_sql("f.target")),
+    };
+    let rows: Vec<(String, String, i64, i64)> = sqlx::query_as(&sql)
+        .bind(from)
+        .bind(to)
+        .bind(tags)
+        .fetch_all(&state.conn)
+        .await?;
+
+    // Net the flows by node pair, keyed in lower case as the names are case insensitive.
+    let separator = account_separator(&state.conn).await?;
+    let mut names: BTreeMap<String, String> = Default::default();
+    let mut pairs: BTreeMap<(String, String), (i64, i64)> = Default::default();
+    for (source, target, amount, num_transactions) in rows {
+        let mut key = |name: String| {
+            let name = truncate(name, &separator, depth);
+            let key = name.to_lowercase();
+            names.entry(key.clone()).or_insert(name);
+            key
+        };
+        let (source, target) = (key(source), key(target));
+        if source == target {
+            continue;
+        }
+
+        let (pair, amount) = if source < target {
+            ((source, target), amount)
+        } else {
+            ((target, source), -amount)
+        };
+        let entry = pairs.entry(pair).or_default();
+        entry.0 += amount;
+        entry.1 += num_transactions;
+    }
+
+    let mut nodes: BTreeMap<String, Node> = Default::default();
+    let mut edges = Vec::new();
+    for ((a, b), (amount, num_transactions)) in pairs {
+        let (source, target) = if amount >= 0 { (a, b) } else { (b, a) };
+        let amount = amount.abs();
+        if amount == 0 || amount < min_amount {
+            continue;
+        }
+
+        for (key, inflow, outflow) in [(&source, 0, amount), (&target, amount, 0)] {
+            let node = nodes.entry(key.clone()).or_insert_with(|| Node {
+                name: names[key].clone(),
+                inflow: 0,
+                outflow: 0,
+            });
+            node.inflow += inflow;
+            node.outflow += outflow;
+        }
+        edges.push(Edge {
+            source: names[&source].clone(),
+            target: names[&target].clone(),
+            amount,
+            num_transactions,
+        });
+    }
+    edges.sort_by_key(|e| std::cmp::Reverse(e.amount));
+
+    Ok(Flow {
+        nodes: nodes.into_values().collect(),
+        edges,
+    }
+    .into())
+}
@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+
+use axum::extract;
+use chrono::{Days, Months, NaiveDate};
+use itertools::Itertools;
+
+use crate::{
+    service::{
+        account_group::input_accounts_sql,
+        recurring::{
+            detect::{detect, payment_key, PaymentKey},
+            model::RecurringTransaction,
+        },
+        Error, Result,
+    },
+    sqlx_ext::Json,
+    state::AppState,
+};
+
+use super::{balance, balance::DataRow, sum::SeriesBy, Bucketing, Frequency};
+
+/// How many days apart a projected occurrence and a scheduled transaction may be to count as the
+/// same payment.
+const SAME_PAYMENT_DAYS: i64 = 3;
+
+/// The furthest ahead, in months, a forecast may project.
+const MAX_MONTHS: u32 = 120;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    /// The day to project from, today when left out.
+    pub as_of: Option<NaiveDate>,
+    #[serde(default = "default_months")]
+    pub months: u32,
+    #[serde(default)]
+    pub freq: Frequency,
+    #[serde(default)]
+    pub accounts: Json<Vec<String>>,
+    #[serde(default)]
+    pub account_groups: Json<Vec<String>>,
+    #[serde(default)]
+    pub series_by: SeriesBy,
+    /// Whether to project the regular patterns found in past transactions too.
+    #[serde(default = "default_include_detected")]
+    pub include_detected: bool,
+}
+
+const fn default_months() -> u32 {
+    3
+}
+
+const fn default_include_detected() -> bool {
+    true
+}
+
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum Source {
+    /// A transaction already entered with a future date.
+    Scheduled {
+        transaction_id: String,
+    },
+    Recurring {
+        recurring_id: i64,
+    },
+    /// A regular pattern found in past transactions.
+    Detected,
+}
+
+/// How much an item changes the balance of a series.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Impact {
+    pub series: Option<String>,
+    pub amount: i64,
+}
+
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Item {
+    pub date: NaiveDate,
+    pub description: String,
+    pub from_account: String,
+    pub to_account: String,
+    pub amount: i64,
+    pub source: Source,
+    pub impacts: Vec<Impact>,
+}
+
+/// The lowest balance a series is projected to reach, and the first day it does.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Lowest {
+    pub series: Option<String>,
+    pub balance: i64,
+    pub date: NaiveDate,
+}
+
+#[derive(serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Forecast {
+    pub balances: Vec<DataRow>,
+    pub items: Vec<Item>,
+    pub lowest: Vec<Lowest>,
+}
+
+//language=sql
+const WEIGHTS_SQL: &str = "select series, account, weight from input_accounts";
+
+//language=sql
+const SCHEDULED_SQL: &str = r#"
+select id, description, trim(fromAccount), trim(toAccount), amount, transDate
+from transactions
+where transDate > ?1 and transDate <= ?2
+order by transDate, updatedDate
+"#;
+
+/// Projects the balances from `as_of` to `months` later, applying the transactions dated after
+/// `as_of`, the recurring transactions and, optionally, the detected regular patterns.
+pub async fn execute(
+    state: extract::State<AppState>,
+    extract::Json(Input {
+        as_of,
+        months,
+        freq,
+        accounts,
+        account_groups,
+        series_by,
+        include_detected,
+    }): extract::Json<Input>,
+) -> Result<extract::Json<Forecast>> {
+    let as_of = as_of.unwrap_or_else(|| chrono::Local::now().date_naive());
+    if months > MAX_MONTHS {
+        return Err(Error::InvalidArgument(
+            format!("A forecast can't go further than {MAX_MONTHS} months").into(),
+        ));
+    }
+    let (Some(first), Some(to)) = (
+        as_of.checked_add_days(Days::new(1)),
+        as_of.checked_add_months(Months::new(months)),
+    ) else {
+        return Err(Error::InvalidArgument(
+            "Forecast period out of range".into(),
+        ));
+    };
+    let buckets = Bucketing::new(&state.conn, freq)
+        .await?
+        .buckets(as_of, to)?;
+
+    let split = series_by == SeriesBy::Selection;
+    let with_input_accounts = |sql: &str| {
+        format!(
+            "with input_accounts as ({}) {sql}",
+            input_accounts_sql(1, 2, "?3")
+        )
+    };
+    let key = |series: &Option<String>| series.as_deref().map(str::to_lowercase);
+
+    let opening: Vec<(Option<String>, i64)> =
+        sqlx::query_as(&with_input_accounts(balance::OPENING_SQL))
+            .bind(&accounts)
+            .bind(&account_groups)
+            .bind(split)
+            .bind(first)
+            .fetch_all(&state.conn)
+            .await?;
+    let weights: Vec<(Option<String>, String, f64)> =
+        sqlx::query_as(&with_input_accounts(WEIGHTS_SQL))
+            .bind(&accounts)
+            .bind(&account_groups)
+            .bind(split)
+            .fetch_all(&state.conn)
+            .await?;
+    let mut weights_by_account: HashMap<String, Vec<(Option<String>, f64)>> = HashMap::new();
+    for (series, account, weight) in weights {
+        weights_by_account
+            .entry(account.to_lowercase())
+            .or_default()
+            .push((series, weight));
+    }
+
+    // Every series requested gets a balance, even one without any transactions.
+    let series: Vec<Option<String>> = if split {
+        accounts
+            .iter()
+            .chain(account_groups.iter())
+            .map(|name| name.trim().to_string())
+            .unique_by(|name| name.to_lowercase())
+            .map(Some)
+            .collect()
+    } else {
+        vec![None]
+    };
+
+    let scheduled: Vec<(String, String, String, String, i64, NaiveDate)> =
+        sqlx::query_as(SCHEDULED_SQL)
+            .bind(as_of)
+            .bind(to)
+            .fetch_all(&state.conn)
+            .await?;
+    let recurring: Vec<RecurringTransaction> =
+        sqlx::query_as("select * from recurring_transactions")
+            .fetch_all(&state.conn)
+            .await?;
+
+    // A scheduled transaction already accounts for a projected occurrence of the same payment
+    let is_scheduled = |key: &PaymentKey, date: NaiveDate| {
+        scheduled.iter().any(|(_, description, from, to, _, d)| {
+            (*d - date).num_days().abs() <= SAME_PAYMENT_DAYS
+                && payment_key(description, from, to) == *key
+        })
+    };
+    let mut items: Vec<(NaiveDate, String, String, String, i64, Source)> = Vec::new();
+    for (id, description, from, to_account, amount, date) in &scheduled {
+        items.push((
+            *date,
+            description.clone(),
+            from.clone(),
+            to_account.clone(),
+            *amount,
+            Source::Scheduled {
+                transaction_id: id.clone(),
+            },
+        ));
+    }
+    for r in &recurring {
+        let key = payment_key(&r.description, &r.from_account, &r.to_account);
+        for date in r.occurrences(first, to) {
+            if is_scheduled(&key, date) {
+                continue;
+            }
+            items.push((
+                date,
+                r.description.clone(),
+                r.from_account.trim().to_string(),
+                r.to_account.trim().to_string(),
+                r.amount,
+                Source::Recurring {
+                    recurring_id: r.id.unwrap_or_default(),
+                },
+            ));
+        }
+    }
+
+    if include_detected {
+        // Patterns already covered by a recurring transaction would count the same payments twice
+        let recurring_keys: Vec<_> = recurring
+            .iter()
+            .map(|r| payment_key(&r.description, &r.from_account, &r.to_account))
+            .collect();
+        for pattern in detect(&state.conn, as_of).await? {
+            let pattern_key = payment_key(
+                &pattern.description,
+                &pattern.from_account,
+                &pattern.to_account,
+            );
+            if !pattern.active || recurring_keys.contains(&pattern_key) {
+                continue;
+            }
+
+            for date in pattern.cadence.occurrences(pattern.last_seen, first, to) {
+                if !is_scheduled(&pattern_key, date) {
+                    items.push((
+                        date,
+                        pattern.description.clone(),
+                        pattern.from_account.clone(),
+                        pattern.to_account.clone(),
+                        pattern.last_amount,
+                        Source::Detected,
+                    ));
+                }
+            }
+        }
+    }
+    items.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+    // Keep the items moving money in or out of the series, with how much they move.
+    let items: Vec<Item> = items
+        .into_iter()
+        .filter_map(
+            |(date, description, from_account, to_account, amount, source)| {
+                let mut impacts: Vec<(Option<String>, f64)> = Vec::new();
+                for (account, sign) in [(&from_account, -1.0), (&to_account, 1.0)] {
+                    for (series, weight) in weights_by_account
+                        .get(&account.to_lowercase())
+                        .into_iter()
+                        .flatten()
+                    {
+                        match impacts.iter_mut().find(|(s, _)| key(s) == key(series)) {
+                            Some((_, total)) => *total += sign * weight,
+                            None => impacts.push((series.clone(), sign * weight)),
+                        }
+                    }
+                }
+                let impacts: Vec<Impact> = impacts
+                    .into_iter()
+                    .map(|(series, factor)| Impact {
+                        series,
+                        amount: (amount as f64 * factor).round() as i64,
+                    })
+                    .filter(|i| i.amount != 0)
+                    .collect();
+                (!impacts.is_empty()).then_some(Item {
+                    date,
+                    description,
+                    from_account,
+                    to_account,
+                    amount,
+                    source,
+                    impacts,
+                })
+            },
+        )
+        .collect();
+
+    let mut balances: HashMap<Option<String>, i64> = opening
+        .into_iter()
+        .map(|(series, balance)| (key(&series), balance))
+        .collect();
+    let mut lowest: Vec<Lowest> = series
+        .iter()
+        .map(|name| Lowest {
+            series: name.clone(),
+            balance: balances.get(&key(name)).copied().unwrap_or_default(),
+            date: as_of,
+        })
+        .collect();
+
+    let mut pending = items.iter().peekable();
+    let mut output = Vec::new();
+    for bucket in buckets {
+        while let Some(item) = pending.next_if(|item| item.date <= bucket.end) {
+            for impact in &item.impacts {
+                *balances.entry(key(&impact.series)).or_default() += impact.amount;
+            }
+            // Items on the same day all land before the day's balance is known.
+            if pending.peek().is_some_and(|next| next.date == item.date) {
+                continue;
+            }
+            for low in lowest.iter_mut() {
+                let balance = balances.get(&key(&low.series)).copied().unwrap_or_default();
+                if balance < low.balance {
+                    low.balance = balance;
+                    low.date = item.date;
+                }
+            }
+        }
+        for name in &series {
+            output.push(DataRow {
+                balance: balances.get(&key(name)).copied().unwrap_or_default(),
+                date: bucket.end,
+                bucket: bucket.clone(),
+                series: name.clone(),
+            });
+        }
+    }
+
+    Ok(Forecast {
+        balances: output,
+        items,
+        lowest,
+    }
+    .into())
+}
@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+
+use axum::extract;
+use chrono::{Days, Months, NaiveDate};
+
+use crate::{
+    service::{config::client::account_separator, Error, Result},
+    state::AppState,
+};
+
+use super::statement::{build_lines, totals, Line};
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DateRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Comparison {
+    /// The period of the same length just before.
+    PreviousPeriod,
+    /// The same dates a year earlier.
+    PreviousYear,
+}
+
+impl Comparison {
+    fn range(self, DateRange { from, to }: DateRange) -> Option<DateRange> {
+        match self {
+            Comparison::PreviousPeriod => {
+                let days = Days::new((to - from).num_days() as u64 + 1);
+                Some(DateRange {
+                    from: from.checked_sub_days(days)?,
+                    to: to.checked_sub_days(days)?,
+                })
+            }
+            Comparison::PreviousYear => Some(DateRange {
+                from: from.checked_sub_months(Months::new(12))?,
+                to: to.checked_sub_months(Months::new(12))?,
+            }),
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    pub periods: Vec<DateRange>,
+    /// Comparison columns to add after each period.
+    #[serde(default)]
+    pub compare: Vec<Comparison>,
+}
+
+#[derive(serde::Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Column {
+    #[serde(flatten)]
+    pub range: DateRange,
+    /// The index of the period this column compares with, for comparison columns.
+    pub period: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<Comparison>,
+}
+
+#[derive(serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomeStatement {
+    pub columns: Vec<Column>,
+    /// Income accounts, with income as positive amounts.
+    pub income: Vec<Line>,
+    pub expenses: Vec<Line>,
+    pub total_income: Vec<i64>,
+    pub total_expenses: Vec<i64>,
+    pub net_income: Vec<i64>,
+}
+
+//language=sql
+const SQL: &str = r#"
+select ds.account, t.type, sum(ds.total)
+from daily_sum ds
+inner join account_types t on t.account = ds.account collate nocase
+where t.type in ('income', 'expense')
+  and ds.transDate >= ?1
+  and ds.transDate <= ?2
+group by ds.account collate nocase
+"#;
+
+pub async fn execute(
+    state: extract::State<AppState>,
+    extract::Json(Input { periods, compare }): extract::Json<Input>,
+) -> Result<extract::Json<IncomeStatement>> {
+    if periods.is_empty() {
+        return Err(Error::InvalidArgument(
+            "At least one period is required".into(),
+        ));
+    }
+
+    let mut columns = Vec::new();
+    for (period, range) in periods.into_iter().enumerate() {
+        if range.to < range.from {
+            return Err(Error::InvalidArgument(
+                "A period ends before it starts".into(),
+            ));
+        }
+        columns.push(Column {
+            range,
+            period,
+            comparison: None,
+        });
+        for comparison in &compare {
+            if let Some(range) = comparison.range(range) {
+                columns.push(Column {
+                    range,
+                    period,
+                    comparison: Some(*comparison),
+                });
+            }
+        }
+    }
+
+    // Amounts by account, keyed in lower case as account names are case insensitive.
+    let mut income: BTreeMap<String, (String, Vec<i64>)> = Default::default();
+    let mut expenses: BTreeMap<String, (String, Vec<i64>)> = Default::default();
+    for (i, column) in columns.iter().enumerate() {
+        let rows: Vec<(String, String, i64)> = sqlx::query_as(SQL)
+            .bind(column.range.from)
+            .bind(column.range.to)
+            .fetch_all(&state.conn)
+            .await?;
+        for (account, account_type, total) in rows {
+            let (accounts, amount) = if account_type == "income" {
+                (&mut income, -total)
+            } else {
+                (&mut expenses, total)
+            };
+            accounts
+                .entry(account.to_lowercase())
+                .or_insert_with(|| (account, vec![0; columns.len()]))
+                .1[i] = amount;
+        }
+    }
+
+    let separator = account_separator(&state.conn).await?;
+    let income = build_lines(income.into_values(), &separator, columns.len());
+    let expenses = build_lines(expenses.into_values(), &separator, columns.len());
+    let total_income = totals(&income, columns.len());
+    let total_expenses = totals(&expenses, columns.len());
+    let net_income = total_income
+        .iter()
+        .zip(&total_expenses)
+        .map(|(i, e)| i - e)
+        .collect();
+
+    Ok(IncomeStatement {
+        columns,
+        income,
+        expenses,
+        total_income,
+        total_expenses,
+        net_income,
+    }
+    .into())
+}
@@ -1,23 +1,233 @@
-use axum::{routing::post, Router};
+use axum::{
+    routing::{get, post},
+    Router,
+};
 
-use crate::state::AppState;
+use chrono::{Datelike, Days, Months, NaiveDate};
 
-mod balance;
-mod sum;
+use crate::{
+    service::{config::client::fiscal_year_start, Error, Result},
+    state::AppState,
+};
 
-#[derive(serde::Deserialize, sqlx::Type)]
-enum Frequency {
+pub mod anomaly;
+pub mod balance;
+pub mod balance_sheet;
+pub mod budget;
+pub mod flow;
+pub mod forecast;
+pub mod income_statement;
+pub mod net_worth;
+mod statement;
+pub mod sum;
+pub mod tags;
+
+#[cfg(test)]
+mod test;
+
+#[derive(serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Frequency {
+    #[default]
     Daily,
+    /// Weeks starting on Monday, except the first of each year, which starts on 1 January.
     Weekly,
+    /// ISO 8601 weeks, which start on Monday and may span two years.
+    IsoWeekly,
+    /// Every other ISO week, counting from the week of 5 January 1970.
+    Fortnightly,
     Monthly,
+    Quarterly,
     Yearly,
+    /// Years starting on the "fiscalYearStart" client config, e.g. "04-01" for 1 April.
+    FiscalYearly,
+}
+
+/// A period of a [`Frequency`], as its time point and the dates it covers.
+#[derive(serde::Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Bucket {
+    pub time_point: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+/// The most buckets a report may have, to keep a long range at a short frequency from building
+/// up millions of them.
+const MAX_BUCKETS: usize = 10_000;
+
+/// The Monday that fortnights count from.
+const FORTNIGHT_EPOCH: &str = "1970-01-05";
+
+/// Splits dates into the buckets of a [`Frequency`].
+#[derive(Clone, Copy, Debug)]
+pub struct Bucketing {
+    freq: Frequency,
+    /// The month and day fiscal years start on.
+    fiscal_year_start: (u32, u32),
+}
+
+impl Bucketing {
+    pub async fn new(conn: &sqlx::SqlitePool, freq: Frequency) -> Result<Self> {
+        let fiscal_year_start = match freq {
+            Frequency::FiscalYearly => fiscal_year_start(conn).await?,
+            _ => (1, 1),
+        };
+        Ok(Self {
+            freq,
+            fiscal_year_start,
+        })
+    }
+
+    /// The SQL expression for the first day of the bucket `date` falls in.
+    pub fn start_sql(&self, date: &str) -> String {
+        match self.freq {
+            Frequency::Daily => format!("date({date})"),
+            Frequency::Weekly => {
+                format!("max(date({date}, 'weekday 0', '-6 days'), date({date}, 'start of year'))")
+            }
+            Frequency::IsoWeekly => format!("date({date}, 'weekday 0', '-6 days')"),
+            Frequency::Fortnightly => {
+                let days =
+                    format!("cast(julianday({date}) - julianday('{FORTNIGHT_EPOCH}') as integer)");
+                format!(
+                    "date('{FORTNIGHT_EPOCH}', ({days} - ((({days} % 14) + 14) % 14)) || ' days')"
+                )
+            }
+            Frequency::Monthly => format!("date({date}, 'start of month')"),
+            Frequency::Quarterly => format!(
+                "date({date}, 'start of month', \
+                      '-' || ((cast(strftime('%m', {date}) as integer) - 1) % 3) || ' months')"
+            ),
+            Frequency::Yearly => format!("date({date}, 'start of year')"),
+            Frequency::FiscalYearly => {
+                let (month, day) = self.fiscal_year_start;
+                let start = format!(
+                    "date({date}, 'start of year', '+{} months', '+{} days')",
+                    month - 1,
+                    day - 1
+                );
+                format!("iif(date({date}) >= {start}, {start}, date({start}, '-1 years'))")
+            }
+        }
+    }
+
+    /// The first day of the bucket `date` falls in, as given by [`Self::start_sql`].
+    pub fn start(&self, date: NaiveDate) -> NaiveDate {
+        let monday = date - Days::new(date.weekday().num_days_from_monday().into());
+        let start_of_year = date.with_ordinal(1).unwrap_or(date);
+        match self.freq {
+            Frequency::Daily => date,
+            Frequency::Weekly => monday.max(start_of_year),
+            Frequency::IsoWeekly => monday,
+            Frequency::Fortnightly => {
+                let epoch = NaiveDate::parse_from_str(FORTNIGHT_EPOCH, "%Y-%m-%d").unwrap();
+                let days = (date - epoch).num_days();
+                epoch + chrono::Duration::days(days - days.rem_euclid(14))
+            }
+            Frequency::Monthly => date.with_day(1).unwrap_or(date),
+            Frequency::Quarterly => {
+                NaiveDate::from_ymd_opt(date.year(), (date.month() - 1) / 3 * 3 + 1, 1)
+                    .unwrap_or(date)
+            }
+            Frequency::Yearly => start_of_year,
+            Frequency::FiscalYearly => {
+                let (month, day) = self.fiscal_year_start;
+                let start = |year| NaiveDate::from_ymd_opt(year, month, day).unwrap_or(date);
+                if date >= start(date.year()) {
+                    start(date.year())
+                } else {
+                    start(date.year() - 1)
+                }
+            }
+        }
+    }
+
+    /// The first day of the bucket after the one starting at `start`.
+    fn next(&self, start: NaiveDate) -> Option<NaiveDate> {
+        match self.freq {
+            Frequency::Daily => start.succ_opt(),
+            Frequency::Weekly => start
+                .checked_add_days(Days::new(
+                    7 - u64::from(start.weekday().num_days_from_monday()),
+                ))
+                .map(|d| d.min(NaiveDate::from_ymd_opt(start.year() + 1, 1, 1).unwrap_or(d))),
+            Frequency::IsoWeekly => start.checked_add_days(Days::new(7)),
+            Frequency::Fortnightly => start.checked_add_days(Days::new(14)),
+            Frequency::Monthly => start.checked_add_months(Months::new(1)),
+            Frequency::Quarterly => start.checked_add_months(Months::new(3)),
+            Frequency::Yearly | Frequency::FiscalYearly => {
+                start.checked_add_months(Months::new(12))
+            }
+        }
+    }
+
+    fn time_point(&self, start: NaiveDate, end: NaiveDate) -> String {
+        match self.freq {
+            Frequency::Daily => start.format("%Y-%j").to_string(),
+            Frequency::Weekly => start.format("%Y-%W").to_string(),
+            Frequency::IsoWeekly => start.format("%G-W%V").to_string(),
+            Frequency::Fortnightly => start.to_string(),
+            Frequency::Monthly => start.format("%Y-%m").to_string(),
+            Frequency::Quarterly => format!("{}-Q{}", start.year(), (start.month() - 1) / 3 + 1),
+            Frequency::Yearly => start.format("%Y").to_string(),
+            // Named after the year they end in, as in "FY2021" for April 2020 to March 2021
+            Frequency::FiscalYearly => format!("FY{}", end.year()),
+        }
+    }
+
+    /// The whole bucket starting at `start`.
+    pub fn bucket(&self, start: NaiveDate) -> Bucket {
+        let end = self
+            .next(start)
+            .and_then(|d| d.pred_opt())
+            .unwrap_or(NaiveDate::MAX);
+        Bucket {
+            time_point: self.time_point(start, end),
+            start,
+            end,
+        }
+    }
+
+    /// The buckets covering `from..=to`, with the first and last cut short to fit, as long as
+    /// there are no more than [`MAX_BUCKETS`] of them.
+    pub fn buckets(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Bucket>> {
+        let mut buckets = Vec::new();
+        let mut start = self.start(from);
+        while start <= to {
+            if buckets.len() == MAX_BUCKETS {
+                return Err(Error::InvalidArgument(
+                    format!(
+                        "More than {MAX_BUCKETS} periods to report, use a shorter range or a longer frequency"
+                    )
+                    .into(),
+                ));
+            }
+            let mut bucket = self.bucket(start);
+            bucket.start = bucket.start.max(from);
+            bucket.end = bucket.end.min(to);
+            buckets.push(bucket);
+            match self.next(start) {
+                Some(next) => start = next,
+                None => break,
+            }
+        }
+        Ok(buckets)
+    }
 }
 
 pub fn router() -> Router<AppState> {
     Router::new().nest(
         "/api/reports",
         Router::new()
+            .route("/anomalies", post(anomaly::execute))
             .route("/balance", post(balance::execute))
-            .route("/sum", post(sum::execute)),
+            .route("/balanceSheet", get(balance_sheet::execute))
+            .route("/budget", post(budget::execute))
+            .route("/flow", post(flow::execute))
+            .route("/forecast", post(forecast::execute))
+            .route("/incomeStatement", post(income_statement::execute))
+            .route("/netWorth", post(net_worth::execute))
+            .route("/sum", post(sum::execute))
+            .route("/tags", post(tags::execute)),
     )
 }
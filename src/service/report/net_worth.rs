@@ -0,0 +1,97 @@
+use axum::extract;
+use chrono::NaiveDate;
+use sqlx::query_as;
+
+use crate::{service::Result, state::AppState};
+
+use super::{Bucket, Bucketing};
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub freq: super::Frequency,
+}
+
+#[derive(serde::Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DataPoint {
+    /// The bucket, cut short to fit `from` and `to`.
+    #[serde(flatten)]
+    pub bucket: Bucket,
+    /// The last day of the bucket, or `to` for a bucket cut short, which the balances are as of.
+    pub date: NaiveDate,
+    pub assets: i64,
+    /// The amount owed on liability accounts.
+    pub liabilities: i64,
+    pub net_worth: i64,
+}
+
+// The daily changes of assets and liabilities, with the opening balances of accounts without an
+// opened date first, under no date.
+//language=sql
+const SQL: &str = r#"
+with entries as (
+    select account, transDate, total
+    from daily_sum
+
+    union all
+
+    select name, openedDate, openingBalance
+    from accounts_meta
+    where openingBalance is not null
+)
+select e.transDate,
+       sum(iif(t.type = 'asset', e.total, 0)),
+       sum(iif(t.type = 'liability', e.total, 0))
+from entries e
+inner join account_types t on t.account = e.account collate nocase
+where t.type in ('asset', 'liability')
+  and (?1 is null or e.transDate is null or e.transDate <= ?1)
+group by e.transDate
+order by e.transDate
+"#;
+
+/// The net worth at the end of every bucket from `from` to `to`, carrying it over buckets without
+/// transactions.
+pub async fn execute(
+    state: extract::State<AppState>,
+    extract::Json(Input { from, to, freq }): extract::Json<Input>,
+) -> Result<extract::Json<Vec<DataPoint>>> {
+    let changes: Vec<(Option<NaiveDate>, i64, i64)> =
+        query_as(SQL).bind(to).fetch_all(&state.conn).await?;
+    let dates = || changes.iter().filter_map(|(date, _, _)| *date);
+    let (Some(from), Some(to)) = (
+        from.or_else(|| dates().next()),
+        to.or_else(|| dates().next_back()),
+    ) else {
+        return Ok(Vec::new().into());
+    };
+    let buckets = Bucketing::new(&state.conn, freq).await?.buckets(from, to)?;
+
+    let (mut assets, mut liabilities) = (0, 0);
+    let mut changes = changes.into_iter().peekable();
+    while let Some((_, a, l)) = changes.next_if(|(date, _, _)| date.is_none_or(|d| d < from)) {
+        assets += a;
+        liabilities += l;
+    }
+
+    let mut output = Vec::new();
+    for bucket in buckets {
+        while let Some((_, a, l)) =
+            changes.next_if(|(date, _, _)| date.is_some_and(|d| d <= bucket.end))
+        {
+            assets += a;
+            liabilities += l;
+        }
+        output.push(DataPoint {
+            date: bucket.end,
+            bucket,
+            assets,
+            liabilities: -liabilities,
+            net_worth: assets + liabilities,
+        });
+    }
+    Ok(output.into())
+}
@@ -0,0 +1,76 @@
+use crate::service::account::tree::{node_mut, Node};
+
+/// A line of a financial statement, with the amounts of the accounts under it rolled up.
+#[derive(serde::Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Line {
+    /// The full account name, e.g. "Expenses:Food".
+    pub name: String,
+    /// The last level of the name, e.g. "Food".
+    pub label: String,
+    /// One amount per column of the statement.
+    pub amounts: Vec<i64>,
+    pub children: Vec<Line>,
+}
+
+impl Line {
+    fn roll_up(&mut self) {
+        for child in &mut self.children {
+            child.roll_up();
+            for (total, amount) in self.amounts.iter_mut().zip(&child.amounts) {
+                *total += amount;
+            }
+        }
+    }
+}
+
+impl Node for Line {
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn children_mut(&mut self) -> &mut Vec<Self> {
+        &mut self.children
+    }
+}
+
+/// Arranges accounts by their names, adding each account's amounts to every line above it.
+pub fn build_lines(
+    accounts: impl IntoIterator<Item = (String, Vec<i64>)>,
+    separator: &str,
+    columns: usize,
+) -> Vec<Line> {
+    let mut roots: Vec<Line> = Default::default();
+
+    for (name, amounts) in accounts {
+        let line = node_mut(&mut roots, &name, separator, |name, label| Line {
+            name,
+            label,
+            amounts: vec![0; columns],
+            children: Default::default(),
+        });
+        for (total, amount) in line.amounts.iter_mut().zip(&amounts) {
+            *total += amount;
+        }
+    }
+
+    for root in &mut roots {
+        root.roll_up();
+    }
+    sort(&mut roots);
+    roots
+}
+
+fn sort(lines: &mut [Line]) {
+    lines.sort_by_key(|l| l.label.to_lowercase());
+    for line in lines {
+        sort(&mut line.children);
+    }
+}
+
+/// Adds up the top level lines, column by column.
+pub fn totals(lines: &[Line], columns: usize) -> Vec<i64> {
+    (0..columns)
+        .map(|i| lines.iter().map(|l| l.amounts[i]).sum())
+        .collect()
+}
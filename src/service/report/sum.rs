@@ -1,42 +1,125 @@
-use chrono::NaiveDate;
+use std::collections::HashMap;
+
+use chrono::{Months, NaiveDate};
 use sqlx::query_as;
 
-use crate::{service::Result, sqlx_ext::Json, state::AppState};
+use crate::{
+    service::{account_group::input_accounts_sql, Result},
+    sqlx_ext::Json,
+    state::AppState,
+};
 use axum::extract;
 
+use super::{Bucket, Bucketing, Frequency};
+
+#[derive(serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum SeriesBy {
+    /// Everything selected adds up to one series.
+    #[default]
+    None,
+    /// Each of `accounts` and `account_groups` is a series of its own.
+    Selection,
+}
+
 #[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Input {
-    from: Option<NaiveDate>,
-    to: Option<NaiveDate>,
-    freq: super::Frequency,
-    accounts: Json<Vec<String>>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub freq: Frequency,
+    #[serde(default)]
+    pub accounts: Json<Vec<String>>,
+    #[serde(default)]
+    pub account_groups: Json<Vec<String>>,
+    #[serde(default)]
+    pub series_by: SeriesBy,
+    #[serde(default)]
+    pub extras: Extras,
 }
 
-#[derive(serde::Serialize, sqlx::FromRow)]
+/// Figures to add to each data point, which look at the buckets before it.
+#[derive(serde::Deserialize, Default, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Extras {
+    /// The total of the bucket before.
+    #[serde(default)]
+    pub previous: bool,
+    /// The total of the bucket a year before.
+    #[serde(default)]
+    pub last_year: bool,
+    /// Averages the total over this many buckets, up to and including the data point's.
+    pub rolling_average: Option<usize>,
+    /// The total since the start of the year, which starts on the "fiscalYearStart" client config.
+    #[serde(default)]
+    pub year_to_date: bool,
+}
+
+impl Extras {
+    fn any(&self) -> bool {
+        self.previous || self.last_year || self.rolling_average.is_some() || self.year_to_date
+    }
+}
+
+#[derive(serde::Serialize, Debug, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct DataPoint {
-    total: i64,
-    time_point: String,
+    pub total: i64,
+    #[serde(flatten)]
+    pub bucket: Bucket,
+    /// The account or group name, when split by [`SeriesBy::Selection`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_year: Option<i64>,
+    /// Rounded to the cent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rolling_average: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year_to_date: Option<i64>,
 }
 
 //language=sql
 const SQL: &str = r#"
-select sum(ds.total) as total,
-       (case ?4 
-           when 'Weekly' collate nocase then strftime('%Y-%W', ds.transDate) 
-           when 'Monthly' collate nocase then strftime('%Y-%m', ds.transDate) 
-           when 'Yearly' collate nocase then strftime('%Y', ds.transDate)
-           else strftime('%Y-%j', ds.transDate)
-        end) as time_point
+select cast(round(sum(ds.total * ia.weight)) as integer) as total,
+       {BUCKET_START} as bucketStart,
+       ia.series
 from daily_sum ds
+inner join input_accounts ia on ia.account = ds.account collate nocase
 where 
       (?1 is null or ds.transDate >= ?1) and
-      (?2 is null or ds.transDate <= ?2) and
-      ds.account in (select trim(value) from json_each(?3)) collate nocase
-group by time_point
-order by time_point
+      (?2 is null or ds.transDate <= ?2)
+group by ia.series collate nocase, bucketStart
+order by ia.series collate nocase, bucketStart
 "#;
 
+/// The total of each series in each bucket, as `(total, bucket start, series)`.
+async fn totals(
+    state: &AppState,
+    bucketing: &Bucketing,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    accounts: &Json<Vec<String>>,
+    account_groups: &Json<Vec<String>>,
+    split: bool,
+) -> Result<Vec<(i64, NaiveDate, Option<String>)>> {
+    let sql = format!(
+        "with input_accounts as ({}) {}",
+        input_accounts_sql(3, 4, "?5"),
+        SQL.replace("{BUCKET_START}", &bucketing.start_sql("ds.transDate"))
+    );
+    Ok(query_as(&sql)
+        .bind(from)
+        .bind(to)
+        .bind(accounts)
+        .bind(account_groups)
+        .bind(split)
+        .fetch_all(&state.conn)
+        .await?)
+}
+
 pub async fn execute(
     state: extract::State<AppState>,
     extract::Json(Input {
@@ -44,14 +127,100 @@ pub async fn execute(
         to,
         freq,
         accounts,
+        account_groups,
+        series_by,
+        extras,
     }): extract::Json<Input>,
 ) -> Result<extract::Json<Vec<DataPoint>>> {
-    Ok(query_as(SQL)
-        .bind(from)
-        .bind(to)
-        .bind(accounts)
-        .bind(freq)
-        .fetch_all(&state.conn)
-        .await?
-        .into())
+    let bucketing = Bucketing::new(&state.conn, freq).await?;
+    let split = series_by == SeriesBy::Selection;
+    let rows = totals(
+        &state,
+        &bucketing,
+        from,
+        to,
+        &accounts,
+        &account_groups,
+        split,
+    )
+    .await?;
+
+    let mut points: Vec<DataPoint> = rows
+        .into_iter()
+        .map(|(total, start, series)| DataPoint {
+            total,
+            bucket: bucketing.bucket(start),
+            series,
+            previous: None,
+            last_year: None,
+            rolling_average: None,
+            year_to_date: None,
+        })
+        .collect();
+    if !extras.any() {
+        return Ok(points.into());
+    }
+
+    // The extras look back before `from`, so they come from the whole history instead.
+    let history: HashMap<(Option<String>, NaiveDate), i64> = totals(
+        &state,
+        &bucketing,
+        None,
+        to,
+        &accounts,
+        &account_groups,
+        split,
+    )
+    .await?
+    .into_iter()
+    .map(|(total, start, series)| ((series.map(|s| s.to_lowercase()), start), total))
+    .collect();
+    let first = history.keys().map(|(_, start)| *start).min();
+    let year = Bucketing::new(&state.conn, Frequency::FiscalYearly).await?;
+
+    for point in &mut points {
+        let series = point.series.as_ref().map(|s| s.to_lowercase());
+        let total = |start: NaiveDate| {
+            history
+                .get(&(series.clone(), start))
+                .copied()
+                .unwrap_or_default()
+        };
+        let before = |start: NaiveDate| start.pred_opt().map(|d| bucketing.start(d));
+        let start = point.bucket.start;
+
+        if extras.previous {
+            point.previous = Some(before(start).map_or(0, total));
+        }
+        if extras.last_year {
+            point.last_year = Some(
+                start
+                    .checked_sub_months(Months::new(12))
+                    .map_or(0, |d| total(bucketing.start(d))),
+            );
+        }
+        if let Some(n) = extras.rolling_average.filter(|n| *n > 0) {
+            // Buckets before the first transaction don't count towards the average
+            let starts: Vec<NaiveDate> = std::iter::successors(Some(start), |s| before(*s))
+                .take(n)
+                .take_while(|s| first.is_some_and(|first| *s >= first))
+                .collect();
+            let sum: i64 = starts.iter().copied().map(total).sum();
+            point.rolling_average = Some((sum as f64 / starts.len().max(1) as f64).round() as i64);
+        }
+        if extras.year_to_date {
+            let year_start = year.start(start);
+            point.year_to_date = Some(
+                history
+                    .iter()
+                    .filter(|((s, bucket), _)| {
+                        *s == series && year_start <= *bucket && *bucket <= start
+                    })
+                    .map(|(_, total)| total)
+                    .sum(),
+            );
+        }
+    }
+
+    Ok(points.into())
 }
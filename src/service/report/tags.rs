@@ -0,0 +1,89 @@
+use chrono::NaiveDate;
+use sqlx::query_as;
+
+use crate::{service::Result, sqlx_ext::Json, state::AppState};
+use axum::extract;
+
+use super::{Bucket, Bucketing};
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub freq: super::Frequency,
+    /// The tags to report on, including the tags under them. All tags when empty.
+    #[serde(default)]
+    pub tags: Json<Vec<String>>,
+}
+
+#[derive(serde::Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DataPoint {
+    pub tag: String,
+    #[serde(flatten)]
+    pub bucket: Bucket,
+    pub total: i64,
+    pub num_tx: i64,
+}
+
+/// Each transaction counts once towards each of its tags and each tag above them, so a parent
+/// tag totals everything under it.
+//language=sql
+const SQL: &str = r#"
+with transaction_ancestors(transactionId, tag) as (
+    select distinct tt.transactionId, ta.ancestor
+    from transaction_tags tt
+    inner join tag_ancestors ta on ta.tag = tt.tag
+)
+select tx.tag,
+       {BUCKET_START} as bucketStart,
+       sum(t.amount) as total,
+       count(*) as num_tx
+from transaction_ancestors tx
+inner join transactions t on t.id = tx.transactionId
+where (?1 is null or t.transDate >= ?1)
+  and (?2 is null or t.transDate <= ?2)
+  and (
+      ifnull(json_array_length(?3), 0) == 0
+      or exists (
+          -- Both are on the same line of ancestors, with the reported tag at or below the requested one
+          select 1 from tag_ancestors reported
+          inner join tag_ancestors requested on requested.tag = reported.tag
+          where reported.ancestor = tx.tag
+            and requested.ancestor collate nocase in (select trim(value) from json_each(?3))
+            and length(reported.ancestor) >= length(requested.ancestor)
+      )
+  )
+group by tx.tag, bucketStart
+order by tx.tag, bucketStart
+"#;
+
+pub async fn execute(
+    state: extract::State<AppState>,
+    extract::Json(Input {
+        from,
+        to,
+        freq,
+        tags,
+    }): extract::Json<Input>,
+) -> Result<extract::Json<Vec<DataPoint>>> {
+    let bucketing = Bucketing::new(&state.conn, freq).await?;
+    let sql = SQL.replace("{BUCKET_START}", &bucketing.start_sql("t.transDate"));
+    let rows: Vec<(String, NaiveDate, i64, i64)> = query_as(&sql)
+        .bind(from)
+        .bind(to)
+        .bind(tags)
+        .fetch_all(&state.conn)
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(tag, start, total, num_tx)| DataPoint {
+            tag,
+            bucket: bucketing.bucket(start),
+            total,
+            num_tx,
+        })
+        .collect::<Vec<_>>()
+        .into())
+}
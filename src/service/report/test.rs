@@ -0,0 +1,1016 @@
+use axum::extract::{Json, State};
+
+use crate::service::{
+    account::test::new_transaction,
+    account_group::models::{AccountGroup, Member, MemberType},
+    budget::model::{Budget, Period},
+    recurring::model::{Cadence, RecurringTransaction},
+    transaction,
+};
+use crate::sqlx_ext;
+use crate::state::AppState;
+use chrono::NaiveDate;
+
+use super::*;
+
+async fn setup() -> State<AppState> {
+    let state = State(AppState::new_test().await);
+    let _ = transaction::save::execute(
+        state.clone(),
+        Json(vec![
+            new_transaction("1", "Income", "Bank", 1000, "2020-01-01"),
+            new_transaction("2", "Bank", "Expenses:Food", 100, "2020-01-02"),
+            new_transaction("3", "Bank", "Expenses:Rent", 300, "2020-02-01"),
+            new_transaction("4", "Bank", "Savings", 200, "2020-02-02"),
+            new_transaction("5", "Credit card", "Expenses:Food", 50, "2020-02-03"),
+        ]),
+    )
+    .await
+    .expect("To save transactions");
+
+    let group = |name: &str, accounts: &[&str], members: Vec<Member>| AccountGroup {
+        group_name: name.to_string(),
+        accounts: sqlx_ext::Json(accounts.iter().map(|a| a.to_string()).collect()),
+        members: Some(sqlx_ext::Json(members)),
+        rules: None,
+    };
+    let _ = crate::service::account_group::save::execute(
+        state.clone(),
+        Json(vec![
+            group("Living costs", &["Expenses"], vec![]),
+            group(
+                "Net worth",
+                &["Bank", "Savings"],
+                vec![Member {
+                    member_type: MemberType::Account,
+                    name: "Credit card".to_string(),
+                    weight: 1.0,
+                }],
+            ),
+        ]),
+    )
+    .await
+    .expect("To save groups");
+    state
+}
+
+#[tokio::test]
+async fn sum_by_groups_works() {
+    let state = setup().await;
+
+    let points = |output: Vec<sum::DataPoint>| {
+        output
+            .into_iter()
+            .map(|p| (p.series, p.bucket.time_point, p.total))
+            .collect::<Vec<_>>()
+    };
+
+    let output = sum::execute(
+        state.clone(),
+        Json(sum::Input {
+            from: None,
+            to: None,
+            freq: Frequency::Monthly,
+            accounts: sqlx_ext::Json(vec!["Savings".to_string()]),
+            account_groups: sqlx_ext::Json(vec!["Living costs".to_string()]),
+            series_by: sum::SeriesBy::None,
+            extras: Default::default(),
+        }),
+    )
+    .await
+    .expect("To sum")
+    .0;
+    assert_eq!(
+        points(output),
+        vec![
+            (None, "2020-01".to_string(), 100),
+            (None, "2020-02".to_string(), 550),
+        ]
+    );
+
+    let output = sum::execute(
+        state.clone(),
+        Json(sum::Input {
+            from: None,
+            to: None,
+            freq: Frequency::Monthly,
+            accounts: sqlx_ext::Json(vec!["Expenses:Food".to_string()]),
+            account_groups: sqlx_ext::Json(vec![
+                "Living costs".to_string(),
+                "Net worth".to_string(),
+            ]),
+            series_by: sum::SeriesBy::Selection,
+            extras: Default::default(),
+        }),
+    )
+    .await
+    .expect("To sum")
+    .0;
+    assert_eq!(
+        points(output),
+        vec![
+            (
+                Some("Expenses:Food".to_string()),
+                "2020-01".to_string(),
+                100
+            ),
+            (Some("Expenses:Food".to_string()), "2020-02".to_string(), 50),
+            (Some("Living costs".to_string()), "2020-01".to_string(), 100),
+            (Some("Living costs".to_string()), "2020-02".to_string(), 350),
+            (Some("Net worth".to_string()), "2020-01".to_string(), 900),
+            (Some("Net worth".to_string()), "2020-02".to_string(), -350),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn balance_by_groups_works() {
+    let state = setup().await;
+
+    let balances = |input: balance::Input| {
+        let state = state.clone();
+        async move {
+            balance::execute(state, Json(input))
+                .await
+                .expect("To get balances")
+                .0
+                .into_iter()
+                .map(|r| (r.series, r.date.to_string(), r.balance))
+                .collect::<Vec<_>>()
+        }
+    };
+    let date = |v: &str| v.parse().ok();
+
+    let output = balances(balance::Input {
+        account_groups: sqlx_ext::Json(vec!["Net worth".to_string()]),
+        ..Default::default()
+    })
+    .await;
+    assert_eq!(output.len(), 34);
+    assert_eq!(output[0], (None, "2020-01-01".to_string(), 1000));
+    // Days without transactions carry the balance over
+    assert_eq!(output[15], (None, "2020-01-16".to_string(), 900));
+    assert_eq!(output[33], (None, "2020-02-03".to_string(), 550));
+
+    // Only the range asked for, one series per selection, in every bucket
+    let s = |v: &str| Some(v.to_string());
+    assert_eq!(
+        balances(balance::Input {
+            from: date("2020-01-02"),
+            to: date("2020-03-15"),
+            freq: Frequency::Monthly,
+            accounts: sqlx_ext::Json(vec!["Savings".to_string()]),
+            account_groups: sqlx_ext::Json(vec!["Net worth".to_string()]),
+            series_by: sum::SeriesBy::Selection,
+        })
+        .await,
+        vec![
+            (s("Savings"), "2020-01-31".to_string(), 0),
+            (s("Net worth"), "2020-01-31".to_string(), 900),
+            (s("Savings"), "2020-02-29".to_string(), 200),
+            (s("Net worth"), "2020-02-29".to_string(), 550),
+            (s("Savings"), "2020-03-15".to_string(), 200),
+            (s("Net worth"), "2020-03-15".to_string(), 550),
+        ]
+    );
+
+    assert_eq!(
+        balances(balance::Input {
+            to: date("2020-01-05"),
+            freq: Frequency::Weekly,
+            accounts: sqlx_ext::Json(vec!["Bank".to_string()]),
+            ..Default::default()
+        })
+        .await,
+        vec![(None, "2020-01-05".to_string(), 900),]
+    );
+}
+
+#[tokio::test]
+async fn budget_works() {
+    let state = setup().await;
+
+    let budget = |name: &str, period: Period, amount: i64, start: &str, end: Option<&str>| Budget {
+        id: None,
+        name: name.to_string(),
+        account: None,
+        account_group: None,
+        period,
+        amount,
+        start_date: start.parse().unwrap(),
+        end_date: end.map(|d| d.parse().unwrap()),
+        rollover: false,
+    };
+    let _ = crate::service::budget::save::execute(
+        state.clone(),
+        Json(vec![
+            Budget {
+                account: Some("Expenses:Food".to_string()),
+                rollover: true,
+                ..budget("Food", Period::Monthly, 80, "2020-01-01", None)
+            },
+            Budget {
+                account_group: Some("Living costs".to_string()),
+                ..budget(
+                    "Moving",
+                    Period::Custom,
+                    500,
+                    "2020-01-15",
+                    Some("2020-02-15"),
+                )
+            },
+        ]),
+    )
+    .await
+    .expect("To save budgets");
+
+    let output = budget::execute(
+        state.clone(),
+        Json(budget::Input {
+            from: "2020-02-01".parse().unwrap(),
+            to: "2020-03-31".parse().unwrap(),
+            budgets: None,
+        }),
+    )
+    .await
+    .expect("To report")
+    .0;
+
+    let lines = output
+        .iter()
+        .map(|l| {
+            (
+                l.name.as_str(),
+                l.period_start.to_string(),
+                l.period_end.to_string(),
+                l.carried_over,
+                l.budgeted,
+                l.actual,
+                l.remaining,
+                l.overspent,
+            )
+        })
+        .collect::<Vec<_>>();
+    // January's overspending doesn't carry over, but February's savings do.
+    assert_eq!(
+        lines,
+        vec![
+            (
+                "Food",
+                "2020-02-01".into(),
+                "2020-02-29".into(),
+                0,
+                80,
+                50,
+                30,
+                0
+            ),
+            (
+                "Food",
+                "2020-03-01".into(),
+                "2020-03-31".into(),
+                30,
+                110,
+                0,
+                110,
+                0
+            ),
+            (
+                "Moving",
+                "2020-01-15".into(),
+                "2020-02-15".into(),
+                0,
+                500,
+                350,
+                150,
+                0
+            ),
+        ]
+    );
+    assert_eq!(output[2].percent_used, Some(70.0));
+
+    let output = budget::execute(
+        state.clone(),
+        Json(budget::Input {
+            from: "2020-01-01".parse().unwrap(),
+            to: "2020-01-31".parse().unwrap(),
+            budgets: Some(vec![output[0].budget_id]),
+        }),
+    )
+    .await
+    .expect("To report")
+    .0;
+    assert_eq!(output.len(), 1);
+    assert_eq!(
+        (output[0].overspent, output[0].percent_used),
+        (20, Some(125.0))
+    );
+}
+
+#[tokio::test]
+async fn income_statement_works() {
+    use crate::service::account::model::{AccountMeta, AccountType};
+    use income_statement::{Comparison, DateRange};
+
+    let state = setup().await;
+    let meta = |name: &str, account_type: AccountType| AccountMeta {
+        name: name.to_string(),
+        account_type: Some(account_type),
+        currency: None,
+        notes: None,
+        opened_date: None,
+        closed_date: None,
+        opening_balance: None,
+    };
+    let _ = crate::service::account::save::execute(
+        state.clone(),
+        Json(vec![
+            meta("Income", AccountType::Income),
+            meta("Expenses", AccountType::Expense),
+        ]),
+    )
+    .await
+    .expect("To save meta");
+
+    let range = |from: &str, to: &str| DateRange {
+        from: from.parse().unwrap(),
+        to: to.parse().unwrap(),
+    };
+    let output = income_statement::execute(
+        state.clone(),
+        Json(income_statement::Input {
+            periods: vec![
+                range("2020-01-01", "2020-01-31"),
+                range("2020-02-01", "2020-02-29"),
+            ],
+            compare: vec![Comparison::PreviousYear],
+        }),
+    )
+    .await
+    .expect("To report")
+    .0;
+
+    assert_eq!(
+        output
+            .columns
+            .iter()
+            .map(|c| (c.range.from.to_string(), c.period, c.comparison))
+            .collect::<Vec<_>>(),
+        vec![
+            ("2020-01-01".to_string(), 0, None),
+            ("2019-01-01".to_string(), 0, Some(Comparison::PreviousYear)),
+            ("2020-02-01".to_string(), 1, None),
+            ("2019-02-01".to_string(), 1, Some(Comparison::PreviousYear)),
+        ]
+    );
+
+    let lines = |lines: &[statement::Line]| {
+        lines
+            .iter()
+            .flat_map(|l| std::iter::once(l).chain(l.children.iter()))
+            .map(|l| (l.name.clone(), l.amounts.clone()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        lines(&output.income),
+        vec![("Income".to_string(), vec![1000, 0, 0, 0])]
+    );
+    assert_eq!(
+        lines(&output.expenses),
+        vec![
+            ("Expenses".to_string(), vec![100, 0, 350, 0]),
+            ("Expenses:Food".to_string(), vec![100, 0, 50, 0]),
+            ("Expenses:Rent".to_string(), vec![0, 0, 300, 0]),
+        ]
+    );
+    assert_eq!(output.net_income, vec![900, 0, -350, 0]);
+
+    let output = income_statement::execute(
+        state.clone(),
+        Json(income_statement::Input {
+            periods: vec![range("2020-02-01", "2020-02-29")],
+            compare: vec![Comparison::PreviousPeriod],
+        }),
+    )
+    .await
+    .expect("To report")
+    .0;
+    assert_eq!(output.columns[1].range, range("2020-01-03", "2020-01-31"));
+    assert_eq!(output.total_expenses, vec![350, 0]);
+}
+
+#[tokio::test]
+async fn balance_sheet_and_net_worth_work() {
+    use crate::service::account::model::{AccountMeta, AccountType};
+
+    let state = setup().await;
+    let meta = |name: &str, account_type: AccountType| AccountMeta {
+        name: name.to_string(),
+        account_type: Some(account_type),
+        currency: None,
+        notes: None,
+        opened_date: None,
+        closed_date: None,
+        opening_balance: None,
+    };
+    let _ = crate::service::account::save::execute(
+        state.clone(),
+        Json(vec![
+            AccountMeta {
+                opening_balance: Some(500),
+                ..meta("Bank", AccountType::Asset)
+            },
+            meta("Savings", AccountType::Asset),
+            meta("Credit card", AccountType::Liability),
+            meta("Income", AccountType::Income),
+            meta("Expenses", AccountType::Expense),
+        ]),
+    )
+    .await
+    .expect("To save meta");
+
+    let sheet = |as_of: Option<&str>| {
+        let state = state.clone();
+        let as_of = as_of.map(|d| d.parse().unwrap());
+        async move {
+            balance_sheet::execute(state, axum::extract::Query(balance_sheet::Input { as_of }))
+                .await
+                .expect("To report")
+                .0
+        }
+    };
+
+    let output = sheet(None).await;
+    assert_eq!(
+        output
+            .assets
+            .iter()
+            .map(|l| (l.name.as_str(), l.amounts[0]))
+            .collect::<Vec<_>>(),
+        vec![("Bank", 900), ("Savings", 200)]
+    );
+    assert_eq!(
+        (
+            output.total_assets,
+            output.total_liabilities,
+            output.total_equity,
+            output.retained_earnings,
+            output.net_worth
+        ),
+        (1100, 50, 0, 550, 1050)
+    );
+
+    let output = sheet(Some("2020-01-31")).await;
+    assert_eq!((output.total_assets, output.net_worth), (1400, 1400));
+
+    let series = |from: Option<&str>, to: Option<&str>| {
+        let state = state.clone();
+        let from = from.map(|d| d.parse().unwrap());
+        let to = to.map(|d| d.parse().unwrap());
+        async move {
+            net_worth::execute(
+                state,
+                Json(net_worth::Input {
+                    from,
+                    to,
+                    freq: Frequency::Monthly,
+                }),
+            )
+            .await
+            .expect("To report")
+            .0
+            .into_iter()
+            .map(|p| {
+                (
+                    p.bucket.time_point,
+                    p.date.to_string(),
+                    p.assets,
+                    p.liabilities,
+                    p.net_worth,
+                )
+            })
+            .collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(
+        series(None, None).await,
+        vec![
+            (
+                "2020-01".to_string(),
+                "2020-01-31".to_string(),
+                1400,
+                0,
+                1400
+            ),
+            (
+                "2020-02".to_string(),
+                "2020-02-03".to_string(),
+                1100,
+                50,
+                1050
+            ),
+        ]
+    );
+    assert_eq!(
+        series(Some("2020-02-01"), None).await,
+        vec![(
+            "2020-02".to_string(),
+            "2020-02-03".to_string(),
+            1100,
+            50,
+            1050
+        )]
+    );
+    // Months without transactions carry the net worth over
+    assert_eq!(
+        series(Some("2020-03-01"), Some("2020-04-15")).await,
+        vec![
+            (
+                "2020-03".to_string(),
+                "2020-03-31".to_string(),
+                1100,
+                50,
+                1050
+            ),
+            (
+                "2020-04".to_string(),
+                "2020-04-15".to_string(),
+                1100,
+                50,
+                1050
+            ),
+        ]
+    );
+
+    // An opening balance only counts from the day the account was opened, in every report.
+    let _ = crate::service::account::save::execute(
+        state.clone(),
+        Json(vec![AccountMeta {
+            opened_date: "2020-02-10".parse().ok(),
+            opening_balance: Some(70),
+            ..meta("Cash", AccountType::Asset)
+        }]),
+    )
+    .await
+    .expect("To save meta");
+    assert_eq!(sheet(Some("2020-02-09")).await.total_assets, 1100);
+    assert_eq!(sheet(Some("2020-02-10")).await.total_assets, 1170);
+    assert_eq!(
+        series(None, None).await[1],
+        (
+            "2020-02".to_string(),
+            "2020-02-10".to_string(),
+            1170,
+            50,
+            1120
+        )
+    );
+    let cash = |from: &str| {
+        let state = state.clone();
+        let from = from.parse().ok();
+        async move {
+            balance::execute(
+                state,
+                Json(balance::Input {
+                    from,
+                    to: "2020-02-29".parse().ok(),
+                    freq: Frequency::Monthly,
+                    accounts: sqlx_ext::Json(vec!["Cash".to_string()]),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .expect("To get balances")
+            .0
+            .into_iter()
+            .map(|r| (r.date.to_string(), r.balance))
+            .collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(
+        cash("2020-01-01").await,
+        vec![
+            ("2020-01-31".to_string(), 0),
+            ("2020-02-29".to_string(), 70)
+        ]
+    );
+    assert_eq!(
+        cash("2020-02-15").await,
+        vec![("2020-02-29".to_string(), 70)]
+    );
+}
+
+#[tokio::test]
+async fn flow_works() {
+    let state = setup().await;
+    let mut tagged = new_transaction("7", "Bank", "Expenses:Fun", 30, "2020-02-04");
+    tagged.tags = sqlx_ext::Json(vec!["holiday/beach".to_string()]);
+    let _ = transaction::save::execute(
+        state.clone(),
+        Json(vec![
+            new_transaction("6", "Savings", "Bank", 50, "2020-02-03"),
+            tagged,
+        ]),
+    )
+    .await
+    .expect("To save transactions");
+
+    let flow = |input: flow::Input| {
+        let state = state.clone();
+        async move {
+            let output = flow::execute(state, Json(input))
+                .await
+                .expect("To report")
+                .0;
+            (
+                output
+                    .edges
+                    .into_iter()
+                    .map(|e| (e.source, e.target, e.amount, e.num_transactions))
+                    .collect::<Vec<_>>(),
+                output
+                    .nodes
+                    .into_iter()
+                    .map(|n| (n.name, n.inflow, n.outflow))
+                    .collect::<Vec<_>>(),
+            )
+        }
+    };
+    let input = || flow::Input {
+        from: None,
+        to: None,
+        by: flow::FlowBy::Account,
+        depth: Some(1),
+        tags: Default::default(),
+        min_amount: 100,
+    };
+    let s = |v: &str| v.to_string();
+
+    // Savings and the bank net out, and the credit card is under the minimum
+    assert_eq!(
+        flow(input()).await,
+        (
+            vec![
+                (s("Income"), s("Bank"), 1000, 1),
+                (s("Bank"), s("Expenses"), 430, 3),
+                (s("Bank"), s("Savings"), 150, 2),
+            ],
+            vec![
+                (s("Bank"), 1000, 580),
+                (s("Expenses"), 430, 0),
+                (s("Income"), 0, 1000),
+                (s("Savings"), 150, 0),
+            ]
+        )
+    );
+
+    assert_eq!(
+        flow(flow::Input {
+            by: flow::FlowBy::Group,
+            min_amount: 0,
+            ..input()
+        })
+        .await
+        .0,
+        vec![
+            (s("Income"), s("Net worth"), 1000, 1),
+            (s("Net worth"), s("Living costs"), 480, 4),
+        ]
+    );
+
+    assert_eq!(
+        flow(flow::Input {
+            depth: None,
+            tags: sqlx_ext::Json(vec![s("holiday")]),
+            min_amount: 0,
+            ..input()
+        })
+        .await
+        .0,
+        vec![(s("Bank"), s("Expenses:Fun"), 30, 1)]
+    );
+}
+
+#[tokio::test]
+async fn bucketing_works() {
+    let state = setup().await;
+    sqlx::query(
+        "insert into configs (name, id, value) values ('client', 'fiscalYearStart', '04-01')",
+    )
+    .execute(&state.conn)
+    .await
+    .expect("To save config");
+
+    let first: NaiveDate = "2019-12-01".parse().unwrap();
+    let dates: Vec<NaiveDate> = first.iter_days().take(500).collect();
+    for freq in [
+        Frequency::Daily,
+        Frequency::Weekly,
+        Frequency::IsoWeekly,
+        Frequency::Fortnightly,
+        Frequency::Monthly,
+        Frequency::Quarterly,
+        Frequency::Yearly,
+        Frequency::FiscalYearly,
+    ] {
+        let bucketing = Bucketing::new(&state.conn, freq).await.expect("To bucket");
+        let sql = format!("select {}", bucketing.start_sql("?1"));
+        for date in &dates {
+            let (start,): (NaiveDate,) = sqlx::query_as(&sql)
+                .bind(date)
+                .fetch_one(&state.conn)
+                .await
+                .expect("To query");
+            assert_eq!(start, bucketing.start(*date), "{freq:?} of {date}");
+
+            let bucket = bucketing.bucket(start);
+            assert!(bucket.start <= *date && *date <= bucket.end);
+        }
+
+        // Buckets follow each other without gaps
+        let buckets = bucketing
+            .buckets(first, *dates.last().unwrap())
+            .expect("To bucket");
+        assert_eq!(buckets[0].start, first);
+        for pair in buckets.windows(2) {
+            assert_eq!(pair[0].end.succ_opt(), Some(pair[1].start));
+        }
+    }
+
+    // Too many buckets are refused instead of built
+    let daily = Bucketing::new(&state.conn, Frequency::Daily)
+        .await
+        .expect("To bucket");
+    assert!(matches!(
+        daily.buckets("0001-01-01".parse().unwrap(), "9999-12-31".parse().unwrap()),
+        Err(crate::service::Error::InvalidArgument(_))
+    ));
+
+    let bucket = |freq: Frequency, date: &str| {
+        let state = state.clone();
+        let date: NaiveDate = date.parse().unwrap();
+        async move {
+            let bucketing = Bucketing::new(&state.conn, freq).await.unwrap();
+            let b = bucketing.bucket(bucketing.start(date));
+            (b.time_point, b.start.to_string(), b.end.to_string())
+        }
+    };
+    let b = |time_point: &str, start: &str, end: &str| {
+        (time_point.to_string(), start.to_string(), end.to_string())
+    };
+    assert_eq!(
+        bucket(Frequency::Weekly, "2020-12-31").await,
+        b("2020-52", "2020-12-28", "2020-12-31")
+    );
+    assert_eq!(
+        bucket(Frequency::IsoWeekly, "2021-01-01").await,
+        b("2020-W53", "2020-12-28", "2021-01-03")
+    );
+    assert_eq!(
+        bucket(Frequency::Quarterly, "2020-05-17").await,
+        b("2020-Q2", "2020-04-01", "2020-06-30")
+    );
+    assert_eq!(
+        bucket(Frequency::FiscalYearly, "2021-03-31").await,
+        b("FY2021", "2020-04-01", "2021-03-31")
+    );
+
+    let output = sum::execute(
+        state.clone(),
+        Json(sum::Input {
+            from: None,
+            to: None,
+            freq: Frequency::Quarterly,
+            accounts: sqlx_ext::Json(vec!["Expenses".to_string()]),
+            account_groups: Default::default(),
+            series_by: sum::SeriesBy::None,
+            extras: Default::default(),
+        }),
+    )
+    .await
+    .expect("To sum")
+    .0;
+    assert_eq!(
+        output,
+        vec![sum::DataPoint {
+            total: 450,
+            bucket: Bucket {
+                time_point: "2020-Q1".to_string(),
+                start: "2020-01-01".parse().unwrap(),
+                end: "2020-03-31".parse().unwrap(),
+            },
+            series: None,
+            previous: None,
+            last_year: None,
+            rolling_average: None,
+            year_to_date: None,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn sum_extras_work() {
+    let state = setup().await;
+    let _ = transaction::save::execute(
+        state.clone(),
+        Json(vec![
+            new_transaction("6", "Bank", "Expenses:Food", 70, "2019-02-10"),
+            new_transaction("7", "Bank", "Expenses:Food", 20, "2019-12-05"),
+        ]),
+    )
+    .await
+    .expect("To save transactions");
+
+    let output = sum::execute(
+        state.clone(),
+        Json(sum::Input {
+            from: "2020-01-01".parse().ok(),
+            to: None,
+            freq: Frequency::Monthly,
+            accounts: sqlx_ext::Json(vec!["Expenses:Food".to_string()]),
+            account_groups: Default::default(),
+            series_by: sum::SeriesBy::None,
+            extras: sum::Extras {
+                previous: true,
+                last_year: true,
+                rolling_average: Some(3),
+                year_to_date: true,
+            },
+        }),
+    )
+    .await
+    .expect("To sum")
+    .0;
+
+    assert_eq!(
+        output
+            .into_iter()
+            .map(|p| {
+                (
+                    p.bucket.time_point,
+                    p.total,
+                    p.previous,
+                    p.last_year,
+                    p.rolling_average,
+                    p.year_to_date,
+                )
+            })
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "2020-01".to_string(),
+                100,
+                Some(20),
+                Some(0),
+                Some(40),
+                Some(100)
+            ),
+            (
+                "2020-02".to_string(),
+                50,
+                Some(100),
+                Some(70),
+                Some(57),
+                Some(150)
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn forecast_works() {
+    let state = setup().await;
+
+    let salary = |id: &str, date| {
+        let mut tx = new_transaction(id, "Income", "Bank", 500, date);
+        tx.description = format!("Salary {id}");
+        tx
+    };
+    let _ = transaction::save::execute(
+        state.clone(),
+        Json(vec![
+            salary("6", "2020-01-28"),
+            salary("7", "2020-02-28"),
+            salary("8", "2020-03-28"),
+            new_transaction("9", "Bank", "Savings", 100, "2020-04-15"),
+            transaction::model::Transaction {
+                description: "RENT".to_string(),
+                ..new_transaction("10", "Bank", "Expenses:Rent", 300, "2020-05-02")
+            },
+        ]),
+    )
+    .await
+    .expect("To save transactions");
+    let _ = crate::service::recurring::save::execute(
+        state.clone(),
+        Json(vec![RecurringTransaction {
+            id: None,
+            description: "Rent".to_string(),
+            from_account: "Bank".to_string(),
+            to_account: "Expenses:Rent".to_string(),
+            amount: 300,
+            cadence: Cadence::Monthly,
+            start_date: NaiveDate::from_ymd_opt(2020, 4, 1).unwrap(),
+            end_date: None,
+            tags: Default::default(),
+        }]),
+    )
+    .await
+    .expect("To save recurring transactions");
+
+    let date = |s: &str| s.parse::<NaiveDate>().unwrap();
+    let Json(output) = forecast::execute(
+        state.clone(),
+        Json(forecast::Input {
+            as_of: Some(date("2020-03-31")),
+            months: 2,
+            freq: Frequency::Monthly,
+            accounts: sqlx_ext::Json(vec!["Bank".to_string()]),
+            account_groups: sqlx_ext::Json(vec!["Net worth".to_string()]),
+            series_by: sum::SeriesBy::Selection,
+            include_detected: true,
+        }),
+    )
+    .await
+    .expect("To forecast");
+
+    assert_eq!(
+        output
+            .balances
+            .into_iter()
+            .map(|r| (r.series.unwrap(), r.bucket.time_point, r.balance))
+            .collect::<Vec<_>>(),
+        vec![
+            ("Bank".to_string(), "2020-03".to_string(), 1900),
+            ("Net worth".to_string(), "2020-03".to_string(), 2050),
+            ("Bank".to_string(), "2020-04".to_string(), 2000),
+            ("Net worth".to_string(), "2020-04".to_string(), 2250),
+            ("Bank".to_string(), "2020-05".to_string(), 2200),
+            ("Net worth".to_string(), "2020-05".to_string(), 2450),
+        ]
+    );
+
+    assert_eq!(
+        output
+            .items
+            .iter()
+            .map(|i| (i.date.to_string(), i.description.as_str(), &i.source))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "2020-04-01".to_string(),
+                "Rent",
+                &forecast::Source::Recurring { recurring_id: 1 }
+            ),
+            (
+                "2020-04-15".to_string(),
+                "desc",
+                &forecast::Source::Scheduled {
+                    transaction_id: "9".to_string()
+                }
+            ),
+            (
+                "2020-04-28".to_string(),
+                "Salary 8",
+                &forecast::Source::Detected
+            ),
+            (
+                // The rent already entered stands in for the recurring one
+                "2020-05-02".to_string(),
+                "RENT",
+                &forecast::Source::Scheduled {
+                    transaction_id: "10".to_string()
+                }
+            ),
+            (
+                "2020-05-28".to_string(),
+                "Salary 8",
+                &forecast::Source::Detected
+            ),
+        ]
+    );
+    // Moving money between accounts of the same series doesn't change it.
+    assert_eq!(
+        output.items[1].impacts,
+        vec![forecast::Impact {
+            series: Some("Bank".to_string()),
+            amount: -100
+        }]
+    );
+
+    assert_eq!(
+        output.lowest,
+        vec![
+            forecast::Lowest {
+                series: Some("Bank".to_string()),
+                balance: 1500,
+                date: date("2020-04-15"),
+            },
+            forecast::Lowest {
+                series: Some("Net worth".to_string()),
+                balance: 1750,
+                date: date("2020-04-01"),
+            },
+        ]
+    );
+}
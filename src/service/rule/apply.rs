@@ -0,0 +1,186 @@
+use std::collections::BTreeSet;
+
+use axum::extract::{Json, State};
+use chrono::NaiveDate;
+use sqlx::{Sqlite, Transaction};
+
+use crate::{
+    service::{GenericUpdateResponse, Result},
+    sqlx_ext,
+    state::AppState,
+};
+
+use super::model::{Actions, Rule};
+
+/// Limits the transactions rules apply to.
+#[derive(Default)]
+pub struct Scope<'a> {
+    pub ids: Option<&'a [String]>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+//language=sql
+const MATCH_SQL: &str = r#"
+select id from transactions t
+where iif(?1 is null, true, t.description regexp ?1)
+  and (?2 is null or t.amount >= ?2)
+  and (?3 is null or t.amount <= ?3)
+  and (?4 is null or trim(t.fromAccount) collate nocase in (
+      select account from account_ancestors where ancestor = trim(?4) collate nocase
+  ))
+  and (?5 is null or trim(t.toAccount) collate nocase in (
+      select account from account_ancestors where ancestor = trim(?5) collate nocase
+  ))
+  and (?6 is null or json_extract(
+      '["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"]', '$[' || strftime('%w', t.transDate) || ']'
+  ) in (select value from json_each(?6)))
+  and (?7 is null or t.id in (select value from json_each(?7)))
+  and (?8 is null or t.transDate >= ?8)
+  and (?9 is null or t.transDate <= ?9)
+order by t.transDate, t.id
+"#;
+
+//language=sql
+const UPDATE_SQL: &str = r#"
+update transactions
+set fromAccount = coalesce(trim(?1), fromAccount),
+    toAccount = coalesce(trim(?2), toAccount),
+    description = coalesce(?3, description)
+where id in (select value from json_each(?4))
+  -- A transaction can't move money from an account to itself
+  and trim(coalesce(?1, fromAccount)) != trim(coalesce(?2, toAccount)) collate nocase
+  and (fromAccount is not coalesce(trim(?1), fromAccount)
+    or toAccount is not coalesce(trim(?2), toAccount)
+    or description is not coalesce(?3, description))
+returning id
+"#;
+
+//language=sql
+const ADD_TAGS_SQL: &str = r#"
+insert or ignore into transaction_tags (transactionId, tag)
+select t.value, trim(tag.value)
+from json_each(?1) t, json_each(?2) tag
+where trim(tag.value) != ''
+returning transactionId
+"#;
+
+/// The transactions in `scope` that `rule` applies to.
+pub async fn matching(
+    tx: &mut Transaction<'_, Sqlite>,
+    rule: &Rule,
+    scope: &Scope<'_>,
+) -> Result<Vec<String>> {
+    let c = &rule.conditions;
+    let ids: Vec<(String,)> = sqlx::query_as(MATCH_SQL)
+        .bind(&c.description_regex)
+        .bind(c.min_amount)
+        .bind(c.max_amount)
+        .bind(&c.from_account)
+        .bind(&c.to_account)
+        .bind(&c.weekdays)
+        .bind(scope.ids.map(|ids| sqlx_ext::Json(ids.to_vec())))
+        .bind(scope.from)
+        .bind(scope.to)
+        .fetch_all(&mut **tx)
+        .await?;
+    Ok(ids.into_iter().map(|(id,)| id).collect())
+}
+
+/// Applies `rule` to the transactions in `scope`, returning the ids of those it changed.
+pub async fn apply(
+    tx: &mut Transaction<'_, Sqlite>,
+    rule: &Rule,
+    scope: &Scope<'_>,
+) -> Result<BTreeSet<String>> {
+    let ids = sqlx_ext::Json(matching(tx, rule, scope).await?);
+    if ids.is_empty() {
+        return Ok(Default::default());
+    }
+
+    let Actions {
+        set_from_account,
+        set_to_account,
+        set_description,
+        add_tags,
+    } = &rule.actions;
+
+    let mut changed: BTreeSet<String> = sqlx::query_as::<_, (String,)>(UPDATE_SQL)
+        .bind(set_from_account)
+        .bind(set_to_account)
+        .bind(set_description)
+        .bind(&ids)
+        .fetch_all(&mut **tx)
+        .await?
+        .into_iter()
+        .map(|(id,)| id)
+        .collect();
+
+    if let Some(tags) = add_tags {
+        changed.extend(
+            sqlx::query_as::<_, (String,)>(ADD_TAGS_SQL)
+                .bind(&ids)
+                .bind(tags)
+                .fetch_all(&mut **tx)
+                .await?
+                .into_iter()
+                .map(|(id,)| id),
+        );
+    }
+
+    Ok(changed)
+}
+
+/// Applies the given rules, or every enabled rule when `None`, in order of priority.
+pub async fn apply_all(
+    tx: &mut Transaction<'_, Sqlite>,
+    rule_ids: Option<&[i64]>,
+    scope: &Scope<'_>,
+) -> Result<BTreeSet<String>> {
+    let rules: Vec<Rule> = sqlx::query_as(
+        "select * from transaction_rules \
+         where iif(?1 is null, enabled, id in (select value from json_each(?1))) \
+         order by priority, id",
+    )
+    .bind(rule_ids.map(|ids| sqlx_ext::Json(ids.to_vec())))
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let mut changed = BTreeSet::new();
+    for rule in &rules {
+        changed.extend(apply(tx, rule, scope).await?);
+    }
+    Ok(changed)
+}
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    /// The rules to apply, or every enabled rule when left out.
+    pub rules: Option<Vec<i64>>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// Applies rules to existing transactions.
+pub async fn execute(
+    state: State<AppState>,
+    Json(Input { rules, from, to }): Json<Input>,
+) -> Result<Json<GenericUpdateResponse>> {
+    let mut tx = state.conn.begin().await?;
+    let changed = apply_all(
+        &mut tx,
+        rules.as_deref(),
+        &Scope {
+            ids: None,
+            from,
+            to,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(GenericUpdateResponse {
+        num_affected: changed.len(),
+    }
+    .into())
+}
@@ -0,0 +1,95 @@
+use axum::extract::{Json, State};
+use chrono::NaiveDate;
+use sqlx::{Sqlite, Transaction as DbTransaction};
+
+use crate::{
+    service::{transaction::model::Transaction, Result},
+    sqlx_ext,
+    state::AppState,
+};
+
+use super::{
+    apply::{apply, matching, Scope},
+    model::Rule,
+};
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    pub rule: Rule,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+const fn default_limit() -> usize {
+    100
+}
+
+#[derive(serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Change {
+    pub before: Transaction,
+    pub after: Transaction,
+}
+
+#[derive(serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Output {
+    /// How many transactions the rule would change, which may be more than `changes` holds.
+    pub num_affected: usize,
+    pub changes: Vec<Change>,
+}
+
+async fn transactions(
+    tx: &mut DbTransaction<'_, Sqlite>,
+    ids: &[String],
+) -> Result<Vec<Transaction>> {
+    Ok(sqlx::query_as(
+        "select * from transactions_view \
+         where id in (select value from json_each(?)) \
+         order by transDate, id",
+    )
+    .bind(sqlx_ext::Json(ids.to_vec()))
+    .fetch_all(&mut **tx)
+    .await?)
+}
+
+/// Shows which existing transactions a rule would change, and how, without saving anything.
+pub async fn execute(
+    state: State<AppState>,
+    Json(Input {
+        rule,
+        from,
+        to,
+        limit,
+    }): Json<Input>,
+) -> Result<Json<Output>> {
+    let mut tx = state.conn.begin().await?;
+    super::save::validate(&mut tx, &rule).await?;
+
+    let scope = Scope {
+        ids: None,
+        from,
+        to,
+    };
+    let ids = matching(&mut tx, &rule, &scope).await?;
+    let before = transactions(&mut tx, &ids).await?;
+    let changed = apply(&mut tx, &rule, &scope).await?;
+    let after = transactions(&mut tx, &ids).await?;
+    tx.rollback().await?;
+
+    let changes = before
+        .into_iter()
+        .zip(after)
+        .filter(|(before, _)| changed.contains(&before.id))
+        .take(limit)
+        .map(|(before, after)| Change { before, after })
+        .collect();
+
+    Ok(Json::from(Output {
+        num_affected: changed.len(),
+        changes,
+    }))
+}
@@ -0,0 +1,12 @@
+use axum::extract::{Json, State};
+
+use crate::{service::Result, state::AppState};
+
+use super::model::Rule;
+
+pub async fn execute(state: State<AppState>) -> Result<Json<Vec<Rule>>> {
+    let rules = sqlx::query_as("select * from transaction_rules order by priority, id")
+        .fetch_all(&state.conn)
+        .await?;
+    Ok(Json::from(rules))
+}
@@ -0,0 +1,28 @@
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
+
+use crate::state::AppState;
+
+pub mod apply;
+mod delete;
+pub mod dry_run;
+mod list;
+pub mod model;
+pub mod save;
+
+#[cfg(test)]
+mod test;
+
+pub fn router() -> Router<AppState> {
+    Router::new().nest(
+        "/api/rules",
+        Router::new()
+            .route("/", get(list::execute))
+            .route("/", post(save::execute))
+            .route("/", delete(delete::execute))
+            .route("/test", post(dry_run::execute))
+            .route("/apply", post(apply::execute)),
+    )
+}
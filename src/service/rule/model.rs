@@ -0,0 +1,67 @@
+use chrono::Weekday;
+
+use crate::sqlx_ext::Json;
+
+/// Which transactions a rule applies to. Every condition given must hold.
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, sqlx::FromRow, serde::Serialize, serde::Deserialize,
+)]
+#[sqlx(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct Conditions {
+    pub description_regex: Option<String>,
+    pub min_amount: Option<i64>,
+    pub max_amount: Option<i64>,
+    /// Matches the account and the accounts under it.
+    pub from_account: Option<String>,
+    /// Matches the account and the accounts under it.
+    pub to_account: Option<String>,
+    pub weekdays: Option<Json<Vec<Weekday>>>,
+}
+
+/// What a rule changes on the transactions it applies to.
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, sqlx::FromRow, serde::Serialize, serde::Deserialize,
+)]
+#[sqlx(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct Actions {
+    pub set_from_account: Option<String>,
+    pub set_to_account: Option<String>,
+    pub set_description: Option<String>,
+    pub add_tags: Option<Json<Vec<String>>>,
+}
+
+impl Actions {
+    pub fn is_empty(&self) -> bool {
+        self.set_from_account.is_none()
+            && self.set_to_account.is_none()
+            && self.set_description.is_none()
+            && self.add_tags.as_ref().is_none_or(|t| t.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+#[sqlx(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    /// Assigned on saving a new rule.
+    #[serde(default)]
+    pub id: Option<i64>,
+    pub name: String,
+    /// Rules apply from the lowest priority up, so higher ones have the last word.
+    #[serde(default)]
+    pub priority: i64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub conditions: Conditions,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub actions: Actions,
+}
+
+const fn default_enabled() -> bool {
+    true
+}
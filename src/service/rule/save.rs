@@ -0,0 +1,106 @@
+use std::borrow::Cow;
+
+use axum::extract::{Json, State};
+use sqlx::{Sqlite, Transaction};
+
+use crate::{
+    service::{Error, GenericUpdateResponse, Result},
+    state::AppState,
+};
+
+use super::model::Rule;
+
+//language=sql
+const SQL: &str = r#"
+insert into transaction_rules (
+    id, name, priority, enabled,
+    descriptionRegex, minAmount, maxAmount, fromAccount, toAccount, weekdays,
+    setFromAccount, setToAccount, setDescription, addTags
+)
+values (?1, trim(?2), ?3, ?4, ?5, ?6, ?7, trim(?8), trim(?9), ?10, trim(?11), trim(?12), ?13, ?14)
+on conflict (id) do update set
+    name = excluded.name,
+    priority = excluded.priority,
+    enabled = excluded.enabled,
+    descriptionRegex = excluded.descriptionRegex,
+    minAmount = excluded.minAmount,
+    maxAmount = excluded.maxAmount,
+    fromAccount = excluded.fromAccount,
+    toAccount = excluded.toAccount,
+    weekdays = excluded.weekdays,
+    setFromAccount = excluded.setFromAccount,
+    setToAccount = excluded.setToAccount,
+    setDescription = excluded.setDescription,
+    addTags = excluded.addTags
+returning id
+"#;
+
+pub async fn validate(tx: &mut Transaction<'_, Sqlite>, rule: &Rule) -> Result<()> {
+    if rule.actions.is_empty() {
+        return Err(Error::InvalidArgument(Cow::Owned(format!(
+            "Rule {} does nothing",
+            rule.name
+        ))));
+    }
+
+    if let (Some(from), Some(to)) = (&rule.actions.set_from_account, &rule.actions.set_to_account) {
+        if from.trim().eq_ignore_ascii_case(to.trim()) {
+            return Err(Error::InvalidArgument(Cow::Owned(format!(
+                "Rule {} moves money from {from} to itself",
+                rule.name
+            ))));
+        }
+    }
+
+    if let Some(regex) = &rule.conditions.description_regex {
+        if sqlx::query("select '' regexp ?")
+            .bind(regex)
+            .execute(&mut **tx)
+            .await
+            .is_err()
+        {
+            return Err(Error::InvalidArgument(Cow::Owned(format!(
+                "Invalid regular expression {regex}"
+            ))));
+        }
+    }
+
+    Ok(())
+}
+
+/// Saves the rules, returning their ids in the same order.
+pub async fn save(tx: &mut Transaction<'_, Sqlite>, rules: Vec<Rule>) -> Result<Vec<i64>> {
+    let mut ids = Vec::with_capacity(rules.len());
+    for rule in rules {
+        validate(tx, &rule).await?;
+        let (id,): (i64,) = sqlx::query_as(SQL)
+            .bind(rule.id)
+            .bind(rule.name)
+            .bind(rule.priority)
+            .bind(rule.enabled)
+            .bind(rule.conditions.description_regex)
+            .bind(rule.conditions.min_amount)
+            .bind(rule.conditions.max_amount)
+            .bind(rule.conditions.from_account)
+            .bind(rule.conditions.to_account)
+            .bind(rule.conditions.weekdays)
+            .bind(rule.actions.set_from_account)
+            .bind(rule.actions.set_to_account)
+            .bind(rule.actions.set_description)
+            .bind(rule.actions.add_tags)
+            .fetch_one(&mut **tx)
+            .await?;
+        ids.push(id);
+    }
+    Ok(ids)
+}
+
+pub async fn execute(
+    state: State<AppState>,
+    Json(rules): Json<Vec<Rule>>,
+) -> Result<Json<GenericUpdateResponse>> {
+    let mut tx = state.conn.begin().await?;
+    let num_affected = save(&mut tx, rules).await?.len();
+    tx.commit().await?;
+    Ok(GenericUpdateResponse { num_affected }.into())
+}
@@ -0,0 +1,224 @@
+use axum::extract::{Json, State};
+use chrono::Weekday;
+
+use crate::service::{account::test::new_transaction, transaction};
+use crate::sqlx_ext;
+use crate::state::AppState;
+
+use super::model::{Actions, Conditions, Rule};
+use super::*;
+
+fn rule(name: &str, priority: i64, conditions: Conditions, actions: Actions) -> Rule {
+    Rule {
+        id: None,
+        name: name.to_string(),
+        priority,
+        enabled: true,
+        conditions,
+        actions,
+    }
+}
+
+fn tags(tags: &[&str]) -> Option<sqlx_ext::Json<Vec<String>>> {
+    Some(sqlx_ext::Json(tags.iter().map(|t| t.to_string()).collect()))
+}
+
+async fn transactions(state: &State<AppState>) -> Vec<(String, String, String, String)> {
+    sqlx::query_as("select id, description, toAccount, tags from transactions_view order by id")
+        .fetch_all(&state.conn)
+        .await
+        .expect("To list transactions")
+}
+
+#[tokio::test]
+async fn rules_work() {
+    let state = State(AppState::new_test().await);
+
+    let _ = save::execute(
+        state.clone(),
+        Json(vec![
+            rule(
+                "Weekend",
+                1,
+                Conditions {
+                    to_account: Some("Expenses".to_string()),
+                    weekdays: Some(sqlx_ext::Json(vec![Weekday::Sat, Weekday::Sun])),
+                    ..Default::default()
+                },
+                Actions {
+                    add_tags: tags(&["weekend"]),
+                    ..Default::default()
+                },
+            ),
+            rule(
+                "Coffee",
+                0,
+                Conditions {
+                    description_regex: Some("(?i)coffee".to_string()),
+                    max_amount: Some(1000),
+                    ..Default::default()
+                },
+                Actions {
+                    set_to_account: Some("Expenses:Coffee".to_string()),
+                    add_tags: tags(&["coffee"]),
+                    ..Default::default()
+                },
+            ),
+        ]),
+    )
+    .await
+    .expect("To save rules");
+
+    let described = |id: &str, desc: &str, amount: i64, date: &str| {
+        let mut tx = new_transaction(id, "Bank", "Expenses", amount, date);
+        tx.description = desc.to_string();
+        tx
+    };
+    let _ = transaction::save::execute(
+        state.clone(),
+        Json(vec![
+            described("1", "Coffee shop", 500, "2020-01-04"),
+            described("2", "Groceries", 500, "2020-01-06"),
+            described("3", "Coffee beans", 5000, "2020-01-06"),
+        ]),
+    )
+    .await
+    .expect("To save transactions");
+
+    // The coffee rule runs first, so the weekend rule sees the new account under Expenses.
+    let labelled = vec![
+        (
+            "1".to_string(),
+            "Coffee shop".to_string(),
+            "Expenses:Coffee".to_string(),
+            r#"["coffee","weekend"]"#.to_string(),
+        ),
+        (
+            "2".to_string(),
+            "Groceries".to_string(),
+            "Expenses".to_string(),
+            "[]".to_string(),
+        ),
+        (
+            "3".to_string(),
+            "Coffee beans".to_string(),
+            "Expenses".to_string(),
+            "[]".to_string(),
+        ),
+    ];
+    assert_eq!(transactions(&state).await, labelled);
+
+    // Rules run again when a transaction is edited
+    let edited = described("1", "Coffee shop", 500, "2020-01-04");
+    let _ = transaction::save::execute(state.clone(), Json(vec![edited]))
+        .await
+        .expect("To save transaction");
+    assert_eq!(transactions(&state).await, labelled);
+
+    // Testing a rule shows the changes without making them
+    let Json(output) = dry_run::execute(
+        state.clone(),
+        Json(dry_run::Input {
+            rule: rule(
+                "Supermarket",
+                0,
+                Conditions {
+                    description_regex: Some("^Groceries$".to_string()),
+                    ..Default::default()
+                },
+                Actions {
+                    set_description: Some("Supermarket".to_string()),
+                    ..Default::default()
+                },
+            ),
+            from: None,
+            to: None,
+            limit: 10,
+        }),
+    )
+    .await
+    .expect("To test rule");
+    assert_eq!(output.num_affected, 1);
+    assert_eq!(output.changes[0].before.description, "Groceries");
+    assert_eq!(output.changes[0].after.description, "Supermarket");
+    assert_eq!(transactions(&state).await[1].1, "Groceries");
+
+    // Applying to history brings a transaction saved before the rules back in line
+    sqlx::query("update transactions set toAccount = 'Expenses' where id = '1'")
+        .execute(&state.conn)
+        .await
+        .expect("To update transaction");
+    let Json(output) = apply::execute(
+        state.clone(),
+        Json(apply::Input {
+            from: "2020-01-01".parse().ok(),
+            ..Default::default()
+        }),
+    )
+    .await
+    .expect("To apply rules");
+    assert_eq!(output.num_affected, 1);
+    assert_eq!(transactions(&state).await, labelled);
+
+    assert!(save::execute(
+        state.clone(),
+        Json(vec![rule(
+            "Broken",
+            0,
+            Conditions {
+                description_regex: Some("(".to_string()),
+                ..Default::default()
+            },
+            Actions {
+                add_tags: tags(&["x"]),
+                ..Default::default()
+            },
+        )]),
+    )
+    .await
+    .is_err());
+
+    let _ = router();
+}
+
+#[tokio::test]
+async fn rules_never_move_money_to_the_same_account() {
+    let state = State(AppState::new_test().await);
+
+    let to_bank = Actions {
+        set_to_account: Some(" bank ".to_string()),
+        ..Default::default()
+    };
+    let _ = save::execute(
+        state.clone(),
+        Json(vec![rule("Refunds", 0, Conditions::default(), to_bank)]),
+    )
+    .await
+    .expect("To save rule");
+
+    // The rule would turn the first one into a transfer from Bank to itself, so it is skipped.
+    let _ = transaction::save::execute(
+        state.clone(),
+        Json(vec![
+            new_transaction("1", "Bank", "Expenses", 100, "2020-01-01"),
+            new_transaction("2", "Shop", "Expenses", 100, "2020-01-01"),
+        ]),
+    )
+    .await
+    .expect("To save transactions");
+    let saved = transactions(&state).await;
+    assert_eq!(saved[0].2, "Expenses");
+    assert_eq!(saved[1].2, "bank");
+
+    let both = Actions {
+        set_from_account: Some("Bank".to_string()),
+        set_to_account: Some("BANK".to_string()),
+        ..Default::default()
+    };
+    assert!(save::execute(
+        state.clone(),
+        Json(vec![rule("Loop", 0, Conditions::default(), both)])
+    )
+    .await
+    .is_err());
+}
@@ -0,0 +1,34 @@
+use axum::extract::{Json, State};
+
+use crate::{
+    service::{GenericUpdateResponse, Result},
+    sqlx_ext,
+    state::AppState,
+};
+
+pub type Input = Vec<String>;
+
+/// Removes the given tags from every transaction, along with their metadata.
+pub async fn execute(
+    state: State<AppState>,
+    Json(names): Json<Input>,
+) -> Result<Json<GenericUpdateResponse>> {
+    let names = sqlx_ext::Json(names);
+    let mut tx = state.conn.begin().await?;
+
+    let output: GenericUpdateResponse = sqlx::query(
+        "delete from transaction_tags where tag collate nocase in (select trim(value) from json_each(?))",
+    )
+    .bind(&names)
+    .execute(&mut *tx)
+    .await?
+    .into();
+
+    sqlx::query("delete from tags where name in (select trim(value) from json_each(?))")
+        .bind(&names)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(output.into())
+}
@@ -7,31 +7,39 @@ use sqlx::FromRow;
 
 use crate::{
     bind_sqlite_args,
-    service::{self, query::create_paginated_query, PaginatedResponse, Result, SortOrder, ToSQL},
+    service::{
+        self,
+        query::{create_paginated_query, Page, PageRequest},
+        PaginatedResponse, Result, SortOrder, ToSQL,
+    },
     state::AppState,
 };
 
 #[derive(Deserialize, Clone, Copy, Debug)]
 #[serde(rename_all = "camelCase")]
-enum SortField {
+pub enum SortField {
     Tag,
     NumTx,
     Total,
     LastUpdated,
 }
 
-type Sort = service::Sort<SortField>;
+pub type Sort = service::Sort<SortField>;
 
-#[derive(Deserialize, Default)]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Input {
     #[serde(default = "default_sorts")]
-    sorts: Cow<'static, [Sort]>,
+    pub sorts: Cow<'static, [Sort]>,
 
     #[serde(default = "default_limit")]
-    limit: i64,
+    pub limit: i64,
     #[serde(default)]
-    offset: i64,
-    q: Option<String>,
+    pub offset: i64,
+    pub cursor: Option<String>,
+    #[serde(default = "default_with_total")]
+    pub with_total: bool,
+    pub q: Option<String>,
 }
 
 const DEFAULT_SORTS: &[Sort] = &[
@@ -40,13 +48,30 @@ const DEFAULT_SORTS: &[Sort] = &[
 ];
 
 const fn default_sorts() -> Cow<'static, [Sort]> {
-    return Cow::Borrowed(DEFAULT_SORTS);
+    Cow::Borrowed(DEFAULT_SORTS)
 }
 
 const fn default_limit() -> i64 {
     50
 }
 
+const fn default_with_total() -> bool {
+    true
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Self {
+            sorts: default_sorts(),
+            limit: default_limit(),
+            offset: Default::default(),
+            cursor: Default::default(),
+            with_total: default_with_total(),
+            q: Default::default(),
+        }
+    }
+}
+
 impl ToSQL for SortField {
     fn to_sql(&self) -> Option<&str> {
         Some(match self {
@@ -62,19 +87,34 @@ impl ToSQL for SortField {
 #[sqlx(rename_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub struct Tag {
-    tag: String,
-    num_tx: i64,
-    total: i64,
-    last_updated: DateTime<Utc>,
+    pub tag: String,
+    pub num_tx: i64,
+    pub total: i64,
+    /// When a transaction with the tag was last updated, or `None` if none has it.
+    pub last_updated: Option<DateTime<Utc>>,
+    pub description: Option<String>,
+    pub colour: Option<String>,
+    pub archived: bool,
 }
 
 //language=sql
 const SQL: &str = r#"
-select tag, count(transactionId) as numTx, sum(amount) as total, max(t.updatedDate) as lastUpdated
-from transaction_tags
-inner join transactions t on t.id = transactionId
-where (?1 is null or trim(?1) = '' or tag like '%' || trim(?1) || '%' collate nocase)
-group by tag
+with usage(tag, numTx, total, lastUpdated) as (
+    select tag, count(transactionId), sum(amount), max(t.updatedDate)
+    from transaction_tags
+    inner join transactions t on t.id = transactionId
+    group by tag
+)
+select coalesce(u.tag, m.name) as tag,
+       coalesce(u.numTx, 0) as numTx,
+       coalesce(u.total, 0) as total,
+       u.lastUpdated,
+       m.description,
+       m.colour,
+       coalesce(m.archived, false) as archived
+from usage u
+full outer join tags m on m.name = u.tag
+where (?1 is null or trim(?1) = '' or coalesce(u.tag, m.name) like '%' || trim(?1) || '%' collate nocase)
 "#;
 
 pub async fn execute(
@@ -83,18 +123,33 @@ pub async fn execute(
         sorts,
         limit,
         offset,
+        cursor,
+        with_total,
         q,
     }): Json<Input>,
 ) -> Result<Json<PaginatedResponse<Tag>>> {
-    let (data, (total,)) = create_paginated_query(
+    let Page {
+        items,
+        aggregates,
+        next_cursor,
+    } = create_paginated_query::<_, _, (i64,)>(
         &state.conn,
         SQL,
         bind_sqlite_args!(q),
-        limit,
-        offset,
-        sorts,
-        "COUNT(*)",
+        PageRequest {
+            limit,
+            offset,
+            cursor: cursor.as_deref(),
+            sorts: &sorts,
+            key: "tag",
+            aggregate_select: with_total.then_some("COUNT(*)"),
+        },
     )
     .await?;
-    Ok(PaginatedResponse { data, total }.into())
+    Ok(PaginatedResponse {
+        data: items,
+        total: aggregates.map(|(total,)| total),
+        next_cursor,
+    }
+    .into())
 }
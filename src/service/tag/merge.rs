@@ -0,0 +1,134 @@
+use axum::extract::{Json, State};
+use sqlx::{Sqlite, Transaction};
+
+use crate::{
+    service::{merge, Result},
+    state::AppState,
+};
+
+use super::model::TagMeta;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    pub sources: Vec<String>,
+    pub target: String,
+}
+
+/// The number of rows rewritten in each table.
+#[derive(serde::Serialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Output {
+    pub transaction_tags: u64,
+    pub account_group_rules: u64,
+    pub rules: u64,
+    pub recurring_transactions: u64,
+    pub tags: u64,
+}
+
+//language=sql
+const TRANSACTION_TAGS_SQL: &str = r#"
+update or ignore transaction_tags
+set tag = trim(?2)
+where tag collate nocase in (select trim(value) from json_each(?1));
+
+delete from transaction_tags
+where tag collate nocase in (select trim(value) from json_each(?1))
+  and tag != trim(?2);
+"#;
+
+//language=sql
+const ACCOUNT_GROUP_RULES_SQL: &str = r#"
+update or ignore account_group_rules
+set value = trim(?2)
+where ruleType = 'tag'
+  and value collate nocase in (select trim(value) from json_each(?1));
+
+delete from account_group_rules
+where ruleType = 'tag'
+  and value collate nocase in (select trim(value) from json_each(?1))
+  and value != trim(?2);
+"#;
+
+//language=sql
+const RULES_SQL: &str = r#"
+with sources(name) as (select trim(value) from json_each(?1))
+update transaction_rules
+set addTags = (
+    select json_group_array(distinct iif(trim(t.value) collate nocase in sources, trim(?2), t.value))
+    from json_each(addTags) t
+)
+where exists (select 1 from json_each(addTags) t where trim(t.value) collate nocase in sources)
+"#;
+
+//language=sql
+const RECURRING_TRANSACTIONS_SQL: &str = r#"
+with sources(name) as (select trim(value) from json_each(?1))
+update recurring_transactions
+set tags = (
+    select json_group_array(distinct iif(trim(t.value) collate nocase in sources, trim(?2), t.value))
+    from json_each(tags) t
+)
+where exists (select 1 from json_each(tags) t where trim(t.value) collate nocase in sources)
+"#;
+
+/// Rewrites every use of the `sources` tags into `target`, within `tx`.
+pub async fn merge_into(
+    tx: &mut Transaction<'_, Sqlite>,
+    sources: &[String],
+    target: &str,
+) -> Result<Output> {
+    let (sources, target) = merge::names("tag", sources, target)?;
+
+    let mut output = Output {
+        transaction_tags: sqlx::query(TRANSACTION_TAGS_SQL)
+            .bind(&sources)
+            .bind(target)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected(),
+        account_group_rules: sqlx::query(ACCOUNT_GROUP_RULES_SQL)
+            .bind(&sources)
+            .bind(target)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected(),
+        rules: sqlx::query(RULES_SQL)
+            .bind(&sources)
+            .bind(target)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected(),
+        recurring_transactions: sqlx::query(RECURRING_TRANSACTIONS_SQL)
+            .bind(&sources)
+            .bind(target)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected(),
+        ..Default::default()
+    };
+
+    let (taken, metas): (_, Vec<TagMeta>) = merge::take_metas(tx, "tags", &sources, target).await?;
+    output.tags = taken;
+    if let Some(first) = metas.first() {
+        sqlx::query("insert into tags (name, description, colour, archived) values (?, ?, ?, ?)")
+            .bind(target)
+            .bind(&first.description)
+            .bind(&first.colour)
+            .bind(first.archived)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    Ok(output)
+}
+
+pub async fn execute(
+    state: State<AppState>,
+    Json(Input { sources, target }): Json<Input>,
+) -> Result<Json<Output>> {
+    let mut tx = state.conn.begin().await?;
+    let output = merge_into(&mut tx, &sources, &target).await?;
+    tx.commit().await?;
+    Ok(output.into())
+}
@@ -1,9 +1,28 @@
-use axum::{routing::post, Router};
+use axum::{
+    routing::{delete, post},
+    Router,
+};
 
 use crate::state::AppState;
 
+mod delete;
 mod list;
+mod merge;
+pub mod model;
+mod rename;
+mod save;
+
+#[cfg(test)]
+mod test;
 
 pub fn router() -> Router<AppState> {
-    Router::new().route("/api/tags/list", post(list::execute))
+    Router::new().nest(
+        "/api/tags",
+        Router::new()
+            .route("/", post(save::execute))
+            .route("/", delete(delete::execute))
+            .route("/list", post(list::execute))
+            .route("/rename", post(rename::execute))
+            .route("/merge", post(merge::execute)),
+    )
 }
@@ -0,0 +1,12 @@
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+#[sqlx(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct TagMeta {
+    pub name: String,
+    pub description: Option<String>,
+    /// A CSS colour for showing the tag, e.g. `#ff8800`.
+    pub colour: Option<String>,
+    /// Archived tags are kept on their transactions, but no longer offered for new ones.
+    #[serde(default)]
+    pub archived: bool,
+}
@@ -0,0 +1,36 @@
+use axum::extract::{Json, State};
+
+use crate::{
+    service::{merge, Result},
+    state::AppState,
+};
+
+use super::merge::{merge_into, Output};
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    pub from: String,
+    pub to: String,
+}
+
+pub async fn execute(
+    state: State<AppState>,
+    Json(Input { from, to }): Json<Input>,
+) -> Result<Json<Output>> {
+    let mut tx = state.conn.begin().await?;
+
+    merge::check_rename(
+        &mut tx,
+        "Tag",
+        "select exists (select 1 from transaction_tags where tag = ?1 collate nocase) \
+         or exists (select 1 from tags where name = ?1)",
+        &from,
+        &to,
+    )
+    .await?;
+
+    let output = merge_into(&mut tx, &[from], &to).await?;
+    tx.commit().await?;
+    Ok(output.into())
+}
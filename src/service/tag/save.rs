@@ -0,0 +1,44 @@
+use axum::extract::{Json, State};
+
+use crate::{
+    service::{GenericUpdateResponse, Result},
+    state::AppState,
+};
+
+use super::model::TagMeta;
+
+//language=sql
+const SQL: &str = r#"
+insert or replace into tags (name, description, colour, archived)
+values (trim(?), ?, trim(?), ?)
+"#;
+
+pub async fn execute(
+    state: State<AppState>,
+    Json(tags): Json<Vec<TagMeta>>,
+) -> Result<Json<GenericUpdateResponse>> {
+    let mut tx = state.conn.begin().await?;
+    let mut num_affected: u64 = 0;
+
+    for TagMeta {
+        name,
+        description,
+        colour,
+        archived,
+    } in tags
+    {
+        num_affected += sqlx::query(SQL)
+            .bind(name)
+            .bind(description)
+            .bind(colour)
+            .bind(archived)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    }
+
+    tx.commit().await?;
+    Ok(Json::from(GenericUpdateResponse {
+        num_affected: num_affected as usize,
+    }))
+}
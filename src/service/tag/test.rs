@@ -0,0 +1,356 @@
+use axum::extract::{Json, State};
+
+use crate::service::{account::test::new_transaction, transaction};
+use crate::sqlx_ext;
+use crate::state::AppState;
+
+use super::*;
+
+async fn tags(state: &State<AppState>) -> Vec<list::Tag> {
+    list::execute(state.clone(), Json(Default::default()))
+        .await
+        .expect("To list tags")
+        .0
+        .data
+}
+
+async fn transaction_tags(state: &State<AppState>) -> Vec<(String, String)> {
+    sqlx::query_as("select transactionId, tag from transaction_tags order by transactionId, tag")
+        .fetch_all(&state.conn)
+        .await
+        .expect("To list transaction tags")
+}
+
+#[tokio::test]
+async fn tag_management_works() {
+    let state = State(AppState::new_test().await);
+    let tagged = |id: &str, tags: &[&str]| {
+        let mut tx = new_transaction(id, "Bank", "Expenses", 100, "2020-01-01");
+        tx.tags = sqlx_ext::Json(tags.iter().map(|t| t.to_string()).collect());
+        tx
+    };
+    let _ = transaction::save::execute(
+        state.clone(),
+        Json(vec![
+            tagged("1", &["food", "work"]),
+            tagged("2", &["Food", "groceries"]),
+            tagged("3", &["groceries"]),
+        ]),
+    )
+    .await
+    .expect("To save transactions");
+
+    let meta = |name: &str, description: &str| model::TagMeta {
+        name: name.to_string(),
+        description: Some(description.to_string()),
+        colour: Some("#ff8800".to_string()),
+        archived: false,
+    };
+    let _ = save::execute(
+        state.clone(),
+        Json(vec![
+            meta("groceries", "Supermarkets"),
+            meta("Holiday", "Trips"),
+        ]),
+    )
+    .await
+    .expect("To save metadata");
+
+    let output = tags(&state).await;
+    let holiday = output
+        .iter()
+        .find(|t| t.tag == "Holiday")
+        .expect("To list unused tags");
+    assert_eq!(holiday.num_tx, 0);
+    assert_eq!(holiday.last_updated, None);
+    let groceries = output
+        .iter()
+        .find(|t| t.tag == "groceries")
+        .expect("To list groceries");
+    assert_eq!(groceries.num_tx, 2);
+    assert_eq!(groceries.description.as_deref(), Some("Supermarkets"));
+
+    // Renaming onto an existing tag is refused
+    assert!(rename::execute(
+        state.clone(),
+        Json(rename::Input {
+            from: "work".to_string(),
+            to: "FOOD".to_string(),
+        }),
+    )
+    .await
+    .is_err());
+    assert!(rename::execute(
+        state.clone(),
+        Json(rename::Input {
+            from: "nope".to_string(),
+            to: "other".to_string(),
+        }),
+    )
+    .await
+    .is_err());
+
+    let output = rename::execute(
+        state.clone(),
+        Json(rename::Input {
+            from: "GROCERIES".to_string(),
+            to: "Supermarket".to_string(),
+        }),
+    )
+    .await
+    .expect("To rename")
+    .0;
+    assert_eq!(output.transaction_tags, 2);
+    assert_eq!(output.tags, 1);
+
+    sqlx::query(
+        "insert into transaction_rules (name, addTags) values ('Shopping', '[\"food\", \"work\", \"Supermarket\"]'); \
+         insert into recurring_transactions (description, fromAccount, toAccount, amount, cadence, startDate, tags) \
+         values ('Shopping', 'Bank', 'Expenses', 100, 'weekly', '2020-01-01', '[\"supermarket\"]')",
+    )
+    .execute(&state.conn)
+    .await
+    .expect("To add rule");
+
+    // Merging keeps one tag per transaction
+    let _ = merge::execute(
+        state.clone(),
+        Json(merge::Input {
+            sources: vec!["food".to_string(), "Supermarket".to_string()],
+            target: "Food".to_string(),
+        }),
+    )
+    .await
+    .expect("To merge");
+    assert_eq!(
+        transaction_tags(&state).await,
+        vec![
+            ("1".to_string(), "Food".to_string()),
+            ("1".to_string(), "work".to_string()),
+            ("2".to_string(), "Food".to_string()),
+            ("3".to_string(), "Food".to_string()),
+        ]
+    );
+    let (add_tags,): (String,) = sqlx::query_as("select addTags from transaction_rules")
+        .fetch_one(&state.conn)
+        .await
+        .expect("To get rule");
+    assert_eq!(add_tags, r#"["Food","work"]"#);
+    let (recurring_tags,): (String,) = sqlx::query_as("select tags from recurring_transactions")
+        .fetch_one(&state.conn)
+        .await
+        .expect("To get recurring transaction");
+    assert_eq!(recurring_tags, r#"["Food"]"#);
+
+    let food = tags(&state)
+        .await
+        .into_iter()
+        .find(|t| t.tag == "Food")
+        .expect("To list food");
+    assert_eq!(food.num_tx, 3);
+    assert_eq!(food.description.as_deref(), Some("Supermarkets"));
+
+    let output = delete::execute(state.clone(), Json(vec!["FOOD".to_string()]))
+        .await
+        .expect("To delete")
+        .0;
+    assert_eq!(output.num_affected, 3);
+    assert_eq!(
+        transaction_tags(&state).await,
+        vec![("1".to_string(), "work".to_string())]
+    );
+    assert_eq!(
+        tags(&state)
+            .await
+            .into_iter()
+            .map(|t| t.tag)
+            .collect::<Vec<_>>(),
+        vec!["Holiday".to_string(), "work".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn tag_hierarchy_works() {
+    use crate::service::report::{self, Frequency};
+
+    let state = State(AppState::new_test().await);
+    let tagged = |id: &str, amount: i64, date: &str, tags: &[&str]| {
+        let mut tx = new_transaction(id, "Bank", "Expenses", amount, date);
+        tx.tags = sqlx_ext::Json(tags.iter().map(|t| t.to_string()).collect());
+        tx
+    };
+    let _ = transaction::save::execute(
+        state.clone(),
+        Json(vec![
+            tagged("1", 100, "2024-01-05", &["trip/japan-2024/food"]),
+            tagged(
+                "2",
+                200,
+                "2024-01-06",
+                &["trip/japan-2024/food", "Trip/Japan-2024/transport"],
+            ),
+            tagged("3", 400, "2024-02-01", &["trip/nz"]),
+            tagged("4", 800, "2024-02-02", &["tripod"]),
+        ]),
+    )
+    .await
+    .expect("To save transactions");
+
+    let ids = |output: transaction::list::Output| {
+        let mut ids = output.data.into_iter().map(|t| t.id).collect::<Vec<_>>();
+        ids.sort();
+        ids
+    };
+    let output = transaction::list::execute(
+        state.clone(),
+        Json(transaction::list::Input {
+            tags: Some(sqlx_ext::Json(vec!["TRIP".to_string()])),
+            ..Default::default()
+        }),
+    )
+    .await
+    .expect("To list")
+    .0;
+    assert_eq!(ids(output), vec!["1", "2", "3"]);
+
+    let output = transaction::list::execute(
+        state.clone(),
+        Json(transaction::list::Input {
+            query: Some("tag:trip/japan-2024 -tag:trip/japan-2024/transport".to_string()),
+            ..Default::default()
+        }),
+    )
+    .await
+    .expect("To list")
+    .0;
+    assert_eq!(ids(output), vec!["1"]);
+
+    let output = report::tags::execute(
+        state.clone(),
+        Json(report::tags::Input {
+            from: None,
+            to: None,
+            freq: Frequency::Monthly,
+            tags: sqlx_ext::Json(vec!["trip/japan-2024".to_string()]),
+        }),
+    )
+    .await
+    .expect("To report")
+    .0;
+    assert_eq!(
+        output
+            .into_iter()
+            .map(|p| (p.tag, p.bucket.time_point, p.total, p.num_tx))
+            .collect::<Vec<_>>(),
+        vec![
+            ("Trip/Japan-2024".to_string(), "2024-01".to_string(), 200, 1),
+            (
+                "Trip/Japan-2024/transport".to_string(),
+                "2024-01".to_string(),
+                200,
+                1
+            ),
+            ("trip/japan-2024".to_string(), "2024-01".to_string(), 300, 2),
+            (
+                "trip/japan-2024/food".to_string(),
+                "2024-01".to_string(),
+                300,
+                2
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn tag_list_by_cursor_works() {
+    use crate::service::SortOrder;
+    use std::borrow::Cow;
+
+    let state = State(AppState::new_test().await);
+    let tagged = |id: &str, tags: &[&str]| {
+        let mut tx = new_transaction(id, "Bank", "Expenses", 100, "2020-01-01");
+        tx.tags = sqlx_ext::Json(tags.iter().map(|t| t.to_string()).collect());
+        tx
+    };
+    let _ = transaction::save::execute(
+        state.clone(),
+        Json(vec![
+            tagged("1", &["food", "work"]),
+            tagged("2", &["food", "groceries"]),
+            tagged("3", &["groceries", "car"]),
+            tagged("4", &["rent"]),
+        ]),
+    )
+    .await
+    .expect("To save transactions");
+    // Tags with metadata only come from the other side of the join
+    let meta = |name: &str| model::TagMeta {
+        name: name.to_string(),
+        description: None,
+        colour: None,
+        archived: false,
+    };
+    let _ = save::execute(
+        state.clone(),
+        Json(vec![meta("Holiday"), meta("garden"), meta("food")]),
+    )
+    .await
+    .expect("To save metadata");
+
+    for sorts in [
+        list::Input::default().sorts,
+        Cow::Owned(vec![list::Sort::new(
+            list::SortField::NumTx,
+            SortOrder::DESC,
+        )]),
+    ] {
+        let Json(all) = list::execute(
+            state.clone(),
+            Json(list::Input {
+                sorts: sorts.clone(),
+                ..Default::default()
+            }),
+        )
+        .await
+        .expect("To list tags");
+        assert_eq!(all.total, Some(7));
+
+        let mut paged = Vec::new();
+        let mut cursor = None;
+        loop {
+            let Json(page) = list::execute(
+                state.clone(),
+                Json(list::Input {
+                    sorts: sorts.clone(),
+                    limit: 3,
+                    cursor: cursor.take(),
+                    with_total: false,
+                    ..Default::default()
+                }),
+            )
+            .await
+            .expect("To list a page");
+            assert!(page.data.len() <= 3);
+            assert_eq!(page.total, None);
+            paged.extend(page.data.into_iter().map(|t| t.tag));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(
+            paged,
+            all.data.into_iter().map(|t| t.tag).collect::<Vec<_>>()
+        );
+    }
+
+    assert!(list::execute(
+        state.clone(),
+        Json(list::Input {
+            cursor: Some("garbage".to_string()),
+            ..Default::default()
+        }),
+    )
+    .await
+    .is_err());
+}
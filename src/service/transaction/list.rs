@@ -6,7 +6,9 @@ use serde::{Deserialize, Serialize};
 use super::model::Transaction;
 use crate::bind_sqlite_args;
 use crate::service;
-use crate::service::query::create_paginated_query;
+use crate::service::account_group::input_accounts_sql;
+use crate::service::filter::Filter;
+use crate::service::query::{create_paginated_query, Page, PageRequest};
 use crate::service::SortOrder;
 use crate::service::ToSQL;
 use crate::state::AppState;
@@ -29,6 +31,7 @@ pub type Sort = service::Sort<SortField>;
 #[serde(rename_all = "camelCase")]
 pub struct Input {
     pub q: Option<String>,
+    pub query: Option<String>,
     pub from: Option<NaiveDate>,
     pub to: Option<NaiveDate>,
     #[serde(default = "default_limit")]
@@ -36,6 +39,9 @@ pub struct Input {
 
     #[serde(default)]
     pub offset: i64,
+    pub cursor: Option<String>,
+    #[serde(default = "default_with_total")]
+    pub with_total: bool,
     #[serde(default = "default_sorts")]
     pub sorts: Cow<'static, [Sort]>,
 
@@ -57,14 +63,21 @@ const fn default_sorts() -> Cow<'static, [Sort]> {
     Cow::Borrowed(DEFAULT_SORTS)
 }
 
+const fn default_with_total() -> bool {
+    true
+}
+
 impl Default for Input {
     fn default() -> Self {
         Self {
             q: Default::default(),
+            query: Default::default(),
             from: Default::default(),
             to: Default::default(),
             limit: default_limit(),
             offset: Default::default(),
+            cursor: Default::default(),
+            with_total: default_with_total(),
             sorts: default_sorts(),
             accounts: Default::default(),
             tags: Default::default(),
@@ -88,12 +101,16 @@ impl ToSQL for SortField {
 #[derive(Serialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Output {
-    pub total: i64,
-    pub amount_total: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_total: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
     pub data: Vec<Transaction>,
 }
 
-const SQL: &'static str = r#"
+const SQL: &str = r#"
     select t.*,
         (select json_group_array(attachmentId) from transaction_attachments where transactionId = t.id) as attachments,
         (select json_group_array(tag) from transaction_tags where transactionId = t.id) as tags
@@ -101,22 +118,15 @@ const SQL: &'static str = r#"
     where
     (
         (ifnull(json_array_length(?4), 0) == 0 and ifnull(json_array_length(?6), 0) == 0)
-        or trim(t.fromAccount) in (
-            select trim(value) from json_each(?4)
-            union
-            select ag.accountName from json_each(?6) g inner join account_groups ag on ag.groupName = trim(g.value) collate nocase
-        ) collate nocase
-        or trim(t.toAccount) in (
-            select trim(value) from json_each(?4)
-            union
-            select ag.accountName from json_each(?6) g inner join account_groups ag on ag.groupName = trim(g.value) collate nocase
-        ) collate nocase
+        or trim(t.fromAccount) collate nocase in (select account from input_accounts)
+        or trim(t.toAccount) collate nocase in (select account from input_accounts)
     )
     and (
         ifnull(json_array_length(?5), 0) == 0
         or t.id in (
-            select transactionId from transaction_tags
-            inner join json_each(?5) tags on tags.value = tag collate nocase
+            select tt.transactionId from transaction_tags tt
+            inner join tag_ancestors ta on ta.tag = tt.tag
+            where ta.ancestor collate nocase in (select trim(value) from json_each(?5))
         )
     )
     and (?1 is null or ?1 = '' or t.description like '%' || ?1 || '%' collate nocase)
@@ -128,28 +138,49 @@ pub async fn execute(
     state: State<AppState>,
     Json(input): Json<Input>,
 ) -> super::super::Result<Json<Output>> {
-    let (data, (total, amount_total)) = create_paginated_query(
+    let mut args = bind_sqlite_args!(
+        &input.q,
+        &input.from,
+        &input.to,
+        &input.accounts,
+        &input.tags,
+        &input.account_groups
+    );
+    let filter = Filter::compile(
+        input.query.as_deref().unwrap_or_default(),
+        args.next_index(),
+    )?;
+    let sql = format!(
+        "with input_accounts as ({}) {SQL} and ({})",
+        input_accounts_sql(4, 6, "0"),
+        filter.sql()
+    );
+    filter.bind(&mut args);
+
+    let Page {
+        items,
+        aggregates,
+        next_cursor,
+    } = create_paginated_query::<_, _, (i64, Option<i64>)>(
         &state.conn,
-        SQL,
-        bind_sqlite_args!(
-            &input.q,
-            &input.from,
-            &input.to,
-            &input.accounts,
-            &input.tags,
-            &input.account_groups
-        ),
-        input.limit,
-        input.offset,
-        &input.sorts,
-        "COUNT(*), SUM(amount)",
+        &sql,
+        args,
+        PageRequest {
+            limit: input.limit,
+            offset: input.offset,
+            cursor: input.cursor.as_deref(),
+            sorts: &input.sorts,
+            key: "id",
+            aggregate_select: input.with_total.then_some("COUNT(*), SUM(amount)"),
+        },
     )
     .await?;
 
     Ok(Output {
-        data,
-        total,
-        amount_total,
+        data: items,
+        total: aggregates.map(|(total, _)| total),
+        amount_total: aggregates.map(|(_, amount_total)| amount_total.unwrap_or_default()),
+        next_cursor,
     }
     .into())
 }
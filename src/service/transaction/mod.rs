@@ -2,10 +2,11 @@ use axum::{routing::post, Router};
 
 use crate::state::AppState;
 
-mod delete;
-mod list;
+pub mod delete;
+pub mod list;
 pub mod model;
 pub mod save;
+pub mod suggest;
 
 #[cfg(test)]
 pub mod test;
@@ -16,6 +17,7 @@ pub fn router() -> Router<AppState> {
         Router::new()
             .route("/", post(save::execute))
             .route("/", axum::routing::delete(delete::execute))
-            .route("/list", post(list::execute)),
+            .route("/list", post(list::execute))
+            .route("/suggest", post(suggest::execute)),
     )
 }
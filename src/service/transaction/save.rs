@@ -1,9 +1,10 @@
 use crate::{
-    service::{GenericUpdateResponse, Result},
+    service::{rule, GenericUpdateResponse, Result},
     state::AppState,
 };
 
 use axum::extract::{Json, State};
+use sqlx::Sqlite;
 
 use super::model::Transaction;
 
@@ -14,12 +15,13 @@ insert into
 values (?, ?, ?, ?, ?, ?, ?, ?, ?)
 "#;
 
-pub async fn execute(
-    state: State<AppState>,
-    Json(transactions): Json<Vec<Transaction>>,
-) -> Result<Json<GenericUpdateResponse>> {
+/// Saves the transactions and runs the enabled rules over them, returning how many were saved.
+pub async fn save(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    transactions: Vec<Transaction>,
+) -> Result<usize> {
     let mut num_affected: usize = 0;
-    let mut tx = state.conn.begin().await?;
+    let mut ids = Vec::with_capacity(transactions.len());
 
     for Transaction {
         id,
@@ -33,6 +35,7 @@ pub async fn execute(
         tags,
     } in transactions
     {
+        ids.push(id.clone());
         num_affected += sqlx::query(INSERT_SQL)
             .bind(id)
             .bind(description)
@@ -43,11 +46,28 @@ pub async fn execute(
             .bind(updated_date)
             .bind(attachments)
             .bind(tags)
-            .execute(&mut *tx)
+            .execute(&mut **tx)
             .await?
             .rows_affected() as usize;
     }
 
+    if !ids.is_empty() {
+        let scope = rule::apply::Scope {
+            ids: Some(&ids),
+            ..Default::default()
+        };
+        rule::apply::apply_all(tx, None, &scope).await?;
+    }
+
+    Ok(num_affected)
+}
+
+pub async fn execute(
+    state: State<AppState>,
+    Json(transactions): Json<Vec<Transaction>>,
+) -> Result<Json<GenericUpdateResponse>> {
+    let mut tx = state.conn.begin().await?;
+    let num_affected = save(&mut tx, transactions).await?;
     tx.commit().await?;
     Ok(GenericUpdateResponse { num_affected }.into())
 }
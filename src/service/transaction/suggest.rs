@@ -0,0 +1,267 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use axum::extract::{Json, State};
+
+use crate::{
+    service::{Error, Result},
+    sqlx_ext,
+    state::AppState,
+};
+
+/// How many of the latest transactions to learn from.
+const HISTORY_SIZE: i64 = 10000;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    pub description: String,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+const fn default_limit() -> usize {
+    5
+}
+
+#[derive(serde::Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Suggestion {
+    pub value: String,
+    /// The estimated probability, between 0 and 1, that the suggestion is right.
+    pub confidence: f64,
+}
+
+#[derive(serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Output {
+    pub from_accounts: Vec<Suggestion>,
+    pub to_accounts: Vec<Suggestion>,
+    pub tags: Vec<Suggestion>,
+}
+
+/// Splits a description into lowercase words, ignoring numbers such as dates and references.
+pub fn tokenise(description: &str) -> HashSet<String> {
+    description
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() > 1 && !w.chars().all(|c| c.is_numeric()))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[derive(Default)]
+struct Counts {
+    docs: usize,
+    /// Occurrences of each token, counted once per description.
+    tokens: HashMap<String, usize>,
+    total_tokens: usize,
+}
+
+impl Counts {
+    fn add(&mut self, tokens: &HashSet<String>) {
+        self.docs += 1;
+        self.total_tokens += tokens.len();
+        for token in tokens {
+            *self.tokens.entry(token.clone()).or_default() += 1;
+        }
+    }
+
+    /// The log likelihood of seeing `tokens` in this class, with add-one smoothing.
+    fn log_likelihood(&self, tokens: &[&String], vocab_size: usize) -> f64 {
+        let denominator = (self.total_tokens + vocab_size) as f64;
+        tokens
+            .iter()
+            .map(|t| {
+                ((self.tokens.get(*t).copied().unwrap_or_default() + 1) as f64 / denominator).ln()
+            })
+            .sum()
+    }
+}
+
+/// A multinomial naive Bayes classifier over description tokens.
+#[derive(Default)]
+pub struct Classifier {
+    all: Counts,
+    classes: HashMap<String, Counts>,
+}
+
+impl Classifier {
+    pub fn add(&mut self, tokens: &HashSet<String>, labels: impl IntoIterator<Item = String>) {
+        self.all.add(tokens);
+        for label in labels {
+            self.classes.entry(label).or_default().add(tokens);
+        }
+    }
+
+    /// The tokens seen in training, as the others tell nothing about the classes.
+    fn known<'a>(&self, tokens: &'a HashSet<String>) -> Vec<&'a String> {
+        tokens
+            .iter()
+            .filter(|t| self.all.tokens.contains_key(*t))
+            .collect()
+    }
+
+    /// Ranks the classes for a description where each one has exactly one class.
+    pub fn rank(&self, tokens: &HashSet<String>) -> Vec<Suggestion> {
+        let tokens = self.known(tokens);
+        if tokens.is_empty() {
+            return Default::default();
+        }
+
+        let vocab_size = self.all.tokens.len();
+        let scores: Vec<(&String, f64)> = self
+            .classes
+            .iter()
+            .map(|(label, counts)| {
+                let prior = (counts.docs as f64 / self.all.docs as f64).ln();
+                (label, prior + counts.log_likelihood(&tokens, vocab_size))
+            })
+            .collect();
+
+        // Softmax, shifted by the highest score so the exponentials don't underflow.
+        let max = scores.iter().map(|(_, s)| *s).fold(f64::MIN, f64::max);
+        let total: f64 = scores.iter().map(|(_, s)| (s - max).exp()).sum();
+        sorted(
+            scores
+                .into_iter()
+                .map(|(label, s)| Suggestion {
+                    value: label.clone(),
+                    confidence: (s - max).exp() / total,
+                })
+                .collect(),
+        )
+    }
+
+    /// Scores each class on its own, for descriptions which may have any number of classes.
+    pub fn rank_independently(&self, tokens: &HashSet<String>) -> Vec<Suggestion> {
+        let tokens = self.known(tokens);
+        if tokens.is_empty() {
+            return Default::default();
+        }
+
+        let vocab_size = self.all.tokens.len();
+        sorted(
+            self.classes
+                .iter()
+                .filter(|(_, with)| with.docs < self.all.docs)
+                .map(|(label, with)| {
+                    let without = Counts {
+                        docs: self.all.docs - with.docs,
+                        tokens: self
+                            .all
+                            .tokens
+                            .iter()
+                            .map(|(t, n)| {
+                                (
+                                    t.clone(),
+                                    n - with.tokens.get(t).copied().unwrap_or_default(),
+                                )
+                            })
+                            .collect(),
+                        total_tokens: self.all.total_tokens - with.total_tokens,
+                    };
+                    let score = |c: &Counts| {
+                        (c.docs as f64 / self.all.docs as f64).ln()
+                            + c.log_likelihood(&tokens, vocab_size)
+                    };
+                    Suggestion {
+                        value: label.clone(),
+                        confidence: 1.0 / (1.0 + (score(&without) - score(with)).exp()),
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+fn sorted(mut suggestions: Vec<Suggestion>) -> Vec<Suggestion> {
+    suggestions.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then_with(|| a.value.cmp(&b.value))
+    });
+    suggestions
+}
+
+/// What is learnt from the transactions, one classifier for each field suggested.
+#[derive(Default)]
+struct Classifiers {
+    from_accounts: Classifier,
+    to_accounts: Classifier,
+    tags: Classifier,
+}
+
+/// The [`Classifiers`] last learnt, with the `transactions_version` they were learnt at, so they
+/// are only learnt again once transactions change.
+#[derive(Default)]
+pub struct Cache(Mutex<Option<(i64, Arc<Classifiers>)>>);
+
+//language=sql
+const VERSION_SQL: &str = "select version from transactions_version";
+
+//language=sql
+const SQL: &str = r#"
+select description, trim(fromAccount), trim(toAccount), tags
+from transactions_view
+order by transDate desc, updatedDate desc
+limit ?
+"#;
+
+/// The classifiers for the current transactions, from the cache when they haven't changed.
+async fn classifiers(state: &AppState) -> Result<Arc<Classifiers>> {
+    let (version,): (i64,) = sqlx::query_as(VERSION_SQL).fetch_one(&state.conn).await?;
+    if let Some((_, classifiers)) = state
+        .suggest_cache
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .filter(|(v, _)| *v == version)
+    {
+        return Ok(classifiers.clone());
+    }
+
+    let history: Vec<(String, String, String, sqlx_ext::Json<Vec<String>>)> = sqlx::query_as(SQL)
+        .bind(HISTORY_SIZE)
+        .fetch_all(&state.conn)
+        .await?;
+    let mut classifiers = Classifiers::default();
+    for (description, from, to, tx_tags) in history {
+        let tx_tokens = tokenise(&description);
+        classifiers.from_accounts.add(&tx_tokens, [from]);
+        classifiers.to_accounts.add(&tx_tokens, [to]);
+        classifiers.tags.add(&tx_tokens, tx_tags.0);
+    }
+
+    let classifiers = Arc::new(classifiers);
+    *state
+        .suggest_cache
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = Some((version, classifiers.clone()));
+    Ok(classifiers)
+}
+
+/// Suggests accounts and tags for a description, learnt from past transactions.
+pub async fn execute(
+    state: State<AppState>,
+    Json(Input { description, limit }): Json<Input>,
+) -> Result<Json<Output>> {
+    if description.trim().is_empty() {
+        return Err(Error::InvalidArgument("Description is empty".into()));
+    }
+    let tokens = tokenise(&description);
+    let classifiers = classifiers(&state).await?;
+
+    let top = |mut v: Vec<Suggestion>| {
+        v.truncate(limit);
+        v
+    };
+    Ok(Json::from(Output {
+        from_accounts: top(classifiers.from_accounts.rank(&tokens)),
+        to_accounts: top(classifiers.to_accounts.rank(&tokens)),
+        tags: top(classifiers.tags.rank_independently(&tokens)),
+    }))
+}
@@ -58,7 +58,7 @@ async fn transaction_works() {
     let extract::Json(rs) = list::execute(state.clone(), Default::default())
         .await
         .expect("To list");
-    assert_eq!(rs.total, 1);
+    assert_eq!(rs.total, Some(1));
     assert_eq!(
         normalise_transaction(
             rs.data
@@ -73,9 +73,244 @@ async fn transaction_works() {
     let extract::Json(rs) = list::execute(state.clone(), Default::default())
         .await
         .expect("To list");
-    assert_eq!(rs.total, 2);
+    assert_eq!(rs.total, Some(2));
     assert_eq!(
         normalise_transaction_list(vec![tx2, tx3]),
         normalise_transaction_list(rs.data)
     );
 }
+
+#[tokio::test]
+async fn list_by_query_works() {
+    let state = State(AppState::new_test().await);
+
+    let new_tx = |id: &str, desc: &str, to: &str, amount: i64, date: &str, tags: &[&str]| {
+        model::Transaction {
+            id: id.to_string(),
+            description: desc.to_string(),
+            from_account: "Bank".to_string(),
+            to_account: to.to_string(),
+            amount,
+            trans_date: date.to_string(),
+            updated_date: DateTime::from(SystemTime::now()),
+            attachments: Json(vec![]),
+            tags: Json(tags.iter().map(|t| t.to_string()).collect()),
+        }
+    };
+
+    let _ = save::execute(
+        state.clone(),
+        vec![
+            new_tx(
+                "1",
+                "Uber trip",
+                "Transport",
+                2500,
+                "2024-01-10",
+                &["holiday"],
+            ),
+            new_tx(
+                "2",
+                "Countdown",
+                "Groceries",
+                15000,
+                "2024-02-03",
+                &["holiday", "work"],
+            ),
+            new_tx(
+                "3",
+                "Countdown",
+                "Groceries",
+                12000,
+                "2024-03-31",
+                &["holiday"],
+            ),
+            new_tx("4", "New World", "Groceries", 9000, "2024-04-01", &[]),
+        ]
+        .into(),
+    )
+    .await
+    .expect("To save");
+
+    let query = |q: &str| {
+        let state = state.clone();
+        let q = q.to_string();
+        async move {
+            list::execute(
+                state,
+                list::Input {
+                    query: Some(q),
+                    ..Default::default()
+                }
+                .into(),
+            )
+            .await
+            .map(|rs| rs.0.data.into_iter().map(|tx| tx.id).sorted().collect_vec())
+        }
+    };
+
+    assert_eq!(
+        query(r#"amount>100 account:"groceries" tag:holiday -tag:work date:2024-01..2024-03"#)
+            .await
+            .expect("To list"),
+        vec!["3".to_string()]
+    );
+    assert_eq!(
+        query("desc:~uber").await.expect("To list"),
+        vec!["1".to_string()]
+    );
+    assert_eq!(
+        query("countdown amount<=120").await.expect("To list"),
+        vec!["3".to_string()]
+    );
+    assert!(query("amount>abc").await.is_err());
+}
+
+#[tokio::test]
+async fn list_by_cursor_works() {
+    let state = State(AppState::new_test().await);
+
+    let mut ids = Vec::new();
+    for day in 1..=7 {
+        let tx = model::Transaction {
+            trans_date: format!("2020-01-{:02}", day % 3 + 1),
+            ..new_transaction(state.clone(), None).await
+        };
+        let _ = save::execute(state.clone(), vec![tx.clone()].into())
+            .await
+            .expect("To save");
+        ids.push(tx.id);
+    }
+
+    let extract::Json(all) = list::execute(state.clone(), Default::default())
+        .await
+        .expect("To list");
+
+    let mut paged = Vec::new();
+    let mut cursor = None;
+    loop {
+        let extract::Json(rs) = list::execute(
+            state.clone(),
+            list::Input {
+                limit: 3,
+                cursor: cursor.take(),
+                with_total: false,
+                ..Default::default()
+            }
+            .into(),
+        )
+        .await
+        .expect("To list a page");
+
+        assert_eq!(rs.total, None);
+        paged.extend(rs.data.into_iter().map(|tx| tx.id));
+        match rs.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    assert_eq!(paged, all.data.into_iter().map(|tx| tx.id).collect_vec());
+    assert_eq!(
+        paged.iter().sorted().collect_vec(),
+        ids.iter().sorted().collect_vec()
+    );
+
+    assert!(list::execute(
+        state.clone(),
+        list::Input {
+            cursor: Some("garbage".to_string()),
+            ..Default::default()
+        }
+        .into(),
+    )
+    .await
+    .is_err());
+}
+
+#[tokio::test]
+async fn suggest_works() {
+    let state = State(AppState::new_test().await);
+
+    let new_tx = |id: &str, desc: &str, to: &str, tags: &[&str]| model::Transaction {
+        id: id.to_string(),
+        description: desc.to_string(),
+        from_account: "Bank".to_string(),
+        to_account: to.to_string(),
+        amount: 100,
+        trans_date: "2024-01-01".to_string(),
+        updated_date: DateTime::from(SystemTime::now()),
+        attachments: Json(vec![]),
+        tags: Json(tags.iter().map(|t| t.to_string()).collect()),
+    };
+
+    let _ = save::execute(
+        state.clone(),
+        vec![
+            new_tx("1", "Countdown Ponsonby 1234", "Groceries", &["food"]),
+            new_tx("2", "COUNTDOWN Newmarket", "Groceries", &["food"]),
+            new_tx("3", "New World Victoria Park", "Groceries", &["food"]),
+            new_tx("4", "Uber trip", "Transport", &["work"]),
+            new_tx("5", "Uber trip to airport", "Transport", &[]),
+            new_tx("6", "Z Energy Ponsonby", "Transport", &["car"]),
+        ]
+        .into(),
+    )
+    .await
+    .expect("To save");
+
+    let suggest = |description: &str| {
+        let state = state.clone();
+        let input = suggest::Input {
+            description: description.to_string(),
+            limit: 5,
+        };
+        async move {
+            suggest::execute(state, input.into())
+                .await
+                .expect("To suggest")
+                .0
+        }
+    };
+
+    let output = suggest("countdown 2024-03-01").await;
+    assert_eq!(output.to_accounts[0].value, "Groceries");
+    assert!(output.to_accounts[0].confidence > 0.7);
+    assert_eq!(output.from_accounts[0].value, "Bank");
+    assert_eq!(output.tags[0].value, "food");
+    assert!(output.tags[0].confidence > 0.5);
+
+    let output = suggest("Uber").await;
+    assert_eq!(output.to_accounts[0].value, "Transport");
+    assert!(output
+        .tags
+        .iter()
+        .all(|t| t.value != "food" || t.confidence < 0.5));
+
+    let output = suggest("Something else").await;
+    assert!(output.to_accounts.is_empty());
+    assert!(output.tags.is_empty());
+
+    // What is learnt is kept only until transactions change.
+    assert!(suggest("Netflix").await.to_accounts.is_empty());
+    let _ = save::execute(
+        state.clone(),
+        vec![new_tx("7", "Netflix", "Entertainment", &["fun"])].into(),
+    )
+    .await
+    .expect("To save");
+    let output = suggest("Netflix").await;
+    assert_eq!(output.to_accounts[0].value, "Entertainment");
+    assert_eq!(output.tags[0].value, "fun");
+
+    assert!(suggest::execute(
+        state.clone(),
+        suggest::Input {
+            description: " ".to_string(),
+            limit: 5,
+        }
+        .into()
+    )
+    .await
+    .is_err());
+}
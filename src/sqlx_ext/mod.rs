@@ -1,4 +1,3 @@
 mod utils;
 
 pub use sqlx::types::Json;
-pub use utils::*;
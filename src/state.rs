@@ -1,20 +1,37 @@
+use std::sync::Arc;
+
 use sqlx::SqlitePool;
 
+use crate::service::transaction::suggest;
+
 #[derive(Clone)]
 pub struct AppState {
     pub conn: SqlitePool,
     pub port: u16,
+    pub suggest_cache: Arc<suggest::Cache>,
 }
 
 #[cfg(test)]
 impl AppState {
     pub async fn new_test() -> AppState {
-        let conn = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        use std::str::FromStr;
+
+        let conn = sqlx::SqlitePool::connect_with(
+            sqlx::sqlite::SqliteConnectOptions::from_str("sqlite::memory:")
+                .unwrap()
+                .with_regexp(),
+        )
+        .await
+        .unwrap();
         sqlx::migrate!()
             .run(&conn)
             .await
             .expect("Migration to run successfully");
 
-        AppState { conn, port: 4000 }
+        AppState {
+            conn,
+            port: 4000,
+            suggest_cache: Default::default(),
+        }
     }
 }